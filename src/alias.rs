@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Magic bytes at the start of bookmark data, which is what Finder writes into
+/// the data fork of an alias file.
+const BOOKMARK_MAGIC: &[u8] = b"book";
+/// Marks a table of contents inside bookmark data.
+const TOC_MAGIC: u32 = 0xffff_fffe;
+/// Bookmark item holding the target's path components.
+const KEY_PATH: u32 = 0x1004;
+const TYPE_STRING: u32 = 0x0101;
+const TYPE_ARRAY: u32 = 0x0601;
+
+/// Resolves a Finder alias file to the path it points at.
+///
+/// Only the path recorded in the bookmark is used, so an alias whose target has
+/// been moved since it was created resolves to the old location.
+pub fn resolve(path: &Path) -> Option<PathBuf> {
+    let data = fs::read(path).ok()?;
+    if !data.starts_with(BOOKMARK_MAGIC) {
+        return None;
+    }
+    target_path(&data)
+}
+
+fn target_path(data: &[u8]) -> Option<PathBuf> {
+    let header_size = read_u32(data, 12)? as usize;
+    let body = data.get(header_size..)?;
+
+    let toc = read_u32(body, 0)? as usize;
+    if read_u32(body, toc + 4)? != TOC_MAGIC {
+        return None;
+    }
+    let count = read_u32(body, toc + 16)? as usize;
+
+    let path_item = (0..count)
+        .map(|index| toc + 20 + index * 12)
+        .find(|&entry| read_u32(body, entry) == Some(KEY_PATH))
+        .and_then(|entry| read_u32(body, entry + 4))? as usize;
+
+    let (item_type, components) = read_item(body, path_item)?;
+    if item_type != TYPE_ARRAY {
+        return None;
+    }
+
+    let mut target = PathBuf::from("/");
+    for offset in components.chunks_exact(4) {
+        let offset = u32::from_le_bytes(offset.try_into().ok()?) as usize;
+        let (item_type, component) = read_item(body, offset)?;
+        if item_type != TYPE_STRING {
+            return None;
+        }
+        target.push(std::str::from_utf8(component).ok()?);
+    }
+    Some(target)
+}
+
+/// Reads the item at `offset`: a length and type followed by its data.
+fn read_item(body: &[u8], offset: usize) -> Option<(u32, &[u8])> {
+    let length = read_u32(body, offset)? as usize;
+    let item_type = read_u32(body, offset + 4)?;
+    let data = body.get(offset + 8..offset + 8 + length)?;
+    Some((item_type, data))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds minimal bookmark data holding only a path.
+    fn bookmark(components: &[&str]) -> Vec<u8> {
+        let mut body = vec![0u8; 4];
+        let mut offsets = Vec::new();
+        for component in components {
+            offsets.push(body.len() as u32);
+            body.extend((component.len() as u32).to_le_bytes());
+            body.extend(TYPE_STRING.to_le_bytes());
+            body.extend(component.as_bytes());
+            body.resize(body.len().next_multiple_of(4), 0);
+        }
+
+        let array = body.len() as u32;
+        body.extend((offsets.len() as u32 * 4).to_le_bytes());
+        body.extend(TYPE_ARRAY.to_le_bytes());
+        for offset in offsets {
+            body.extend(offset.to_le_bytes());
+        }
+
+        let toc = body.len() as u32;
+        body[0..4].copy_from_slice(&toc.to_le_bytes());
+        for value in [20 + 12, TOC_MAGIC, 1, 0, 1, KEY_PATH, array, 0] {
+            body.extend(value.to_le_bytes());
+        }
+
+        let mut data = BOOKMARK_MAGIC.to_vec();
+        let total = 48 + body.len() as u32;
+        for value in [total, 0x1004_0000, 48] {
+            data.extend(value.to_le_bytes());
+        }
+        data.resize(48, 0);
+        data.extend(body);
+        data
+    }
+
+    #[test]
+    fn test_target_path() {
+        let data = bookmark(&["nix", "store", "abc-firefox", "Firefox.app"]);
+        assert_eq!(
+            target_path(&data),
+            Some(PathBuf::from("/nix/store/abc-firefox/Firefox.app"))
+        );
+    }
+
+    #[test]
+    fn test_target_path_invalid() {
+        let data = bookmark(&["Applications", "Firefox.app"]);
+        assert!(target_path(&data[..data.len() - 8]).is_none());
+        assert!(target_path(b"book").is_none());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let path = std::env::temp_dir().join(format!("bintrim-alias-{}", std::process::id()));
+        fs::write(&path, bookmark(&["Volumes", "Apps", "Slack.app"])).unwrap();
+        let alias = resolve(&path);
+        fs::write(&path, b"not an alias").unwrap();
+        let not_alias = resolve(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(alias, Some(PathBuf::from("/Volumes/Apps/Slack.app")));
+        assert!(not_alias.is_none());
+    }
+}
@@ -0,0 +1,874 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::confirm::Confirmation;
+use crate::filter::AppFilter;
+use crate::i18n::t;
+use crate::scanner::{AppInfo, Badge};
+use crate::trimmer::Strategy;
+
+/// Screen or popup the TUI is showing, which decides what keys do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppState {
+    Loading,
+    Ready,
+    PopupNoSelection,
+    PopupConfirmManaged,
+    /// Asks whether to trim, at the `normal` confirmation level
+    PopupConfirmTrim,
+    /// Asks to type `trim` or the number of apps, at the `strict` level
+    PopupConfirmTyped,
+    PopupPasswordInput,
+    PopupDiagnostics,
+    PopupError,
+    PopupColumns,
+    PopupBreakdown,
+    PopupVendors,
+    PopupDeveloper,
+    PopupFilter,
+    PopupSavings,
+    PopupLegend,
+    Trimming,
+}
+
+/// What a key press asks the app to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    MoveDown,
+    MoveUp,
+    /// Jump to the next selected app
+    NextSelected,
+    PreviousSelected,
+    ToggleSelected,
+    ToggleAll,
+    /// Restore the selection from before the last toggle
+    Undo,
+    ToggleVisibility,
+    ToggleSort,
+    ToggleSummary,
+    /// Ask for confirmation or the password, whichever comes first
+    StartTrim,
+    ShowDiagnostics,
+    ShowColumns,
+    /// Show where the space in the app under the cursor goes
+    ShowBreakdown,
+    /// Show the prunable apps grouped by vendor
+    ShowVendors,
+    /// Show simulator runtimes and other developer files outside of apps
+    ShowDeveloper,
+    /// Only show the next category of apps, or all of them again
+    CycleCategory,
+    /// Open the filter prompt with the current filter
+    EditFilter,
+    TypeFilter(char),
+    DeleteFilterChar,
+    /// Show only the apps matching the typed filter
+    ApplyFilter,
+    /// Move the cursor in the column chooser
+    ColumnDown,
+    ColumnUp,
+    /// Show or hide the column under the cursor
+    ToggleColumn,
+    /// Reopen the errors of the last trim, if there were any
+    ShowErrors,
+    /// Copy the selected apps, or else the visible ones, to the clipboard
+    CopyList,
+    /// Compare what the last trim freed with the estimates
+    ShowSavings,
+    /// Switch the app under the cursor to the next trim strategy
+    CycleStrategy,
+    /// Explain the symbols of the badges column
+    ShowLegend,
+    /// Close the current popup and go back to the app list
+    Close,
+    /// Scan again after a worker thread crashed
+    Retry,
+    /// Stop the running scan or trim at the next app or binary
+    Cancel,
+    ScrollDown,
+    ScrollUp,
+    /// Trim even though managed apps are selected
+    ConfirmManaged,
+    /// Go on to the password after the confirmation popup
+    ConfirmTrim,
+    TypeConfirm(char),
+    DeleteConfirmChar,
+    TypePassword(char),
+    DeletePasswordChar,
+    ClearPassword,
+    /// Use sudo's own prompt instead of the popup
+    TerminalPrompt,
+    /// Trim with the typed password, unless it's empty
+    SubmitPassword,
+    CancelPassword,
+}
+
+impl Action {
+    /// Does the action lead to changing apps on disk? These do nothing in audit mode.
+    pub fn modifies(self) -> bool {
+        matches!(self, Action::StartTrim | Action::CycleStrategy)
+    }
+}
+
+/// Maps a key press to an action in `state`, `None` if the key does nothing there.
+pub fn handle_key(state: AppState, key: &KeyEvent) -> Option<Action> {
+    let action = match state {
+        AppState::Ready => match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q'))
+            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => Action::Quit,
+            (_, KeyCode::Down | KeyCode::Char('j')) => Action::MoveDown,
+            (_, KeyCode::Up | KeyCode::Char('k')) => Action::MoveUp,
+            (_, KeyCode::Char('n')) => Action::NextSelected,
+            (_, KeyCode::Char('N')) => Action::PreviousSelected,
+            (_, KeyCode::Char(' ')) => Action::ToggleSelected,
+            (_, KeyCode::Char('a')) => Action::ToggleAll,
+            (_, KeyCode::Char('u')) => Action::Undo,
+            (_, KeyCode::Char('h')) => Action::ToggleVisibility,
+            (_, KeyCode::Char('s')) => Action::ToggleSort,
+            (_, KeyCode::Char('i')) => Action::ToggleSummary,
+            (_, KeyCode::Enter) => Action::StartTrim,
+            (_, KeyCode::Char('d')) => Action::ShowDiagnostics,
+            (_, KeyCode::Char('c')) => Action::ShowColumns,
+            (_, KeyCode::Char('b')) => Action::ShowBreakdown,
+            (_, KeyCode::Char('g')) => Action::ShowVendors,
+            (_, KeyCode::Char('x')) => Action::ShowDeveloper,
+            (_, KeyCode::Char('f')) => Action::CycleCategory,
+            (_, KeyCode::Char('/')) => Action::EditFilter,
+            (_, KeyCode::Char('e')) => Action::ShowErrors,
+            (_, KeyCode::Char('y')) => Action::CopyList,
+            (_, KeyCode::Char('t')) => Action::ShowSavings,
+            (_, KeyCode::Char('m')) => Action::CycleStrategy,
+            (_, KeyCode::Char('?')) => Action::ShowLegend,
+            _ => return None,
+        },
+        AppState::PopupError | AppState::PopupNoSelection => match key.code {
+            KeyCode::Enter | KeyCode::Esc => Action::Close,
+            KeyCode::Char('r') if state == AppState::PopupError => Action::Retry,
+            _ => return None,
+        },
+        AppState::PopupDiagnostics => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('d') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupBreakdown => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('b') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupLegend => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('?') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupVendors => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('g') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupDeveloper => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('x') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupSavings => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollUp,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('t') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupColumns => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::ColumnDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::ColumnUp,
+            KeyCode::Char(' ') => Action::ToggleColumn,
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('c') => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupConfirmManaged => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Action::ConfirmManaged,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupConfirmTrim => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Action::ConfirmTrim,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupConfirmTyped => match key.code {
+            KeyCode::Char(c) => Action::TypeConfirm(c),
+            KeyCode::Backspace => Action::DeleteConfirmChar,
+            KeyCode::Enter => Action::ConfirmTrim,
+            KeyCode::Esc => Action::Close,
+            _ => return None,
+        },
+        AppState::PopupPasswordInput => match (key.modifiers, key.code) {
+            (KeyModifiers::CONTROL, KeyCode::Char('u')) => Action::ClearPassword,
+            (KeyModifiers::CONTROL, KeyCode::Char('t')) => Action::TerminalPrompt,
+            (_, KeyCode::Char(c)) => Action::TypePassword(c),
+            (_, KeyCode::Backspace) => Action::DeletePasswordChar,
+            (_, KeyCode::Enter) => Action::SubmitPassword,
+            (_, KeyCode::Esc) => Action::CancelPassword,
+            _ => return None,
+        },
+        AppState::PopupFilter => match key.code {
+            KeyCode::Char(c) => Action::TypeFilter(c),
+            KeyCode::Backspace => Action::DeleteFilterChar,
+            KeyCode::Enter => Action::ApplyFilter,
+            KeyCode::Esc => Action::Close,
+            _ => return None,
+        },
+        AppState::Loading | AppState::Trimming => match key.code {
+            KeyCode::Esc => Action::Cancel,
+            _ => return None,
+        },
+    };
+    Some(action)
+}
+
+/// Which apps the list shows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListFilter<'a> {
+    /// Also show apps that can't be trimmed
+    pub show_all: bool,
+    /// Only show apps this filter matches
+    pub filter: Option<&'a AppFilter>,
+}
+
+/// Is `app` shown in the list? Apps that can't be trimmed are hidden unless `show_all`.
+pub fn is_visible(app: &AppInfo, filter: ListFilter) -> bool {
+    (filter.show_all || app.is_prunable()) && filter.filter.is_none_or(|filter| filter.matches(app))
+}
+
+/// Why the list hides an app, as counted in the note under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenReason {
+    Arm64Only,
+    /// Read-only or locked
+    NotWritable,
+    Excluded,
+    Protected,
+    /// iOS apps, Wine, copies and apps whose nested binaries are thin already
+    Other,
+}
+
+impl HiddenReason {
+    const ALL: [HiddenReason; 5] = [
+        HiddenReason::Arm64Only,
+        HiddenReason::NotWritable,
+        HiddenReason::Excluded,
+        HiddenReason::Protected,
+        HiddenReason::Other,
+    ];
+
+    /// Why `app` can't be trimmed, or `None` if it can.
+    pub fn of(app: &AppInfo) -> Option<Self> {
+        if app.is_prunable() {
+            return None;
+        }
+        if !app.has_x86_64() {
+            return Some(HiddenReason::Arm64Only);
+        }
+        Some(match app.badges.iter().find(|badge| badge.blocks_trim()) {
+            Some(Badge::ReadOnly | Badge::Immutable) => HiddenReason::NotWritable,
+            Some(Badge::Excluded) => HiddenReason::Excluded,
+            Some(Badge::Protected) => HiddenReason::Protected,
+            _ => HiddenReason::Other,
+        })
+    }
+
+    fn label(self, count: usize) -> String {
+        let strings = t();
+        match self {
+            HiddenReason::Arm64Only => (strings.hidden_arm64_only)(count),
+            HiddenReason::NotWritable => (strings.hidden_not_writable)(count),
+            HiddenReason::Excluded => (strings.hidden_excluded)(count),
+            HiddenReason::Protected => (strings.hidden_protected)(count),
+            HiddenReason::Other => (strings.hidden_other)(count),
+        }
+    }
+}
+
+/// One line such as `47 app(s) hidden (31 arm64-only, 9 not writable, 7 excluded)`
+/// about the apps that `filter` matches but hides because they can't be trimmed,
+/// the most common reason first. `None` if nothing is hidden that way.
+pub fn hidden_note(apps: &[AppInfo], filter: ListFilter) -> Option<String> {
+    if filter.show_all {
+        return None;
+    }
+    let reasons: Vec<_> = apps
+        .iter()
+        .filter(|app| filter.filter.is_none_or(|filter| filter.matches(app)))
+        .filter_map(HiddenReason::of)
+        .collect();
+    if reasons.is_empty() {
+        return None;
+    }
+    let mut counts: Vec<_> = HiddenReason::ALL
+        .into_iter()
+        .map(|reason| (reason, reasons.iter().filter(|&&r| r == reason).count()))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    // Stable, so equal counts keep the order of `ALL`
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let parts: Vec<_> = counts
+        .into_iter()
+        .map(|(reason, count)| reason.label(count))
+        .collect();
+    Some((t().hidden_note)(reasons.len(), &parts.join(", ")))
+}
+
+/// Index of the first visible app, or 0 if there is none.
+pub fn first_visible(apps: &[AppInfo], filter: ListFilter) -> usize {
+    apps.iter()
+        .position(|app| is_visible(app, filter))
+        .unwrap_or(0)
+}
+
+/// Index of the next (or previous) visible app after `current`, wrapping around.
+/// Stays on `current` if no other app is visible.
+pub fn step(apps: &[AppInfo], current: usize, filter: ListFilter, forward: bool) -> usize {
+    find_from(apps, current, forward, |app| is_visible(app, filter)).unwrap_or(current)
+}
+
+/// The strategy after `current` when cycling with `m`. Skipping is left to the
+/// config.
+pub fn next_strategy(current: Strategy) -> Strategy {
+    match current {
+        Strategy::LipoRemove => Strategy::KeepOnlyNative,
+        Strategy::KeepOnlyNative => Strategy::NestedOnly,
+        Strategy::NestedOnly | Strategy::Skip => Strategy::LipoRemove,
+    }
+}
+
+/// The category after `current` in alphabetical order of the apps' categories,
+/// `None` (all categories) after the last one.
+pub fn next_category(apps: &[AppInfo], current: Option<&str>) -> Option<String> {
+    let mut categories: Vec<&str> = apps
+        .iter()
+        .filter_map(|app| app.category.as_deref())
+        .collect();
+    categories.sort_unstable();
+    categories.dedup();
+    match current {
+        None => categories.first(),
+        Some(current) => categories.iter().find(|&&category| category > current),
+    }
+    .map(|category| category.to_string())
+}
+
+/// Index of the next (or previous) visible selected app after `current`,
+/// wrapping around to `current` itself. `None` if none is selected.
+pub fn step_selected(
+    apps: &[AppInfo],
+    current: usize,
+    filter: ListFilter,
+    forward: bool,
+) -> Option<usize> {
+    let matches = |app: &AppInfo| app.selected && is_visible(app, filter);
+    find_from(apps, current, forward, matches).or_else(|| {
+        apps.get(current)
+            .filter(|app| matches(app))
+            .map(|_| current)
+    })
+}
+
+/// First app after `current` in the given direction that matches, skipping `current`.
+fn find_from(
+    apps: &[AppInfo],
+    current: usize,
+    forward: bool,
+    matches: impl Fn(&AppInfo) -> bool,
+) -> Option<usize> {
+    let len = apps.len();
+    (1..len)
+        .map(|offset| {
+            if forward {
+                (current + offset) % len
+            } else {
+                (current + len - offset) % len
+            }
+        })
+        .find(|&index| matches(&apps[index]))
+}
+
+/// Selects every visible prunable app, or deselects them all if they already are.
+/// Apps the filter hides keep their selection.
+pub fn toggle_all(apps: &mut [AppInfo], filter: ListFilter) {
+    let toggled = |app: &AppInfo| app.is_prunable() && is_visible(app, filter);
+    let all_selected = apps
+        .iter()
+        .filter(|app| toggled(app))
+        .all(|app| app.selected);
+
+    for app in apps.iter_mut().filter(|app| toggled(app)) {
+        app.selected = !all_selected;
+    }
+}
+
+/// Does trimming go through `app`? Only if it's selected, prunable and shown,
+/// so that a filter keeps what it hides out of the trim.
+pub fn will_trim(app: &AppInfo, filter: ListFilter) -> bool {
+    app.selected && app.is_prunable() && is_visible(app, filter)
+}
+
+/// How many selection changes can be undone.
+const UNDO_LIMIT: usize = 20;
+
+/// Earlier selections, most recent last, so a stray `a` doesn't wipe a
+/// carefully picked selection. Stores bundle paths so sorting doesn't matter.
+#[derive(Debug, Default)]
+pub struct SelectionHistory {
+    snapshots: Vec<Vec<PathBuf>>,
+}
+
+impl SelectionHistory {
+    /// Remembers the current selection before changing it.
+    pub fn record(&mut self, apps: &[AppInfo]) {
+        if self.snapshots.len() == UNDO_LIMIT {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(
+            apps.iter()
+                .filter(|app| app.selected)
+                .map(|app| app.path.clone())
+                .collect(),
+        );
+    }
+
+    /// Restores the last recorded selection, `false` if there is none.
+    pub fn undo(&mut self, apps: &mut [AppInfo]) -> bool {
+        let Some(selected) = self.snapshots.pop() else {
+            return false;
+        };
+        for app in apps {
+            app.selected = selected.contains(&app.path);
+        }
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// The popup to show before trimming the selected apps, `None` if it can go ahead.
+/// The warning about managed apps comes first, whatever the confirmation level.
+pub fn trim_popup(
+    apps: &[AppInfo],
+    filter: ListFilter,
+    confirmation: Confirmation,
+) -> Option<AppState> {
+    let selected: Vec<_> = apps.iter().filter(|app| will_trim(app, filter)).collect();
+
+    if selected.is_empty() {
+        Some(AppState::PopupNoSelection)
+    } else if selected
+        .iter()
+        .any(|app| app.badges.contains(&Badge::Managed))
+    {
+        Some(AppState::PopupConfirmManaged)
+    } else {
+        confirm_popup(confirmation)
+    }
+}
+
+/// The popup that asks for confirmation at `confirmation`'s level, `None` if none
+/// is needed.
+pub fn confirm_popup(confirmation: Confirmation) -> Option<AppState> {
+    match confirmation {
+        Confirmation::None => None,
+        Confirmation::Normal => Some(AppState::PopupConfirmTrim),
+        Confirmation::Strict => Some(AppState::PopupConfirmTyped),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEventKind, KeyEventState};
+
+    fn key(modifiers: KeyModifiers, code: KeyCode) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    fn press(state: AppState, code: KeyCode) -> Option<Action> {
+        handle_key(state, &key(KeyModifiers::NONE, code))
+    }
+
+    fn apps() -> Vec<AppInfo> {
+        let arm64 = AppInfo::for_test("Notes", &[("arm64", None)]);
+        let universal = AppInfo::for_test("Slack", &[("x86_64", None), ("arm64", None)]);
+        let mut managed = AppInfo::for_test("Figma", &[("x86_64", None), ("arm64", None)]);
+        managed.badges.push(Badge::Managed);
+        vec![arm64, universal, managed]
+    }
+
+    #[test]
+    fn test_handle_key() {
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('q')),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            handle_key(
+                AppState::Ready,
+                &key(KeyModifiers::CONTROL, KeyCode::Char('c'))
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('j')),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            handle_key(
+                AppState::Ready,
+                &key(KeyModifiers::SHIFT, KeyCode::Char('N'))
+            ),
+            Some(Action::PreviousSelected)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Enter),
+            Some(Action::StartTrim)
+        );
+        assert_eq!(press(AppState::Ready, KeyCode::Char('z')), None);
+
+        assert_eq!(
+            press(AppState::PopupError, KeyCode::Esc),
+            Some(Action::Close)
+        );
+        assert_eq!(
+            press(AppState::PopupError, KeyCode::Char('r')),
+            Some(Action::Retry)
+        );
+        assert_eq!(press(AppState::PopupNoSelection, KeyCode::Char('r')), None);
+        assert_eq!(
+            press(AppState::PopupDiagnostics, KeyCode::Char('d')),
+            Some(Action::Close)
+        );
+        assert_eq!(
+            press(AppState::PopupConfirmManaged, KeyCode::Char('Y')),
+            Some(Action::ConfirmManaged)
+        );
+        assert_eq!(
+            press(AppState::PopupConfirmTrim, KeyCode::Enter),
+            Some(Action::ConfirmTrim)
+        );
+        assert_eq!(
+            press(AppState::PopupConfirmTyped, KeyCode::Char('y')),
+            Some(Action::TypeConfirm('y'))
+        );
+        assert_eq!(
+            press(AppState::PopupColumns, KeyCode::Char(' ')),
+            Some(Action::ToggleColumn)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('b')),
+            Some(Action::ShowBreakdown)
+        );
+        assert_eq!(
+            press(AppState::PopupBreakdown, KeyCode::Char('j')),
+            Some(Action::ScrollDown)
+        );
+        assert_eq!(
+            press(AppState::PopupVendors, KeyCode::Char('g')),
+            Some(Action::Close)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('x')),
+            Some(Action::ShowDeveloper)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('/')),
+            Some(Action::EditFilter)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('y')),
+            Some(Action::CopyList)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('?')),
+            Some(Action::ShowLegend)
+        );
+        assert_eq!(
+            press(AppState::PopupLegend, KeyCode::Char('?')),
+            Some(Action::Close)
+        );
+        assert_eq!(
+            press(AppState::Ready, KeyCode::Char('m')),
+            Some(Action::CycleStrategy)
+        );
+        // Typed into the prompt instead of quitting
+        assert_eq!(
+            press(AppState::PopupFilter, KeyCode::Char('q')),
+            Some(Action::TypeFilter('q'))
+        );
+        assert_eq!(
+            press(AppState::PopupFilter, KeyCode::Esc),
+            Some(Action::Close)
+        );
+        assert_eq!(press(AppState::Loading, KeyCode::Char('q')), None);
+        assert_eq!(
+            press(AppState::Trimming, KeyCode::Esc),
+            Some(Action::Cancel)
+        );
+    }
+
+    #[test]
+    fn test_handle_key_password() {
+        let state = AppState::PopupPasswordInput;
+        // Letters that are shortcuts elsewhere are part of the password here
+        assert_eq!(
+            press(state, KeyCode::Char('q')),
+            Some(Action::TypePassword('q'))
+        );
+        assert_eq!(
+            handle_key(state, &key(KeyModifiers::CONTROL, KeyCode::Char('u'))),
+            Some(Action::ClearPassword)
+        );
+        assert_eq!(
+            handle_key(state, &key(KeyModifiers::CONTROL, KeyCode::Char('t'))),
+            Some(Action::TerminalPrompt)
+        );
+        assert_eq!(
+            press(state, KeyCode::Backspace),
+            Some(Action::DeletePasswordChar)
+        );
+        assert_eq!(press(state, KeyCode::Esc), Some(Action::CancelPassword));
+    }
+
+    #[test]
+    fn test_step() {
+        let apps = apps();
+        let prunable = ListFilter::default();
+        let all = ListFilter {
+            show_all: true,
+            ..ListFilter::default()
+        };
+        assert_eq!(first_visible(&apps, prunable), 1);
+        assert_eq!(first_visible(&apps, all), 0);
+
+        assert_eq!(step(&apps, 1, prunable, true), 2);
+        // Wraps around, skipping the hidden arm64-only app
+        assert_eq!(step(&apps, 2, prunable, true), 1);
+        assert_eq!(step(&apps, 1, prunable, false), 2);
+        assert_eq!(step(&apps, 2, all, true), 0);
+        assert_eq!(step(&apps[..2], 1, prunable, true), 1);
+        assert_eq!(step(&[], 0, prunable, true), 0);
+    }
+
+    #[test]
+    fn test_hidden_note() {
+        let mut apps = apps();
+        let mut locked = AppInfo::for_test("Old", &[("x86_64", None), ("arm64", None)]);
+        locked.badges.push(Badge::Immutable);
+        apps.push(locked);
+        apps.push(AppInfo::for_test("Music", &[("arm64", None)]));
+        assert_eq!(HiddenReason::of(&apps[1]), None);
+        assert_eq!(HiddenReason::of(&apps[3]), Some(HiddenReason::NotWritable));
+
+        let strings = t();
+        assert_eq!(
+            hidden_note(&apps, ListFilter::default()),
+            Some((strings.hidden_note)(
+                3,
+                &format!(
+                    "{}, {}",
+                    (strings.hidden_arm64_only)(2),
+                    (strings.hidden_not_writable)(1)
+                )
+            ))
+        );
+        let all = ListFilter {
+            show_all: true,
+            ..ListFilter::default()
+        };
+        assert_eq!(hidden_note(&apps, all), None);
+        assert_eq!(hidden_note(&apps[1..3], ListFilter::default()), None);
+    }
+
+    #[test]
+    fn test_category_filter() {
+        let mut apps = apps();
+        apps[0].category = Some("public.app-category.productivity".to_string());
+        apps[1].category = Some("public.app-category.business".to_string());
+        apps[2].category = Some("public.app-category.productivity".to_string());
+
+        let business = next_category(&apps, None);
+        assert_eq!(business.as_deref(), Some("public.app-category.business"));
+        let productivity = next_category(&apps, business.as_deref());
+        assert_eq!(
+            productivity.as_deref(),
+            Some("public.app-category.productivity")
+        );
+        assert_eq!(next_category(&apps, productivity.as_deref()), None);
+
+        let app_filter = AppFilter {
+            category: productivity,
+            ..AppFilter::default()
+        };
+        let filter = ListFilter {
+            show_all: false,
+            filter: Some(&app_filter),
+        };
+        assert!(!is_visible(&apps[0], filter));
+        assert!(!is_visible(&apps[1], filter));
+        assert!(is_visible(&apps[2], filter));
+    }
+
+    #[test]
+    fn test_next_strategy() {
+        let mut strategy = Strategy::Skip;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            strategy = next_strategy(strategy);
+            seen.push(strategy);
+        }
+        assert_eq!(
+            seen,
+            [
+                Strategy::LipoRemove,
+                Strategy::KeepOnlyNative,
+                Strategy::NestedOnly,
+                Strategy::LipoRemove,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_selected() {
+        let mut apps = apps();
+        apps.push(AppInfo::for_test(
+            "Zoom",
+            &[("x86_64", None), ("arm64", None)],
+        ));
+        let all = ListFilter::default();
+        assert_eq!(step_selected(&apps, 0, all, true), None);
+
+        apps[1].selected = true;
+        assert_eq!(step_selected(&apps, 1, all, true), Some(1));
+        apps[3].selected = true;
+        assert_eq!(step_selected(&apps, 1, all, true), Some(3));
+        assert_eq!(step_selected(&apps, 3, all, true), Some(1));
+        assert_eq!(step_selected(&apps, 0, all, false), Some(3));
+        assert_eq!(step_selected(&apps, 2, all, false), Some(1));
+
+        // Selected apps the filter hides are skipped
+        let zoom = AppFilter::parse("zoom").unwrap();
+        let filtered = ListFilter {
+            filter: Some(&zoom),
+            ..ListFilter::default()
+        };
+        assert_eq!(step_selected(&apps, 3, filtered, true), Some(3));
+        assert_eq!(step_selected(&apps, 0, filtered, false), Some(3));
+        apps[3].selected = false;
+        assert_eq!(step_selected(&apps, 0, filtered, true), None);
+    }
+
+    #[test]
+    fn test_toggle_all() {
+        let mut apps = apps();
+        toggle_all(&mut apps, ListFilter::default());
+        assert_eq!(
+            apps.iter().map(|app| app.selected).collect::<Vec<_>>(),
+            [false, true, true]
+        );
+        toggle_all(&mut apps, ListFilter::default());
+        assert!(apps.iter().all(|app| !app.selected));
+
+        // With a filter, only what it shows
+        let slack = AppFilter::parse("slack").unwrap();
+        let filtered = ListFilter {
+            filter: Some(&slack),
+            ..ListFilter::default()
+        };
+        toggle_all(&mut apps, filtered);
+        assert_eq!(
+            apps.iter().map(|app| app.selected).collect::<Vec<_>>(),
+            [false, true, false]
+        );
+        apps[2].selected = true;
+        assert!(!will_trim(&apps[2], filtered));
+        assert_eq!(
+            trim_popup(&apps, filtered, Confirmation::None),
+            None,
+            "the hidden managed app doesn't count"
+        );
+        toggle_all(&mut apps, filtered);
+        assert_eq!(
+            apps.iter().map(|app| app.selected).collect::<Vec<_>>(),
+            [false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_selection_history() {
+        let mut apps = apps();
+        let mut history = SelectionHistory::default();
+        assert!(!history.undo(&mut apps));
+
+        apps[1].selected = true;
+        history.record(&apps);
+        toggle_all(&mut apps, ListFilter::default());
+        history.record(&apps);
+        toggle_all(&mut apps, ListFilter::default());
+        assert!(apps.iter().all(|app| !app.selected));
+
+        // Survives re-sorting
+        apps.reverse();
+        assert!(history.undo(&mut apps));
+        assert_eq!(
+            apps.iter().map(|app| app.selected).collect::<Vec<_>>(),
+            [true, true, false]
+        );
+        assert!(history.undo(&mut apps));
+        assert_eq!(
+            apps.iter().map(|app| app.selected).collect::<Vec<_>>(),
+            [false, true, false]
+        );
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_trim_popup() {
+        let mut apps = apps();
+        assert_eq!(
+            trim_popup(&apps, ListFilter::default(), Confirmation::Normal),
+            Some(AppState::PopupNoSelection)
+        );
+        apps[1].selected = true;
+        assert_eq!(
+            trim_popup(&apps, ListFilter::default(), Confirmation::None),
+            None
+        );
+        assert_eq!(
+            trim_popup(&apps, ListFilter::default(), Confirmation::Normal),
+            Some(AppState::PopupConfirmTrim)
+        );
+        assert_eq!(
+            trim_popup(&apps, ListFilter::default(), Confirmation::Strict),
+            Some(AppState::PopupConfirmTyped)
+        );
+        apps[2].selected = true;
+        assert_eq!(
+            trim_popup(&apps, ListFilter::default(), Confirmation::None),
+            Some(AppState::PopupConfirmManaged)
+        );
+    }
+}
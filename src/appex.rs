@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner;
+
+/// Folders of a bundle with app extensions: `PlugIns` for classic ones such as
+/// Safari and Finder extensions, `Extensions` for ExtensionKit ones.
+const EXTENSION_DIRS: [&str; 2] = ["PlugIns", "Extensions"];
+/// Folders of a bundle with apps of its own that may host extensions too, such as
+/// the login item of a password manager.
+const CONTAINER_DIRS: [&str; 2] = ["Library/LoginItems", "Helpers"];
+
+/// Universal executables of the app's extensions and those of the apps it
+/// contains, with the bytes trimming each one frees.
+pub fn fat_binaries(app_path: &Path) -> Vec<(PathBuf, u64)> {
+    let contents = app_path.join("Contents");
+    let mut hosts = vec![app_path.to_path_buf()];
+    for dir in CONTAINER_DIRS {
+        hosts.extend(
+            entries(&contents.join(dir))
+                .into_iter()
+                .filter(|path| path.extension().is_some_and(|extension| extension == "app")),
+        );
+    }
+
+    let mut binaries = Vec::new();
+    for host in hosts {
+        for dir in EXTENSION_DIRS {
+            let appexes = entries(&host.join("Contents").join(dir))
+                .into_iter()
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "appex")
+                });
+            for appex in appexes {
+                binaries.extend(
+                    entries(&appex.join("Contents/MacOS"))
+                        .into_iter()
+                        .filter(|path| path.is_file())
+                        .filter_map(|path| Some((path.clone(), scanner::nested_savings(&path)?))),
+                );
+            }
+        }
+    }
+    binaries.sort();
+    binaries
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fat(path: &Path) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_fat_binaries() {
+        let app = std::env::temp_dir().join(format!("bintrim-appex-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&app);
+        let safari = app.join("Contents/PlugIns/Safari.appex/Contents/MacOS");
+        let autofill = app.join(
+            "Contents/Library/LoginItems/Helper.app/Contents/Extensions/AutoFill.appex/Contents/MacOS",
+        );
+        let framework = app.join("Contents/PlugIns/Shared.framework");
+        for dir in [&safari, &autofill, &framework] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        write_fat(&safari.join("Safari"));
+        write_fat(&autofill.join("AutoFill"));
+        // Not an extension
+        write_fat(&framework.join("Shared"));
+        // Already thin
+        fs::write(safari.join("thin"), b"").unwrap();
+
+        let binaries = fat_binaries(&app);
+        fs::remove_dir_all(&app).unwrap();
+
+        let names: Vec<_> = binaries
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["AutoFill", "Safari"]);
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// CPU architecture of a Mach-O slice, named the way lipo and otool name it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Arch {
+    X86_64,
+    Arm64,
+    /// arm64 with pointer authentication, used by system binaries
+    Arm64e,
+    I386,
+    /// Anything else, such as `ppc` or an unknown CPU type
+    Other(String),
+}
+
+impl Arch {
+    /// Parses a name as lipo prints it or a user types it, ignoring case.
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "x86_64" => Arch::X86_64,
+            "arm64" => Arch::Arm64,
+            "arm64e" => Arch::Arm64e,
+            "i386" => Arch::I386,
+            _ => Arch::Other(name.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::Arm64 => "arm64",
+            Arch::Arm64e => "arm64e",
+            Arch::I386 => "i386",
+            Arch::Other(name) => name,
+        }
+    }
+
+    /// Does the slice run natively on Apple silicon?
+    pub fn is_arm64(&self) -> bool {
+        matches!(self, Arch::Arm64 | Arch::Arm64e)
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Arch::from_name("x86_64"), Arch::X86_64);
+        assert_eq!(Arch::from_name("ARM64"), Arch::Arm64);
+        assert!(Arch::from_name("arm64e").is_arm64());
+        assert_eq!(Arch::from_name("ppc"), Arch::Other("ppc".to_string()));
+        assert_eq!(Arch::from_name("ppc").to_string(), "ppc");
+    }
+}
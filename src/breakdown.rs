@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::arch::Arch;
+use crate::macho;
+use crate::scanner::{self, AppInfo};
+use crate::trimmer::Strategy;
+use crate::xcode::{self, XcodePlan};
+
+/// How many of the largest directories and files are listed.
+const LARGEST_COUNT: usize = 8;
+
+/// Where the space inside a bundle goes, for the per-app breakdown popup.
+#[derive(Debug, Default, PartialEq)]
+pub struct Breakdown {
+    pub total_bytes: u64,
+    /// Largest directories directly inside `Contents`, relative to the bundle
+    pub largest_dirs: Vec<(PathBuf, u64)>,
+    /// Largest files anywhere in the bundle, relative to the bundle
+    pub largest_files: Vec<(PathBuf, u64)>,
+    /// Universal binaries with their slices, largest first
+    pub fat_binaries: Vec<(PathBuf, Vec<Arch>)>,
+    /// Names of the `.lproj` localization directories, without the extension
+    pub localizations: Vec<String>,
+    /// Estimated savings of each trim strategy for the main binary
+    pub savings: Vec<(Strategy, Option<u64>)>,
+    /// Toolchain binaries trimmed along with Xcode, `None` for other apps
+    pub xcode: Option<XcodePlan>,
+}
+
+/// Walks the app's bundle. Can take a while for large bundles such as Xcode.
+pub fn analyze(app: &AppInfo) -> Breakdown {
+    let mut breakdown = Breakdown {
+        savings: [Strategy::LipoRemove, Strategy::KeepOnlyNative]
+            .into_iter()
+            .map(|strategy| {
+                let savings = scanner::trim_savings(&app.binary_path, &app.architectures, strategy);
+                (strategy, savings)
+            })
+            .collect(),
+        xcode: xcode::is_xcode(app.bundle_id.as_deref()).then(|| xcode::plan(&app.path)),
+        ..Breakdown::default()
+    };
+
+    let mut dir_sizes: HashMap<PathBuf, u64> = HashMap::new();
+    let mut files = Vec::new();
+    let mut fat_binaries = Vec::new();
+    let mut pending = vec![app.path.clone()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Symlinks inside frameworks point at content that is counted anyway
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let relative = path.strip_prefix(&app.path).unwrap_or(&path).to_path_buf();
+
+            if metadata.is_dir() {
+                if path
+                    .extension()
+                    .is_some_and(|extension| extension == "lproj")
+                    && let Some(name) = path.file_stem()
+                {
+                    breakdown
+                        .localizations
+                        .push(name.to_string_lossy().into_owned());
+                }
+                pending.push(path);
+            } else if metadata.is_file() {
+                let size = metadata.len();
+                breakdown.total_bytes += size;
+                if let Some(top) = top_level_dir(&relative) {
+                    *dir_sizes.entry(top).or_default() += size;
+                }
+                if let Some(archs) = macho::fat_architectures(&path) {
+                    let slices = archs.into_iter().map(|arch| arch.arch).collect();
+                    fat_binaries.push((relative.clone(), slices, size));
+                }
+                files.push((relative, size));
+            }
+        }
+    }
+
+    breakdown.largest_dirs = largest(dir_sizes.into_iter().collect());
+    breakdown.largest_files = largest(files);
+    fat_binaries.sort_by_key(|binary| std::cmp::Reverse(binary.2));
+    breakdown.fat_binaries = fat_binaries
+        .into_iter()
+        .map(|(path, slices, _)| (path, slices))
+        .collect();
+    breakdown.localizations.sort();
+    breakdown.localizations.dedup();
+    breakdown
+}
+
+/// `Contents/<name>` for anything below it.
+fn top_level_dir(relative: &Path) -> Option<PathBuf> {
+    let mut components = relative.components();
+    let contents = components.next()?;
+    let name = components.next()?;
+    // Files directly in Contents, like Info.plist, don't belong to a directory
+    components.next()?;
+    Some(Path::new(&contents).join(name))
+}
+
+fn largest(mut entries: Vec<(PathBuf, u64)>) -> Vec<(PathBuf, u64)> {
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(LARGEST_COUNT);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fat_header(archs: &[(u32, u32)]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(0xcafebabe_u32.to_be_bytes());
+        header.extend((archs.len() as u32).to_be_bytes());
+        for &(cpu_type, cpu_subtype) in archs {
+            header.extend(cpu_type.to_be_bytes());
+            header.extend(cpu_subtype.to_be_bytes());
+            header.extend([0u8; 12]);
+        }
+        header
+    }
+
+    #[test]
+    fn test_analyze() {
+        let root = std::env::temp_dir().join(format!("bintrim-breakdown-{}", std::process::id()));
+        let bundle = root.join("Slack.app");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(bundle.join("Contents/MacOS")).unwrap();
+        fs::create_dir_all(bundle.join("Contents/Resources/de.lproj")).unwrap();
+        fs::create_dir_all(bundle.join("Contents/Resources/en.lproj")).unwrap();
+
+        let mut binary = fat_header(&[(0x0100_0007, 3), (0x0100_000c, 0)]);
+        binary.resize(1000, 0);
+        fs::write(bundle.join("Contents/MacOS/Slack"), binary).unwrap();
+        fs::write(bundle.join("Contents/Resources/icon.icns"), [0u8; 300]).unwrap();
+        fs::write(
+            bundle.join("Contents/Resources/de.lproj/Main.strings"),
+            [0u8; 50],
+        )
+        .unwrap();
+        fs::write(bundle.join("Contents/Info.plist"), [0u8; 10]).unwrap();
+        // A Java class file, not a universal binary
+        let mut class = 0xcafebabe_u32.to_be_bytes().to_vec();
+        class.extend([0, 0, 0, 52]);
+        fs::write(bundle.join("Contents/Resources/Main.class"), class).unwrap();
+
+        let mut app = AppInfo::for_test("Slack", &[]);
+        app.path = bundle.clone();
+        app.binary_path = bundle.join("Contents/MacOS/Slack");
+        let breakdown = analyze(&app);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(breakdown.total_bytes, 1368);
+        assert_eq!(
+            breakdown.largest_dirs,
+            [
+                (PathBuf::from("Contents/MacOS"), 1000),
+                (PathBuf::from("Contents/Resources"), 358),
+            ]
+        );
+        assert_eq!(
+            breakdown.largest_files[0],
+            (PathBuf::from("Contents/MacOS/Slack"), 1000)
+        );
+        assert_eq!(
+            breakdown.fat_binaries,
+            [(
+                PathBuf::from("Contents/MacOS/Slack"),
+                vec![Arch::X86_64, Arch::Arm64]
+            )]
+        );
+        assert_eq!(breakdown.localizations, ["de", "en"]);
+    }
+}
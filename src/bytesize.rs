@@ -0,0 +1,81 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+
+use serde::Serialize;
+
+const MB: f64 = 1024.0 * 1024.0;
+const GB: f64 = MB * 1024.0;
+
+/// A number of bytes, shown in MB or, from 1 GB on, in GB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub const fn bytes(self) -> u64 {
+        self.0
+    }
+
+    pub fn mb(self) -> f64 {
+        self.0 as f64 / MB
+    }
+
+    /// Always in MB, so sizes in a column can be compared at a glance.
+    pub fn display_mb(self) -> String {
+        format!("{:.2} MB", self.mb())
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gb = self.0 as f64 / GB;
+        let text = if gb >= 1.0 {
+            format!("{:.1} GB", gb)
+        } else {
+            self.display_mb()
+        };
+        // Through `pad` so width and alignment apply
+        f.pad(&text)
+    }
+}
+
+impl Add for ByteSize {
+    type Output = ByteSize;
+
+    fn add(self, other: ByteSize) -> ByteSize {
+        ByteSize(self.0 + other.0)
+    }
+}
+
+impl AddAssign for ByteSize {
+    fn add_assign(&mut self, other: ByteSize) {
+        self.0 += other.0;
+    }
+}
+
+impl Sum for ByteSize {
+    fn sum<I: Iterator<Item = ByteSize>>(iter: I) -> ByteSize {
+        ByteSize(iter.map(ByteSize::bytes).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ByteSize(5 * 1024 * 1024).to_string(), "5.00 MB");
+        assert_eq!(ByteSize(3 * 1024 * 1024 * 1024 / 2).to_string(), "1.5 GB");
+        assert_eq!(
+            ByteSize(3 * 1024 * 1024 * 1024 / 2).display_mb(),
+            "1536.00 MB"
+        );
+        assert_eq!(format!("{:>9}", ByteSize(1024 * 1024)), "  1.00 MB");
+        assert_eq!(
+            [ByteSize(1), ByteSize(2)].into_iter().sum::<ByteSize>(),
+            ByteSize(3)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Tokens created by this process so far, to give each its own marker file.
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Asks a scan or trim running on another thread to stop at the next point where
+/// it can do so cleanly: between apps, and between the binaries of an app.
+///
+/// Clones share the same state. The trim script runs as root in its own process,
+/// so cancelling also creates a marker file that the script checks before each
+/// binary.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    cancelled: AtomicBool,
+    marker: PathBuf,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        let marker = std::env::temp_dir().join(format!(
+            "bintrim-cancel-{}-{}",
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::Relaxed)
+        ));
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                marker,
+            }),
+        }
+    }
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::Relaxed) {
+            // Without the marker the script only stops once it's done
+            let _ = fs::write(&self.inner.marker, "");
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The file that exists once the token is cancelled.
+    pub fn marker(&self) -> &Path {
+        &self.inner.marker
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if *self.cancelled.get_mut() {
+            let _ = fs::remove_file(&self.marker);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::default();
+        let clone = token.clone();
+        let marker = token.marker().to_path_buf();
+        assert_ne!(marker, CancelToken::default().marker());
+        assert!(!clone.is_cancelled());
+        assert!(!marker.exists());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(marker.exists());
+
+        drop(token);
+        assert!(marker.exists());
+        drop(clone);
+        assert!(!marker.exists());
+    }
+}
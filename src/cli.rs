@@ -0,0 +1,194 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+
+use crate::SortMode;
+use crate::bytesize::ByteSize;
+use crate::filter;
+
+/// A CLI utility for stripping legacy x86_64 architectures from macOS universal binaries
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Also scan every local user's ~/Applications (must be run with sudo)
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// Also scan the Applications folder of mounted external volumes
+    #[arg(long)]
+    pub volumes: bool,
+
+    /// Also scan games in the Steam library and the config's `game-dirs`
+    #[arg(long)]
+    pub games: bool,
+
+    /// Also scan the apps built by Xcode in ~/Library/Developer/Xcode/DerivedData
+    #[arg(long)]
+    pub derived_data: bool,
+
+    /// Also scan this folder of build products (can be repeated, adds to the
+    /// config's `build-dirs`)
+    #[arg(long, value_name = "PATH")]
+    pub build_dir: Vec<PathBuf>,
+
+    /// Print why applications were skipped after exiting and increase log detail
+    /// (-v: debug, -vv: trace)
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Write logs to this file
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Type the sudo password into sudo's own prompt instead of bintrim's popup
+    #[arg(long)]
+    pub sudo_prompt: bool,
+
+    /// Draw borders, progress and symbols with plain ASCII characters
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Don't use colors (also enabled by the NO_COLOR environment variable)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Leave out apps whose bundle identifier matches this glob, or whose path does
+    /// if it starts with / or ~/ (can be repeated, adds to the config's `ignore`)
+    #[arg(long, value_name = "PATTERN")]
+    pub ignore: Vec<String>,
+
+    /// Only list apps that free at least this much, such as 50MB or 1.5GB
+    #[arg(long, value_name = "SIZE", value_parser = filter::parse_size)]
+    pub min_size: Option<ByteSize>,
+
+    /// Only list apps whose name or bundle identifier matches this regular
+    /// expression, ignoring case
+    #[arg(long, value_name = "REGEX")]
+    pub name: Option<String>,
+
+    /// Only list apps with this architecture, such as x86_64 (can be repeated)
+    #[arg(long, value_name = "ARCH")]
+    pub arch: Vec<String>,
+
+    /// Only list apps of this category, such as games or
+    /// public.app-category.developer-tools
+    #[arg(long, value_name = "CATEGORY")]
+    pub category: Option<String>,
+
+    /// Inspect load commands and code signatures with otool and mark apps that
+    /// trimming may break as risky, and find native Python and Node extensions in
+    /// their resources (slower)
+    #[arg(long)]
+    pub deep: bool,
+
+    /// Also list helper daemons and launch agents that apps installed outside
+    /// their bundles, such as in /Library/PrivilegedHelperTools
+    #[arg(long)]
+    pub helpers: bool,
+
+    /// Only list and report apps, with every way of trimming them turned off, for
+    /// inventory on machines that must not be changed
+    #[arg(long)]
+    pub audit: bool,
+
+    /// Run even if another bintrim instance is trimming
+    #[arg(long)]
+    pub force: bool,
+
+    /// Print what was trimmed, how much that freed and what failed after leaving
+    /// the interface
+    #[arg(long)]
+    pub summary_on_exit: bool,
+
+    /// Use a line-based prompt instead of the full-screen interface (the default
+    /// when stdout isn't a terminal)
+    #[arg(long)]
+    pub plain: bool,
+
+    /// Write a JSON summary of the run to this file (plain mode only)
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// POST a JSON summary of the run to this URL (plain mode only)
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Also write the detailed JSON report of each trim batch to this file, in
+    /// addition to the state directory
+    #[arg(long, value_name = "PATH")]
+    pub report_out: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Trim the given apps without asking, then exit like plain mode
+    Trim(TrimArgs),
+    /// Pick the apps to trim in fzf or sk if installed, or else with a filter prompt
+    Pick(PickArgs),
+    /// Print a table of the prunable apps and exit, without trimming anything
+    List(ListArgs),
+    /// Install or remove the privileged helper, which trims apps without asking
+    /// for a password (must be run with sudo)
+    Helper(HelperArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TrimArgs {
+    /// Bundle paths or bundle identifiers of the apps to trim
+    #[arg(value_name = "APP", required_unless_present = "stdin")]
+    pub apps: Vec<String>,
+
+    /// Also read bundle paths or identifiers from stdin, one per line (empty lines
+    /// and lines starting with # are skipped)
+    #[arg(long)]
+    pub stdin: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PickArgs {
+    /// Only print the prunable apps, one tab-separated line each starting with the
+    /// bundle path, for piping into another picker and `bintrim trim --stdin`
+    #[arg(long)]
+    pub print: bool,
+
+    /// Use the built-in filter prompt even if fzf or sk is installed
+    #[arg(long)]
+    pub builtin: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Order of the apps: by the bytes trimming frees, by what every approach
+    /// together could free, or by name
+    #[arg(long, value_enum, default_value = "size")]
+    pub sort: SortMode,
+
+    /// Only print the first N apps
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct HelperArgs {
+    #[command(subcommand)]
+    pub action: HelperAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HelperAction {
+    /// Install the helper as a launch daemon, allowed to trim binaries in the
+    /// config's `helper-roots`
+    Install,
+    /// Stop and remove the helper
+    Uninstall,
+    /// Run the helper (started by launchd)
+    #[command(hide = true)]
+    Serve {
+        /// Folder the helper may trim binaries in (can be repeated)
+        #[arg(long = "root", value_name = "PATH")]
+        roots: Vec<PathBuf>,
+    },
+}
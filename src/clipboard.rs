@@ -0,0 +1,68 @@
+use std::process::Command;
+use std::time::Duration;
+
+use crate::command::{self, CommandError};
+use crate::scanner::AppInfo;
+
+const PBCOPY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The apps as CSV with a header row, for pasting into spreadsheets, tickets and
+/// chats.
+pub fn csv(apps: &[&AppInfo]) -> String {
+    let mut csv = String::from("name,bundle_id,path,architectures,prunable_bytes\n");
+    for app in apps {
+        let fields = [
+            app.name.clone(),
+            app.bundle_id.clone().unwrap_or_default(),
+            app.path.display().to_string(),
+            app.architectures_display(),
+            app.prune_size()
+                .map(|size| size.bytes().to_string())
+                .unwrap_or_default(),
+        ];
+        let fields: Vec<_> = fields.iter().map(|field| escape(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Puts `text` on the macOS clipboard with `pbcopy`.
+pub fn copy(text: &str) -> Result<(), CommandError> {
+    let output = command::run(&mut Command::new("pbcopy"), Some(text), PBCOPY_TIMEOUT)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(CommandError::Io(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv() {
+        let mut slack = AppInfo::for_test("Slack", &[("x86_64", Some(60)), ("arm64", Some(80))]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        let quoted = AppInfo::for_test("Say \"Hi\", Inc", &[("arm64", None)]);
+
+        assert_eq!(
+            csv(&[&slack, &quoted]),
+            "name,bundle_id,path,architectures,prunable_bytes\n\
+             Slack,com.tinyspeck.slackmacgap,/Applications/Slack.app,\"x86_64, arm64\",60\n\
+             \"Say \"\"Hi\"\", Inc\",,\"/Applications/Say \"\"Hi\"\", Inc.app\",arm64,\n"
+        );
+    }
+}
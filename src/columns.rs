@@ -0,0 +1,182 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::i18n::t;
+
+/// Gap between table columns.
+pub const SPACING: u16 = 1;
+/// Marker in front of the row under the cursor.
+pub const HIGHLIGHT_SYMBOL: &str = "> ";
+
+/// Columns of the app list, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Checkbox,
+    Name,
+    /// One symbol per badge, explained in the legend
+    Badges,
+    Architectures,
+    /// Size of every slice, the native one first
+    Slices,
+    Size,
+    Notes,
+}
+
+const ALL: &[Column] = &[
+    Column::Checkbox,
+    Column::Name,
+    Column::Badges,
+    Column::Architectures,
+    Column::Slices,
+    Column::Size,
+    Column::Notes,
+];
+
+/// Columns that can be hidden in the column chooser. The checkbox and name are always shown.
+pub const OPTIONAL: &[Column] = &[
+    Column::Badges,
+    Column::Architectures,
+    Column::Slices,
+    Column::Size,
+    Column::Notes,
+];
+
+impl Column {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::Checkbox => "",
+            Column::Name => t().column_name,
+            Column::Badges => t().column_badges,
+            Column::Architectures => t().column_architectures,
+            Column::Slices => t().column_slices,
+            Column::Size => t().column_size,
+            Column::Notes => t().column_notes,
+        }
+    }
+
+    pub fn constraint(&self) -> Constraint {
+        match self {
+            Column::Checkbox => Constraint::Length(3),
+            Column::Name => Constraint::Fill(3),
+            Column::Badges => Constraint::Length(6),
+            Column::Architectures => Constraint::Length(20),
+            Column::Slices => Constraint::Length(34),
+            Column::Size => Constraint::Length(14),
+            Column::Notes => Constraint::Fill(1),
+        }
+    }
+
+    /// Narrowest table, in cells, that still shows this column.
+    fn min_table_width(&self) -> u16 {
+        match self {
+            Column::Checkbox | Column::Name | Column::Size => 0,
+            Column::Notes | Column::Badges => 48,
+            Column::Architectures => 64,
+            Column::Slices => 120,
+        }
+    }
+}
+
+/// Columns shown in a table `width` cells wide, leaving out `hidden`. Less
+/// important columns are dropped first on narrow terminals.
+pub fn visible(width: u16, hidden: &[Column]) -> Vec<Column> {
+    ALL.iter()
+        .copied()
+        .filter(|column| width >= column.min_table_width() && !hidden.contains(column))
+        .collect()
+}
+
+/// Widths the table gives `columns` when drawn `width` cells wide, used to
+/// truncate cell text before the table clips it.
+pub fn widths(columns: &[Column], width: u16) -> Vec<usize> {
+    let selection = HIGHLIGHT_SYMBOL.width() as u16;
+    let area = Rect::new(0, 0, width.saturating_sub(selection), 1);
+    Layout::horizontal(columns.iter().map(Column::constraint))
+        .spacing(SPACING)
+        .split(area)
+        .iter()
+        .map(|rect| rect.width as usize)
+        .collect()
+}
+
+/// Shortens `text` to at most `width` terminal cells, ending it with an ellipsis
+/// if anything was cut off.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    // Leave room for the ellipsis
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += char_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Slack", 8), "Slack");
+        assert_eq!(truncate("Visual Studio Code", 7), "Visual…");
+        // Wide characters count as two cells
+        assert_eq!(truncate("微信微信", 5), "微信…");
+        assert_eq!(truncate("微信微信", 6), "微信…");
+        assert_eq!(truncate("anything", 0), "");
+    }
+
+    #[test]
+    fn test_visible() {
+        assert_eq!(visible(120, &[]), ALL);
+        assert_eq!(
+            visible(100, &[]),
+            [
+                Column::Checkbox,
+                Column::Name,
+                Column::Badges,
+                Column::Architectures,
+                Column::Size,
+                Column::Notes
+            ]
+        );
+        assert_eq!(
+            visible(60, &[]),
+            [
+                Column::Checkbox,
+                Column::Name,
+                Column::Badges,
+                Column::Size,
+                Column::Notes
+            ]
+        );
+        assert_eq!(
+            visible(30, &[]),
+            [Column::Checkbox, Column::Name, Column::Size]
+        );
+        assert_eq!(
+            visible(100, &[Column::Badges, Column::Architectures, Column::Notes]),
+            [Column::Checkbox, Column::Name, Column::Size]
+        );
+    }
+
+    #[test]
+    fn test_widths() {
+        let columns = visible(80, &[]);
+        let widths = widths(&columns, 80);
+        assert_eq!(widths, [3, 23, 6, 20, 14, 7]);
+    }
+}
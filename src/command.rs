@@ -0,0 +1,243 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// How long a timed out command has to end after SIGTERM before it's killed.
+const TERM_GRACE: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command couldn't be started or its output couldn't be read
+    Io(io::Error),
+    /// The command didn't finish in time and was killed
+    TimedOut(Duration),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Io(err) => err.fmt(f),
+            CommandError::TimedOut(timeout) => {
+                write!(f, "timed out after {}s", timeout.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<io::Error> for CommandError {
+    fn from(err: io::Error) -> Self {
+        CommandError::Io(err)
+    }
+}
+
+/// Runs `command` to completion, writing `stdin` to it first, and kills it if it
+/// takes longer than `timeout`.
+///
+/// Unlike [`Command::output`] this can't hang forever on a stuck child, such as
+/// `sudo` waiting for a password that never comes.
+pub fn run(
+    command: &mut Command,
+    stdin: Option<&str>,
+    timeout: Duration,
+) -> Result<Output, CommandError> {
+    let mut child = spawn(command, stdin)?;
+
+    // Drain the pipes on separate threads so a chatty child can't block on a full pipe
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let status = wait(&mut child, timeout, || thread::sleep(POLL_INTERVAL))?;
+
+    Ok(Output {
+        status,
+        stdout: stdout.map(join_drain).unwrap_or_default(),
+        stderr: stderr.map(join_drain).unwrap_or_default(),
+    })
+}
+
+/// [`run`], calling `on_line` with each line the command prints to stdout as soon
+/// as it's printed. The returned output only has stderr.
+pub fn run_lines(
+    command: &mut Command,
+    stdin: Option<&str>,
+    timeout: Duration,
+    mut on_line: impl FnMut(&str),
+) -> Result<Output, CommandError> {
+    let mut child = spawn(command, stdin)?;
+
+    let (sender, lines) = mpsc::channel();
+    let stdout = child.stdout.take().map(|pipe| {
+        thread::spawn(move || {
+            for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        })
+    });
+    let stderr = child.stderr.take().map(drain);
+
+    let status = wait(&mut child, timeout, || {
+        if let Ok(line) = lines.recv_timeout(POLL_INTERVAL) {
+            on_line(&line);
+        }
+    })?;
+    // The reader ends at the end of the pipe, with the last lines still queued
+    if let Some(stdout) = stdout {
+        let _ = stdout.join();
+    }
+    for line in lines.try_iter() {
+        on_line(&line);
+    }
+
+    Ok(Output {
+        status,
+        stdout: Vec::new(),
+        stderr: stderr.map(join_drain).unwrap_or_default(),
+    })
+}
+
+/// Starts `command` with piped output, writing `stdin` to it first. It gets a
+/// process group of its own, so a timeout can end everything it started.
+fn spawn(command: &mut Command, stdin: Option<&str>) -> io::Result<Child> {
+    let mut child = command
+        .process_group(0)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        // The child may exit without reading its input, which isn't an error here
+        let _ = pipe.write_all(input.as_bytes());
+        let _ = pipe.flush();
+    }
+    Ok(child)
+}
+
+/// Waits for `child` to exit, calling `idle` (which should take about
+/// [`POLL_INTERVAL`]) between checks, and ends its process group after `timeout`.
+fn wait(
+    child: &mut Child,
+    timeout: Duration,
+    mut idle: impl FnMut(),
+) -> Result<ExitStatus, CommandError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            terminate_group(child);
+            return Err(CommandError::TimedOut(timeout));
+        }
+        idle();
+    }
+}
+
+/// Ends the process group `child` leads: SIGTERM first, which sudo passes on to
+/// the root command it runs and which this user couldn't signal, then SIGKILL
+/// for whatever is left after [`TERM_GRACE`].
+fn terminate_group(child: &mut Child) {
+    let group = child.id() as libc::pid_t;
+    unsafe { libc::killpg(group, libc::SIGTERM) };
+    let deadline = Instant::now() + TERM_GRACE;
+    while Instant::now() < deadline && !matches!(child.try_wait(), Ok(Some(_))) {
+        thread::sleep(POLL_INTERVAL);
+    }
+    unsafe { libc::killpg(group, libc::SIGKILL) };
+    let _ = child.wait();
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+fn join_drain(handle: thread::JoinHandle<Vec<u8>>) -> Vec<u8> {
+    handle.join().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let output = run(
+            Command::new("sh").args(["-c", "cat; echo err >&2"]),
+            Some("hello"),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hello");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_run_lines() {
+        let mut lines = Vec::new();
+        let output = run_lines(
+            Command::new("sh").args(["-c", "echo one; echo err >&2; echo two"]),
+            None,
+            Duration::from_secs(5),
+            |line| lines.push(line.to_string()),
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(lines, ["one", "two"]);
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let started = Instant::now();
+        let result = run(
+            Command::new("sleep").arg("10"),
+            None,
+            Duration::from_millis(100),
+        );
+
+        assert!(matches!(result, Err(CommandError::TimedOut(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // What the command started ends with it
+        let mut pid = String::new();
+        let result = run_lines(
+            Command::new("sh").args(["-c", "sleep 30 & echo $!; wait"]),
+            None,
+            Duration::from_millis(200),
+            |line| pid = line.to_string(),
+        );
+        assert!(matches!(result, Err(CommandError::TimedOut(_))));
+        thread::sleep(Duration::from_millis(100));
+        let state = Command::new("ps")
+            .args(["-o", "stat=", "-p", &pid])
+            .output()
+            .unwrap();
+        let state = String::from_utf8_lossy(&state.stdout);
+        assert!(
+            state.trim().is_empty() || state.starts_with('Z'),
+            "{}",
+            state
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use color_eyre::eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::columns::Column;
+use crate::confirm::Confirmation;
+use crate::priority::Priority;
+use crate::tools::ToolPaths;
+use crate::trimmer::{Ownership, Strategy};
+
+/// User configuration, read from `~/.config/bintrim/config.toml`.
+///
+/// Every setting is optional, command line flags take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    /// Write logs to this file
+    pub log_file: Option<PathBuf>,
+    /// Maximum seconds a `lipo` call may take while scanning a single app
+    pub scan_timeout_secs: Option<u64>,
+    /// Maximum seconds a `sudo lipo` call may take while trimming a single app
+    pub trim_timeout_secs: Option<u64>,
+    /// Always use sudo's own password prompt, like `--sudo-prompt`
+    pub sudo_prompt: bool,
+    /// UI language such as `de`, instead of the one from `LANG`
+    pub language: Option<String>,
+    /// Only use ASCII characters, like `--ascii`
+    pub ascii: bool,
+    /// Don't use colors, like `--no-color`
+    pub no_color: bool,
+    /// Columns to hide by default (`badges`, `architectures`, `slices`, `size`, `notes`)
+    pub hidden_columns: Vec<Column>,
+    /// Run scanning and trimming at `background` priority instead of `normal`
+    pub priority: Priority,
+    /// Print a summary of the session after leaving the interface, like
+    /// `--summary-on-exit`
+    pub summary_on_exit: bool,
+    /// Look for a newer release once a day and mention it in the header
+    pub check_for_updates: bool,
+    /// Inspect load commands and code signatures while scanning, like `--deep`
+    pub deep_inspection: bool,
+    /// List helper daemons and launch agents, like `--helpers`
+    pub helpers: bool,
+    /// Also scan game libraries, like `--games`
+    pub games: bool,
+    /// Game library folders with a folder per game, in addition to Steam's
+    pub game_dirs: Vec<PathBuf>,
+    /// Also scan Xcode's DerivedData, like `--derived-data`
+    pub derived_data: bool,
+    /// Folders of build products to scan, in addition to those from `--build-dir`
+    pub build_dirs: Vec<PathBuf>,
+    /// Write a JSON summary of plain runs to this file, like `--report`
+    pub report_path: Option<PathBuf>,
+    /// POST a JSON summary of plain runs to this URL, like `--webhook`
+    pub report_webhook: Option<String>,
+    /// Who owns trimmed binaries: their owner from before (`keep`), the
+    /// `current-user` or root, as lipo leaves them (`skip`)
+    pub ownership: Ownership,
+    /// Globs of bundle identifiers, or paths if they start with `/` or `~/`, to leave
+    /// out of the scan
+    pub ignore: Vec<String>,
+    /// Patterns like those of `ignore` for bundles on the built-in denylist that may
+    /// be trimmed after all
+    pub unprotect: Vec<String>,
+    /// Folders the privileged helper may trim binaries in, instead of /Applications
+    pub helper_roots: Vec<PathBuf>,
+    /// Lock bintrim into audit mode, which `--audit` turns on for a single run
+    pub audit: bool,
+    /// What it takes to start trimming: nothing (`none`), a y/n popup (`normal`)
+    /// or typing `trim` or the number of apps (`strict`)
+    pub confirm: Confirmation,
+    /// Paths of lipo, otool and codesign, from the `[tools]` table
+    pub tools: ToolPaths,
+    /// Per-app settings by bundle identifier, from `[apps."com.example.App"]` tables
+    pub apps: HashMap<String, AppConfig>,
+}
+
+/// Settings for a single app.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AppConfig {
+    /// How to trim the app instead of the default `lipo-remove`
+    pub strategy: Option<Strategy>,
+    /// Who owns its binaries after trimming, instead of the global `ownership`
+    pub ownership: Option<Ownership>,
+}
+
+impl Config {
+    /// Trim strategies of the apps that override the default.
+    pub fn strategies(&self) -> HashMap<String, Strategy> {
+        self.apps
+            .iter()
+            .filter_map(|(bundle_id, app)| Some((bundle_id.clone(), app.strategy?)))
+            .collect()
+    }
+
+    /// Ownership of the apps that override the global setting.
+    pub fn app_ownership(&self) -> HashMap<String, Ownership> {
+        self.apps
+            .iter()
+            .filter_map(|(bundle_id, app)| Some((bundle_id.clone(), app.ownership?)))
+            .collect()
+    }
+
+    /// Loads the config file, falling back to the defaults if it doesn't exist.
+    pub fn load() -> color_eyre::Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .wrap_err_with(|| format!("invalid config file {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => {
+                Err(err).wrap_err_with(|| format!("failed to read config file {}", path.display()))
+            }
+        }
+    }
+}
+
+/// Directory holding bintrim's configuration, honoring `XDG_CONFIG_HOME`.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("bintrim"))
+}
+
+fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(r#"log-file = "/tmp/bintrim.log""#).unwrap();
+        assert_eq!(config.log_file, Some(PathBuf::from("/tmp/bintrim.log")));
+
+        let config: Config = toml::from_str("trim-timeout-secs = 60").unwrap();
+        assert_eq!(config.trim_timeout_secs, Some(60));
+
+        let config: Config =
+            toml::from_str(r#"ignore = ["com.apple.*", "/Applications/Adobe*"]"#).unwrap();
+        assert_eq!(config.ignore, ["com.apple.*", "/Applications/Adobe*"]);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.log_file.is_none());
+    }
+
+    #[test]
+    fn test_app_strategies() {
+        let config: Config = toml::from_str(
+            r#"
+            [apps."com.google.Chrome"]
+            strategy = "skip"
+
+            [apps."com.tinyspeck.slackmacgap"]
+            strategy = "keep-only-native"
+            ownership = "current-user"
+
+            [apps."com.example.Other"]
+            "#,
+        )
+        .unwrap();
+        let strategies = config.strategies();
+        assert_eq!(strategies.len(), 2);
+        assert_eq!(strategies["com.google.Chrome"], Strategy::Skip);
+        assert_eq!(
+            strategies["com.tinyspeck.slackmacgap"],
+            Strategy::KeepOnlyNative
+        );
+        assert_eq!(
+            config.app_ownership()["com.tinyspeck.slackmacgap"],
+            Ownership::CurrentUser
+        );
+        assert_eq!(config.ownership, Ownership::Keep);
+
+        // Strategies bintrim doesn't implement are rejected instead of ignored
+        assert!(
+            toml::from_str::<Config>("[apps.\"com.example.App\"]\nstrategy = \"compress-only\"")
+                .is_err()
+        );
+    }
+}
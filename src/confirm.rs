@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+/// The word that confirms trimming at [`Confirmation::Strict`], as an alternative
+/// to the number of apps. It's the same in every language.
+pub const CONFIRM_WORD: &str = "trim";
+
+/// What it takes to start trimming the selected apps, from the `confirm` setting.
+/// Managed apps are warned about at every level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Confirmation {
+    /// Go straight to the password, for those who look at the list closely anyway
+    None,
+    /// Ask once with a y/n popup
+    #[default]
+    Normal,
+    /// Type `trim` or the number of selected apps
+    Strict,
+}
+
+/// Does `typed` confirm trimming `count` apps at [`Confirmation::Strict`]?
+pub fn accepts(typed: &str, count: usize) -> bool {
+    let typed = typed.trim();
+    typed.eq_ignore_ascii_case(CONFIRM_WORD) || typed.parse() == Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        assert!(accepts("trim", 3));
+        assert!(accepts(" TRIM ", 3));
+        assert!(accepts("3", 3));
+        assert!(!accepts("2", 3));
+        assert!(!accepts("y", 3));
+        assert!(!accepts("", 0));
+    }
+}
@@ -0,0 +1,687 @@
+use std::ffi::{CString, OsStr};
+use std::fs::{self, File, Permissions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use crate::cancel::CancelToken;
+use crate::command::{self, CommandError};
+use crate::elevated::{Message, Operation};
+use crate::macho;
+use crate::tools;
+use crate::trimmer::{self, Strategy};
+
+/// launchd label of the privileged helper.
+const LABEL: &str = "com.github.ecklf.bintrim.helper";
+/// Where `bintrim helper install` copies itself. Root owns the copy, so nobody
+/// else can swap it for another program, and the helper never trims it.
+const INSTALL_PATH: &str = "/Library/PrivilegedHelperTools/com.github.ecklf.bintrim.helper";
+const PLIST_PATH: &str = "/Library/LaunchDaemons/com.github.ecklf.bintrim.helper.plist";
+/// The helper listens here for operations.
+const SOCKET_PATH: &str = "/var/run/bintrim-helper.sock";
+/// Only root can write here, so lipo works on a copy nobody can swap.
+const WORK_DIR: &str = "/var/run/bintrim-helper";
+/// Folders the helper may trim binaries in unless the config names others. The
+/// daemons in /Library/PrivilegedHelperTools run as root, so they're left to sudo.
+pub const DEFAULT_ROOTS: [&str; 1] = ["/Applications"];
+/// Members of this group may use the helper, like they may use sudo.
+const ADMIN_GROUP: u32 = 80;
+/// The only changes the helper makes to binaries.
+const ALLOWED_STRATEGIES: [Strategy; 2] = [Strategy::LipoRemove, Strategy::KeepOnlyNative];
+
+/// Is the helper installed and listening?
+pub fn available() -> bool {
+    UnixStream::connect(SOCKET_PATH).is_ok()
+}
+
+/// Has the helper perform `operations`, calling `on_message` with its progress in
+/// the format of the elevated script. Each reply may take up to `timeout`.
+///
+/// Operations are sent one at a time, each once the previous one is done, so
+/// `cancel` stops the helper before the next binary.
+pub fn run(
+    operations: &[Operation],
+    timeout: Duration,
+    cancel: &CancelToken,
+    mut on_message: impl FnMut(Message),
+) -> Result<(), CommandError> {
+    let timed_out = |err: io::Error| match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => CommandError::TimedOut(timeout),
+        _ => CommandError::Io(err),
+    };
+    let mut stream = UnixStream::connect(SOCKET_PATH)?;
+    stream.set_read_timeout(Some(timeout))?;
+    let mut replies = BufReader::new(stream.try_clone()?).lines();
+    let mut failed_app = None;
+    for (op, operation) in operations.iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        // The helper skips the rest of an app that failed without replying
+        if failed_app == Some(operation.app) {
+            continue;
+        }
+        writeln!(stream, "{}", request_line(op, operation))?;
+        loop {
+            let Some(line) = replies.next() else {
+                return Ok(());
+            };
+            let message = match Message::parse(&line.map_err(timed_out)?) {
+                Some(message) => message,
+                None => {
+                    warn!("unexpected reply from the helper");
+                    continue;
+                }
+            };
+            let done = match message {
+                Message::Trimmed(done) => done == op,
+                Message::Failed(done, _) => {
+                    failed_app = Some(operation.app);
+                    done == op
+                }
+                Message::AppStarted(_) | Message::ChownFailed(..) => false,
+            };
+            on_message(message);
+            if done {
+                break;
+            }
+        }
+    }
+    stream.shutdown(std::net::Shutdown::Write)?;
+    Ok(())
+}
+
+/// One operation as the helper reads it: its index, the app's index, the owner,
+/// lipo's arguments and the binary, separated by tabs.
+fn request_line(op: usize, operation: &Operation) -> String {
+    let owner = operation
+        .owner
+        .map_or("-".to_string(), |(uid, gid)| format!("{}:{}", uid, gid));
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        op,
+        operation.app,
+        owner,
+        operation.lipo_args.join(" "),
+        operation.binary.display()
+    )
+}
+
+/// An operation a client asked for, before it's checked.
+#[derive(Debug, PartialEq)]
+struct Request {
+    op: usize,
+    app: usize,
+    owner: Option<(u32, u32)>,
+    lipo_args: String,
+    binary: PathBuf,
+}
+
+impl Request {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        let op = fields.next()?.parse().ok()?;
+        let app = fields.next()?.parse().ok()?;
+        let owner = match fields.next()? {
+            "-" => None,
+            owner => {
+                let (uid, gid) = owner.split_once(':')?;
+                Some((uid.parse().ok()?, gid.parse().ok()?))
+            }
+        };
+        Some(Request {
+            op,
+            app,
+            owner,
+            lipo_args: fields.next()?.to_string(),
+            binary: PathBuf::from(fields.next()?),
+        })
+    }
+
+    /// The binary's real path and lipo's arguments, if the helper may trim it:
+    /// a universal binary under one of `roots` other than the helper itself,
+    /// changed in one of the [`ALLOWED_STRATEGIES`] and given back to its owner.
+    fn check(&self, roots: &[PathBuf]) -> Result<(PathBuf, &'static [&'static str]), String> {
+        let lipo_args = ALLOWED_STRATEGIES
+            .iter()
+            .map(|strategy| strategy.lipo_args())
+            .find(|args| args.join(" ") == self.lipo_args)
+            .ok_or_else(|| format!("lipo arguments {:?} aren't allowed", self.lipo_args))?;
+        // Resolving links first keeps them from pointing out of the roots
+        let binary = self
+            .binary
+            .canonicalize()
+            .map_err(|err| format!("{}: {}", self.binary.display(), err))?;
+        if !roots
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| binary.starts_with(root))
+        {
+            return Err(format!("{} isn't in an approved folder", binary.display()));
+        }
+        if binary == Path::new(INSTALL_PATH) {
+            return Err(format!("{} is the helper itself", binary.display()));
+        }
+        let metadata = fs::symlink_metadata(&binary).map_err(|err| err.to_string())?;
+        if !metadata.is_file() || macho::fat_architectures(&binary).is_none() {
+            return Err(format!("{} isn't a universal binary", binary.display()));
+        }
+        // The helper only ever gives a binary back to whoever owned it
+        if let Some(owner) = self.owner
+            && owner != (metadata.uid(), metadata.gid())
+        {
+            return Err(format!("owner {}:{} isn't allowed", owner.0, owner.1));
+        }
+        Ok((binary, lipo_args))
+    }
+}
+
+/// Runs the helper: accepts connections on its socket and performs the
+/// operations they send, one connection at a time. Only returns on errors.
+pub fn serve(roots: &[PathBuf]) -> io::Result<()> {
+    let _ = fs::remove_file(SOCKET_PATH);
+    let listener = UnixListener::bind(SOCKET_PATH)?;
+    // Anyone may connect, [`authorized`] and the checks decide what's done
+    fs::set_permissions(SOCKET_PATH, Permissions::from_mode(0o666))?;
+    info!(?roots, "helper listening");
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| handle(stream, roots));
+        if let Err(err) = result {
+            warn!(%err, "helper connection failed");
+        }
+    }
+    Ok(())
+}
+
+fn handle(stream: UnixStream, roots: &[PathBuf]) -> io::Result<()> {
+    let client = client_credentials(&stream);
+    let allowed = client.is_some_and(|(uid, _)| authorized(uid));
+    if !allowed {
+        warn!(?client, "client isn't allowed to use the helper");
+    }
+    let mut replies = stream.try_clone()?;
+    let mut current_app = None;
+    let mut failed_app = None;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let Some(request) = Request::parse(&line) else {
+            warn!(line, "malformed request");
+            continue;
+        };
+        // An app's binaries come one after the other
+        if current_app != Some(request.app) {
+            current_app = Some(request.app);
+            writeln!(replies, "{}", Message::AppStarted(request.app))?;
+        }
+        if failed_app == Some(request.app) {
+            continue;
+        }
+        let messages = if allowed {
+            perform(&request, roots)
+        } else {
+            let error = "only administrators may use the helper";
+            vec![Message::Failed(request.op, error.to_string())]
+        };
+        for message in messages {
+            if matches!(message, Message::Failed(..)) {
+                failed_app = Some(request.app);
+            }
+            writeln!(replies, "{}", message)?;
+        }
+    }
+    Ok(())
+}
+
+/// Trims the binary of `request` if the checks allow it.
+fn perform(request: &Request, roots: &[PathBuf]) -> Vec<Message> {
+    match work_dir() {
+        Ok(work) => trim(request, roots, tools::lipo(), &work),
+        Err(err) => vec![Message::Failed(
+            request.op,
+            format!("{}: {}", WORK_DIR, err),
+        )],
+    }
+}
+
+/// [`perform`] with `lipo` working in `work`.
+fn trim(request: &Request, roots: &[PathBuf], lipo: &Path, work: &Path) -> Vec<Message> {
+    let (binary, lipo_args) = match request.check(roots) {
+        Ok(checked) => checked,
+        Err(error) => {
+            warn!(binary = %request.binary.display(), error, "refused");
+            return vec![Message::Failed(request.op, error)];
+        }
+    };
+    info!(binary = %binary.display(), "trimming");
+    match replace(&binary, lipo, lipo_args, work) {
+        Ok(Some(chown_error)) => vec![
+            Message::ChownFailed(request.op, chown_error.to_string()),
+            Message::Trimmed(request.op),
+        ],
+        Ok(None) => vec![Message::Trimmed(request.op)],
+        Err(error) => vec![Message::Failed(request.op, error)],
+    }
+}
+
+/// [`WORK_DIR`], created if needed and checked to be root's alone.
+fn work_dir() -> io::Result<PathBuf> {
+    let dir = PathBuf::from(WORK_DIR);
+    match fs::create_dir(&dir) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(err),
+        _ => {}
+    }
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != 0 {
+        return Err(io::Error::other("not a directory of root's"));
+    }
+    fs::set_permissions(&dir, Permissions::from_mode(0o700))?;
+    Ok(dir)
+}
+
+/// Has `lipo` trim `binary` without following a link swapped in after
+/// [`Request::check`]: the binary is reached through its folders one by one
+/// without following links, lipo works on a copy in `work`, and the result only
+/// replaces the binary if it's still the file that was copied. The new file
+/// gets the old one's owner and mode as read here, whatever the client asked
+/// for, or loses its setuid and setgid bits if the owner can't be restored so
+/// that root doesn't end up owning them. Returns the error restoring the owner,
+/// if any.
+fn replace(
+    binary: &Path,
+    lipo: &Path,
+    lipo_args: &[&str],
+    work: &Path,
+) -> Result<Option<io::Error>, String> {
+    let error = |err: io::Error| format!("{}: {}", binary.display(), err);
+    let (Some(parent), Some(name)) = (binary.parent(), binary.file_name()) else {
+        return Err(format!("{} isn't a file", binary.display()));
+    };
+    let dir = open_dir(parent).map_err(error)?;
+    // Nonblocking so that a FIFO in place of the binary can't stall the helper
+    let mut original = open_at(&dir, name, libc::O_RDONLY | libc::O_NONBLOCK, 0).map_err(error)?;
+    let metadata = original.metadata().map_err(error)?;
+    if !metadata.is_file() {
+        return Err(format!("{} isn't a universal binary", binary.display()));
+    }
+
+    let input = work.join("input");
+    let output = work.join("output");
+    let _ = fs::remove_file(&output);
+    io::copy(&mut original, &mut File::create(&input).map_err(error)?).map_err(error)?;
+    if macho::fat_architectures(&input).is_none() {
+        let _ = fs::remove_file(&input);
+        return Err(format!("{} isn't a universal binary", binary.display()));
+    }
+    let result = command::run(
+        Command::new(lipo)
+            .arg(&input)
+            .args(lipo_args)
+            .arg("-output")
+            .arg(&output),
+        None,
+        trimmer::DEFAULT_TIMEOUT,
+    );
+    let _ = fs::remove_file(&input);
+    match result {
+        Ok(result) if result.status.success() => {}
+        Ok(result) => {
+            let stderr = String::from_utf8_lossy(&result.stderr).replace('\n', " ");
+            return Err(stderr.trim().to_string());
+        }
+        Err(err) => return Err(err.to_string()),
+    }
+
+    let temp_name = {
+        let mut temp_name = OsStr::new(".").to_os_string();
+        temp_name.push(name);
+        temp_name.push(".bintrim");
+        temp_name
+    };
+    let mut temp = open_at(
+        &dir,
+        &temp_name,
+        libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+        0o600,
+    )
+    .map_err(error)?;
+    let written = File::open(&output)
+        .and_then(|mut trimmed| io::copy(&mut trimmed, &mut temp))
+        .and_then(|_| {
+            let _ = fs::remove_file(&output);
+            let chown_error =
+                std::os::unix::fs::fchown(&temp, Some(metadata.uid()), Some(metadata.gid())).err();
+            let mode = match chown_error {
+                Some(_) => metadata.mode() & 0o1777,
+                None => metadata.mode() & 0o7777,
+            };
+            // After the chown, which may clear the setuid bits
+            temp.set_permissions(Permissions::from_mode(mode))?;
+            Ok(chown_error)
+        })
+        .and_then(|chown_error| {
+            let current = open_at(&dir, name, libc::O_RDONLY | libc::O_NONBLOCK, 0)?.metadata()?;
+            if (current.dev(), current.ino()) != (metadata.dev(), metadata.ino()) {
+                return Err(io::Error::other("it changed while being trimmed"));
+            }
+            rename_at(&dir, &temp_name, name)?;
+            Ok(chown_error)
+        });
+    if written.is_err() {
+        let _ = unlink_at(&dir, &temp_name);
+    }
+    written.map_err(error)
+}
+
+/// Opens the folder at the absolute, canonical `path`, following no links on the way.
+fn open_dir(path: &Path) -> io::Result<File> {
+    let mut dir = File::open("/")?;
+    for component in path.components() {
+        match component {
+            Component::RootDir => {}
+            Component::Normal(name) => {
+                dir = open_at(&dir, name, libc::O_RDONLY | libc::O_DIRECTORY, 0)?;
+            }
+            _ => return Err(io::Error::other("not a canonical path")),
+        }
+    }
+    Ok(dir)
+}
+
+/// Opens `name` in `dir`, failing if it's a link.
+fn open_at(dir: &File, name: &OsStr, flags: libc::c_int, mode: libc::mode_t) -> io::Result<File> {
+    let name = CString::new(name.as_bytes())?;
+    let fd = unsafe {
+        libc::openat(
+            dir.as_raw_fd(),
+            name.as_ptr(),
+            flags | libc::O_NOFOLLOW | libc::O_CLOEXEC,
+            libc::c_uint::from(mode),
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+fn rename_at(dir: &File, from: &OsStr, to: &OsStr) -> io::Result<()> {
+    let from = CString::new(from.as_bytes())?;
+    let to = CString::new(to.as_bytes())?;
+    let fd = dir.as_raw_fd();
+    if unsafe { libc::renameat(fd, from.as_ptr(), fd, to.as_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn unlink_at(dir: &File, name: &OsStr) -> io::Result<()> {
+    let name = CString::new(name.as_bytes())?;
+    if unsafe { libc::unlinkat(dir.as_raw_fd(), name.as_ptr(), 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// May the user `uid` have the helper trim? Root and members of the admin
+/// group, who could trim with sudo anyway.
+fn authorized(uid: u32) -> bool {
+    uid == 0 || groups(uid).contains(&ADMIN_GROUP)
+}
+
+/// The IDs of the groups `uid` belongs to, as `id -G` lists them.
+fn groups(uid: u32) -> Vec<u32> {
+    command::run(
+        Command::new("/usr/bin/id").arg("-G").arg(uid.to_string()),
+        None,
+        Duration::from_secs(10),
+    )
+    .ok()
+    .filter(|output| output.status.success())
+    .map(|output| {
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .filter_map(|group| group.parse().ok())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// User and group ID of the process on the other end of `stream`.
+#[cfg(target_os = "macos")]
+fn client_credentials(stream: &UnixStream) -> Option<(u32, u32)> {
+    let mut uid = 0;
+    let mut gid = 0;
+    (unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } == 0).then_some((uid, gid))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn client_credentials(_stream: &UnixStream) -> Option<(u32, u32)> {
+    None
+}
+
+/// The launch daemon that runs the helper with `roots`.
+fn launchd_job(roots: &[PathBuf]) -> plist::Value {
+    let mut arguments: Vec<plist::Value> =
+        vec![INSTALL_PATH.into(), "helper".into(), "serve".into()];
+    for root in roots {
+        arguments.push("--root".into());
+        arguments.push(root.to_string_lossy().as_ref().into());
+    }
+    let mut job = plist::Dictionary::new();
+    job.insert("Label".to_string(), LABEL.into());
+    job.insert("ProgramArguments".to_string(), arguments.into());
+    job.insert("RunAtLoad".to_string(), true.into());
+    job.insert("KeepAlive".to_string(), true.into());
+    plist::Value::Dictionary(job)
+}
+
+/// Copies the running bintrim to [`INSTALL_PATH`] and has launchd run it as the
+/// helper for `roots`. Must be run as root.
+///
+/// SMAppService and SMJobBless would need bintrim to ship as a signed app bundle
+/// with the daemon's plist inside, which a command line tool installed with
+/// cargo or Homebrew isn't. Installing with sudo asks for the same admin
+/// password their approval prompt would.
+pub fn install(roots: &[PathBuf]) -> io::Result<()> {
+    fs::copy(std::env::current_exe()?, INSTALL_PATH)?;
+    fs::set_permissions(INSTALL_PATH, Permissions::from_mode(0o755))?;
+    launchd_job(roots)
+        .to_file_xml(PLIST_PATH)
+        .map_err(io::Error::other)?;
+    // Replaces an older version of the helper
+    let _ = launchctl(&["bootout", &format!("system/{}", LABEL)]);
+    launchctl(&["bootstrap", "system", PLIST_PATH])
+}
+
+/// Stops the helper and removes what [`install`] put in place. Must be run as root.
+pub fn uninstall() -> io::Result<()> {
+    let _ = launchctl(&["bootout", &format!("system/{}", LABEL)]);
+    for path in [PLIST_PATH, INSTALL_PATH, SOCKET_PATH] {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    match fs::remove_dir_all(WORK_DIR) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn launchctl(args: &[&str]) -> io::Result<()> {
+    let output = command::run(
+        Command::new("launchctl").args(args),
+        None,
+        Duration::from_secs(10),
+    )
+    .map_err(|err| io::Error::other(err.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Where the helper's roots come from: the config's `helper-roots`, or
+/// [`DEFAULT_ROOTS`].
+pub fn roots(configured: &[PathBuf]) -> Vec<PathBuf> {
+    if configured.is_empty() {
+        DEFAULT_ROOTS.iter().map(PathBuf::from).collect()
+    } else {
+        configured.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn write_fat(path: &Path) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_request_check() {
+        let dir = std::env::temp_dir().join(format!("bintrim-daemon-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = dir.join("Applications");
+        fs::create_dir_all(&root).unwrap();
+        let binary = root.join("Slack");
+        write_fat(&binary);
+        let thin = root.join("thin");
+        fs::write(&thin, [0u8; 100]).unwrap();
+        let outside = dir.join("Outside");
+        write_fat(&outside);
+        let escape = root.join("escape");
+        std::os::unix::fs::symlink(&outside, &escape).unwrap();
+        let metadata = fs::metadata(&binary).unwrap();
+        let owner = (metadata.uid(), metadata.gid());
+
+        let operation = Operation {
+            app: 3,
+            binary: binary.clone(),
+            lipo_args: Strategy::LipoRemove.lipo_args(),
+            owner: Some(owner),
+        };
+        let request = Request::parse(&request_line(7, &operation)).unwrap();
+        assert_eq!(request.op, 7);
+        assert_eq!(request.app, 3);
+        assert_eq!(request.binary, binary);
+
+        let roots = [root.clone()];
+        let check = |request: &Request| request.check(&roots);
+        let canonical = binary.canonicalize().unwrap();
+        assert_eq!(
+            check(&request),
+            Ok((canonical, Strategy::LipoRemove.lipo_args()))
+        );
+        let with = |change: &dyn Fn(&mut Request)| {
+            let mut request = Request::parse(&request_line(7, &operation)).unwrap();
+            change(&mut request);
+            check(&request)
+        };
+        assert!(with(&|request| request.binary = thin.clone()).is_err());
+        assert!(with(&|request| request.binary = outside.clone()).is_err());
+        assert!(with(&|request| request.binary = escape.clone()).is_err());
+        assert!(with(&|request| request.lipo_args = "-remove arm64".to_string()).is_err());
+        assert!(with(&|request| request.owner = Some((owner.0 + 1, owner.1))).is_err());
+        assert!(with(&|request| request.owner = None).is_ok());
+        assert!(Request::parse("7\t3\tnobody\t-remove x86_64\t/x").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replace() {
+        let dir = std::env::temp_dir().join(format!("bintrim-replace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let work = dir.join("work");
+        let app = dir.join("Slack.app");
+        fs::create_dir_all(&work).unwrap();
+        fs::create_dir_all(&app).unwrap();
+        let binary = app.join("Slack");
+        write_fat(&binary);
+        fs::set_permissions(&binary, Permissions::from_mode(0o751)).unwrap();
+        // Stands in for lipo, writing a marker instead of the trimmed binary
+        let lipo = dir.join("lipo");
+        fs::write(&lipo, "#!/bin/sh\nprintf trimmed > \"$5\"\n").unwrap();
+        fs::set_permissions(&lipo, Permissions::from_mode(0o755)).unwrap();
+        let args = Strategy::LipoRemove.lipo_args();
+        let canonical = binary.canonicalize().unwrap();
+
+        let before = fs::metadata(&binary).unwrap();
+        assert!(matches!(replace(&canonical, &lipo, args, &work), Ok(None)));
+        let after = fs::metadata(&binary).unwrap();
+        assert_eq!(fs::read(&binary).unwrap(), b"trimmed");
+        assert_eq!(after.mode() & 0o7777, 0o751);
+        assert_eq!((after.uid(), after.gid()), (before.uid(), before.gid()));
+        assert_eq!(fs::read_dir(&work).unwrap().count(), 0);
+
+        // Thin now, so there's nothing left to trim
+        assert!(replace(&canonical, &lipo, args, &work).is_err());
+        assert_eq!(fs::read_dir(&work).unwrap().count(), 0);
+
+        // A setuid binary of the user's stays theirs even when the client sends
+        // no owner, rather than becoming root's
+        let user = if unsafe { libc::geteuid() } == 0 {
+            (501, 20)
+        } else {
+            (before.uid(), before.gid())
+        };
+        let setuid = app.join("Tool");
+        write_fat(&setuid);
+        std::os::unix::fs::chown(&setuid, Some(user.0), Some(user.1)).unwrap();
+        fs::set_permissions(&setuid, Permissions::from_mode(0o4755)).unwrap();
+        let request = Request {
+            op: 0,
+            app: 0,
+            owner: None,
+            lipo_args: args.join(" "),
+            binary: setuid.clone(),
+        };
+        assert_eq!(
+            trim(&request, std::slice::from_ref(&app), &lipo, &work),
+            [Message::Trimmed(0)]
+        );
+        let after = fs::metadata(&setuid).unwrap();
+        assert_eq!(fs::read(&setuid).unwrap(), b"trimmed");
+        assert_eq!((after.uid(), after.gid()), user);
+        assert_eq!(after.mode() & 0o7777, 0o4755);
+
+        // A folder swapped for a link after the check isn't followed
+        let outside = dir.join("Outside");
+        fs::create_dir_all(&outside).unwrap();
+        write_fat(&outside.join("Slack"));
+        fs::remove_dir_all(&app).unwrap();
+        std::os::unix::fs::symlink(&outside, &app).unwrap();
+        assert!(replace(&canonical, &lipo, args, &work).is_err());
+        assert_ne!(fs::read(outside.join("Slack")).unwrap(), b"trimmed");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_authorized() {
+        assert!(authorized(0));
+        assert!(!authorized(u32::MAX - 1));
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use crate::ignore::IgnorePattern;
+use crate::xcode::XCODE_BUNDLE_ID;
+
+/// Bundle identifiers of software bintrim never trims, however it was selected:
+/// Apple's own apps and security agents that check their own integrity or sit
+/// between the system and everything else.
+pub const BUILT_IN: [&str; 13] = [
+    "com.apple.*",
+    "com.crowdstrike.*",
+    "com.sentinelone.*",
+    "com.microsoft.wdav*",
+    "com.paloaltonetworks.*",
+    "com.carbonblack.*",
+    "com.vmware.carbonblack.*",
+    "com.sophos.*",
+    "com.eset.*",
+    "com.malwarebytes.*",
+    "com.trendmicro.*",
+    "com.mcafee.*",
+    "com.jamf.protect*",
+];
+
+/// The built-in denylist minus the patterns from the `unprotect` setting.
+#[derive(Debug, Clone)]
+pub struct Denylist {
+    denied: Vec<IgnorePattern>,
+    /// Bundles that are trimmed despite matching the built-in list
+    unprotected: Vec<IgnorePattern>,
+}
+
+impl Default for Denylist {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Denylist {
+    pub fn new(unprotected: Vec<IgnorePattern>) -> Self {
+        Self {
+            denied: BUILT_IN.iter().map(|id| IgnorePattern::parse(id)).collect(),
+            unprotected,
+        }
+    }
+
+    /// Is the bundle at `path` off limits? Xcode is Apple's but is trimmed with
+    /// care of its own, see [`crate::xcode`].
+    pub fn denies(&self, path: &Path, bundle_id: Option<&str>) -> bool {
+        bundle_id != Some(XCODE_BUNDLE_ID)
+            && self
+                .denied
+                .iter()
+                .any(|pattern| pattern.matches(path, bundle_id))
+            && !self
+                .unprotected
+                .iter()
+                .any(|pattern| pattern.matches(path, bundle_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denies() {
+        let path = Path::new("/Applications/App.app");
+        let denylist = Denylist::default();
+        assert!(denylist.denies(path, Some("com.apple.Safari")));
+        assert!(denylist.denies(path, Some("com.crowdstrike.falcon.App")));
+        assert!(!denylist.denies(path, Some(XCODE_BUNDLE_ID)));
+        assert!(!denylist.denies(path, Some("com.tinyspeck.slackmacgap")));
+        assert!(!denylist.denies(path, None));
+
+        let denylist = Denylist::new(vec![IgnorePattern::parse("com.apple.iWork.*")]);
+        assert!(!denylist.denies(path, Some("com.apple.iWork.Keynote")));
+        assert!(denylist.denies(path, Some("com.apple.Safari")));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kinds of large developer files that bintrim reports but doesn't touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// An installed simulator runtime, such as iOS 17.5
+    SimulatorRuntime,
+    /// Simulator devices with their installed apps and data
+    SimulatorDevices,
+    /// Symbols copied from connected iPhones, Watches and other devices
+    DeviceSupport,
+    /// Archived builds from Xcode's Organizer
+    Archives,
+}
+
+/// Developer files that take up space outside of app bundles. They aren't Mach-O
+/// binaries lipo could thin, so they are only listed with how to remove them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Finds the artifacts of the system and of the user in `HOME`.
+pub fn find_artifacts() -> Vec<Artifact> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    find_artifacts_in(Path::new("/"), home.as_deref())
+}
+
+fn find_artifacts_in(root: &Path, home: Option<&Path>) -> Vec<Artifact> {
+    let simulator = root.join("Library/Developer/CoreSimulator");
+    let mut artifacts = Vec::new();
+
+    // Runtimes are disk images since Xcode 14, older ones are plain bundles
+    for dir in [
+        simulator.join("Images"),
+        simulator.join("Profiles/Runtimes"),
+    ] {
+        for path in sorted_entries(&dir) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "dmg" || extension == "simruntime")
+            {
+                artifacts.push(Artifact {
+                    kind: ArtifactKind::SimulatorRuntime,
+                    bytes: disk_usage(&path),
+                    path,
+                });
+            }
+        }
+    }
+
+    if let Some(home) = home {
+        let developer = home.join("Library/Developer");
+        let user_dirs = [
+            (
+                developer.join("CoreSimulator/Devices"),
+                ArtifactKind::SimulatorDevices,
+            ),
+            (developer.join("Xcode/Archives"), ArtifactKind::Archives),
+        ];
+        for (path, kind) in user_dirs {
+            add_dir(&mut artifacts, kind, path);
+        }
+        // One directory per platform, like `iOS DeviceSupport`
+        for path in sorted_entries(&developer.join("Xcode")) {
+            if path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(" DeviceSupport"))
+            {
+                add_dir(&mut artifacts, ArtifactKind::DeviceSupport, path);
+            }
+        }
+    }
+    artifacts
+}
+
+fn add_dir(artifacts: &mut Vec<Artifact>, kind: ArtifactKind, path: PathBuf) {
+    let bytes = disk_usage(&path);
+    if bytes > 0 {
+        artifacts.push(Artifact { kind, path, bytes });
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Size of a file, or of everything below a directory without following symlinks.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_artifacts() {
+        let root = std::env::temp_dir().join(format!("bintrim-devtools-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let images = root.join("Library/Developer/CoreSimulator/Images");
+        let home = root.join("Users/me");
+        let devices = home.join("Library/Developer/CoreSimulator/Devices/ABCD");
+        let device_support = home.join("Library/Developer/Xcode/iOS DeviceSupport/17.5");
+        fs::create_dir_all(&images).unwrap();
+        fs::create_dir_all(&devices).unwrap();
+        fs::create_dir_all(&device_support).unwrap();
+        fs::create_dir_all(home.join("Library/Developer/Xcode/Archives")).unwrap();
+        fs::write(images.join("iOS_21F79.dmg"), [0u8; 300]).unwrap();
+        fs::write(images.join("images.plist"), [0u8; 10]).unwrap();
+        fs::write(devices.join("device.plist"), [0u8; 200]).unwrap();
+        fs::write(device_support.join("Symbols"), [0u8; 100]).unwrap();
+
+        let artifacts = find_artifacts_in(&root, Some(&home));
+        fs::remove_dir_all(&root).unwrap();
+
+        // Empty directories like Archives are left out
+        assert_eq!(
+            artifacts
+                .iter()
+                .map(|artifact| (artifact.kind, artifact.bytes))
+                .collect::<Vec<_>>(),
+            [
+                (ArtifactKind::SimulatorRuntime, 300),
+                (ArtifactKind::SimulatorDevices, 200),
+                (ArtifactKind::DeviceSupport, 100),
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+/// How the finished frame is adapted for limited terminals, screen readers and
+/// logs, applied to the whole buffer after rendering.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DisplayOptions {
+    /// Replace box drawing, block and other non-ASCII symbols
+    pub ascii: bool,
+    /// Drop all colors, keeping only text attributes such as bold
+    pub no_color: bool,
+}
+
+impl DisplayOptions {
+    pub fn apply(&self, buffer: &mut Buffer) {
+        if !self.ascii && !self.no_color {
+            return;
+        }
+
+        for cell in buffer.content.iter_mut() {
+            if self.no_color {
+                cell.set_fg(Color::Reset).set_bg(Color::Reset);
+                cell.modifier.remove(Modifier::DIM);
+            }
+            if self.ascii && !cell.symbol().is_ascii() {
+                let replacement = ascii_symbol(cell.symbol());
+                cell.set_symbol(replacement);
+            }
+        }
+    }
+}
+
+/// Closest ASCII stand-in for a symbol drawn by ratatui or used in the UI.
+fn ascii_symbol(symbol: &str) -> &'static str {
+    match symbol {
+        "─" | "━" | "═" => "-",
+        "│" | "┃" | "║" => "|",
+        "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "├" | "┤" | "┬" | "┴" | "┼" => {
+            "+"
+        }
+        "█" | "▉" | "▊" | "▋" | "▌" | "▍" | "▎" | "▏" => "#",
+        "…" => "~",
+        "·" => "-",
+        "↑" => "^",
+        "↓" => "v",
+        "ä" => "a",
+        "ö" => "o",
+        "ü" => "u",
+        "Ä" => "A",
+        "Ö" => "O",
+        "Ü" => "U",
+        "ß" => "s",
+        _ => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+    use ratatui::widgets::{Block, Borders, Widget};
+
+    #[test]
+    fn test_apply() {
+        let area = Rect::new(0, 0, 6, 3);
+        let mut buffer = Buffer::empty(area);
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .render(area, &mut buffer);
+        buffer.set_string(1, 1, "↑…", Style::default().bg(Color::Red));
+
+        DisplayOptions {
+            ascii: true,
+            no_color: true,
+        }
+        .apply(&mut buffer);
+
+        assert_eq!(buffer, Buffer::with_lines(["+----+", "|^~  |", "+----+"]));
+    }
+
+    #[test]
+    fn test_apply_default_keeps_buffer() {
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buffer = Buffer::empty(area);
+        buffer.set_string(0, 0, "│", Style::default().fg(Color::Cyan));
+        let expected = buffer.clone();
+
+        DisplayOptions::default().apply(&mut buffer);
+        assert_eq!(buffer, expected);
+    }
+}
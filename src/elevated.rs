@@ -0,0 +1,240 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::cancel::CancelToken;
+use crate::command::{self, CommandError};
+use crate::tools;
+
+/// chown by absolute path, since the script runs with root's `PATH`.
+const CHOWN: &str = "/usr/sbin/chown";
+
+/// Defines `trim`, which the script calls once per binary. It prints one line per
+/// binary, `trimmed`, `chown-failed` (followed by `trimmed`, the binary is
+/// trimmed either way) or `failed`, with the error on the same line, and returns
+/// nonzero if lipo failed so the rest of the app is left alone. Once the cancel
+/// marker exists or sudo is gone, such as after bintrim timed out, it ends the
+/// script before touching the binary. `limit` kills a lipo still running after
+/// `$LIMIT` seconds, since bintrim itself can't signal a root process.
+const PRELUDE: &str = r#"limit() {
+    "$@" &
+    pid=$!
+    (sleep "$LIMIT"; kill -KILL "$pid") >/dev/null 2>&1 &
+    watchdog=$!
+    wait "$pid"
+    status=$?
+    kill "$watchdog" 2>/dev/null
+    return $status
+}
+trim() {
+    op=$1 binary=$2 owner=$3
+    shift 3
+    { [ -e "$CANCEL" ] || ! kill -0 "$PPID" 2>/dev/null; } && exit 0
+    if ! out=$(limit "$LIPO" "$binary" "$@" -output "$binary" 2>&1); then
+        echo "failed $op $(printf '%s' "$out" | tr '\n' ' ')"
+        return 1
+    fi
+    if [ "$owner" != - ] && ! out=$("$CHOWN" "$owner" "$binary" 2>&1); then
+        echo "chown-failed $op $(printf '%s' "$out" | tr '\n' ' ')"
+    fi
+    echo "trimmed $op"
+}
+"#;
+
+/// One binary to trim in the elevated script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    /// Index of the app in the batch. Once one of an app's binaries fails, its
+    /// remaining ones are skipped.
+    pub app: usize,
+    pub binary: PathBuf,
+    /// What lipo does to the binary, such as `-remove x86_64`
+    pub lipo_args: &'static [&'static str],
+    /// User and group ID to give the trimmed binary, or `None` to leave it to lipo
+    pub owner: Option<(u32, u32)>,
+}
+
+/// A line the script printed, with operations as indices into the batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The script got to the app with this index
+    AppStarted(usize),
+    Trimmed(usize),
+    /// The binary was trimmed but its owner couldn't be restored
+    ChownFailed(usize, String),
+    /// lipo failed, which ends the app
+    Failed(usize, String),
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Self> {
+        let (kind, rest) = line.split_once(' ')?;
+        let (index, error) = rest.split_once(' ').unwrap_or((rest, ""));
+        let index = index.parse().ok()?;
+        let error = error.trim().to_string();
+        match kind {
+            "start" => Some(Message::AppStarted(index)),
+            "trimmed" => Some(Message::Trimmed(index)),
+            "chown-failed" => Some(Message::ChownFailed(index, error)),
+            "failed" => Some(Message::Failed(index, error)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::AppStarted(app) => write!(f, "start {}", app),
+            Message::Trimmed(op) => write!(f, "trimmed {}", op),
+            Message::ChownFailed(op, error) => write!(f, "chown-failed {} {}", op, error),
+            Message::Failed(op, error) => write!(f, "failed {} {}", op, error),
+        }
+    }
+}
+
+/// The shell script that performs `operations` in order, as one elevated process
+/// instead of a `sudo` per lipo and chown. It stops before the next binary once
+/// `cancel_marker` exists, and gives each lipo up to `timeout`.
+pub fn script(operations: &[Operation], cancel_marker: &Path, timeout: Duration) -> String {
+    let mut script = format!(
+        "LIPO={}\nCHOWN={}\nCANCEL={}\nLIMIT={}\n{}",
+        quote(&tools::lipo().to_string_lossy()),
+        quote(CHOWN),
+        quote_path(cancel_marker),
+        timeout.as_secs().max(1),
+        PRELUDE
+    );
+    for (index, operation) in operations.iter().enumerate() {
+        let first_of_app = index == 0 || operations[index - 1].app != operation.app;
+        if first_of_app {
+            script.push_str(&format!("\necho \"start {}\"\n", operation.app));
+        } else {
+            script.push_str(" &&\n    ");
+        }
+        let owner = operation
+            .owner
+            .map_or("-".to_string(), |(uid, gid)| format!("{}:{}", uid, gid));
+        script.push_str(&format!(
+            "trim {} {} {} {}",
+            index,
+            quote_path(&operation.binary),
+            owner,
+            operation.lipo_args.join(" ")
+        ));
+    }
+    script.push('\n');
+    script
+}
+
+/// Runs the script for `operations` with `sudo -S`, reading `password` if sudo asks
+/// for one, and calls `on_message` as each line arrives. The whole script may take
+/// `timeout` per operation, and ends early once `cancel` is cancelled.
+pub fn run(
+    operations: &[Operation],
+    password: &str,
+    timeout: Duration,
+    cancel: &CancelToken,
+    mut on_message: impl FnMut(Message),
+) -> Result<Output, CommandError> {
+    let script = script(operations, cancel.marker(), timeout);
+    let timeout = timeout
+        .checked_mul(operations.len().max(1) as u32)
+        .unwrap_or(Duration::MAX);
+    command::run_lines(
+        Command::new("sudo")
+            .arg("-S") // Read password from stdin
+            .arg("/bin/sh")
+            .arg("-c")
+            .arg(script),
+        Some(&format!("{}\n", password)),
+        timeout,
+        |line| match Message::parse(line) {
+            Some(message) => on_message(message),
+            None => debug!(line, "unexpected output of the trim script"),
+        },
+    )
+}
+
+fn quote_path(path: &Path) -> String {
+    quote(&path.to_string_lossy())
+}
+
+/// `text` in single quotes for the shell.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script() {
+        let operation = |app, binary: &str, owner| Operation {
+            app,
+            binary: PathBuf::from(binary),
+            lipo_args: &["-remove", "x86_64"],
+            owner,
+        };
+        let operations = [
+            operation(
+                0,
+                "/Applications/Slack.app/Contents/MacOS/Slack",
+                Some((0, 80)),
+            ),
+            operation(0, "/Applications/Slack.app/Contents/Frameworks/a'b", None),
+            operation(2, "/Applications/zoom.us.app/Contents/MacOS/zoom.us", None),
+        ];
+        let cancel = CancelToken::default();
+        let script = script(&operations, cancel.marker(), Duration::from_secs(1));
+        let body = script.split_once(PRELUDE).unwrap().1;
+
+        assert_eq!(
+            body,
+            "\necho \"start 0\"\n\
+             trim 0 '/Applications/Slack.app/Contents/MacOS/Slack' 0:80 -remove x86_64 &&\n    \
+             trim 1 '/Applications/Slack.app/Contents/Frameworks/a'\\''b' - -remove x86_64\n\
+             echo \"start 2\"\n\
+             trim 2 '/Applications/zoom.us.app/Contents/MacOS/zoom.us' - -remove x86_64\n"
+        );
+
+        // The script runs as is without sudo, with a lipo that fails
+        let run = |lipo: &str| {
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(script.replace(&quote(&tools::lipo().to_string_lossy()), lipo))
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(Message::parse)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            run("false"),
+            [
+                Message::AppStarted(0),
+                Message::Failed(0, String::new()),
+                Message::AppStarted(2),
+                Message::Failed(2, String::new()),
+            ]
+        );
+
+        // A lipo that hangs is killed after the limit
+        let hang = std::env::temp_dir().join(format!("bintrim-hang-{}", std::process::id()));
+        std::fs::write(&hang, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&hang, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(run(&quote_path(&hang)).len(), 4);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        std::fs::remove_file(&hang).unwrap();
+
+        cancel.cancel();
+        assert_eq!(run("false"), [Message::AppStarted(0)]);
+    }
+}
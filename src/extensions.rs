@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner;
+
+/// Extensions of native modules loaded by bundled Python (`.so`) and Node (`.node`)
+/// interpreters, and of the libraries they link.
+const EXTENSIONS: [&str; 3] = ["so", "node", "dylib"];
+
+/// Universal native extensions anywhere under `Contents/Resources`, with the bytes
+/// trimming each one frees. Part of deep inspection, since walking the resources of
+/// large apps takes a while.
+pub fn fat_extensions(app_path: &Path) -> Vec<(PathBuf, u64)> {
+    let mut extensions = Vec::new();
+    let mut pending = vec![app_path.join("Contents/Resources")];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| EXTENSIONS.iter().any(|known| extension == *known))
+                && let Some(savings) = scanner::nested_savings(&path)
+            {
+                extensions.push((path, savings));
+            }
+        }
+    }
+    extensions.sort();
+    extensions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fat(path: &Path) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_fat_extensions() {
+        let app = std::env::temp_dir().join(format!("bintrim-ext-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&app);
+        let numpy = app.join("Contents/Resources/lib/python3.12/site-packages/numpy");
+        let node = app.join("Contents/Resources/app.asar.unpacked/node_modules/keytar/build");
+        fs::create_dir_all(&numpy).unwrap();
+        fs::create_dir_all(&node).unwrap();
+        write_fat(&numpy.join("_multiarray_umath.cpython-312-darwin.so"));
+        write_fat(&node.join("keytar.node"));
+        // Universal, but not a native extension
+        write_fat(&node.join("helper"));
+
+        let extensions = fat_extensions(&app);
+        fs::remove_dir_all(&app).unwrap();
+
+        let names: Vec<_> = extensions
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["keytar.node", "_multiarray_umath.cpython-312-darwin.so"]
+        );
+    }
+}
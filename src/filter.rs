@@ -0,0 +1,231 @@
+use std::fmt;
+
+use regex_lite::{Regex, RegexBuilder};
+
+use crate::arch::Arch;
+use crate::bytesize::ByteSize;
+use crate::scanner::AppInfo;
+
+/// Prefix of the LSApplicationCategoryType values, which filters may leave out.
+const CATEGORY_PREFIX: &str = "public.app-category.";
+
+/// Narrows the apps down by savings, name, architecture and category. The same
+/// filter backs `--min-size`, `--name`, `--arch` and `--category`, the filter
+/// prompt of the interface and its category key.
+#[derive(Debug, Clone, Default)]
+pub struct AppFilter {
+    /// Only apps that free at least this much
+    pub min_size: Option<ByteSize>,
+    /// Only apps whose name or bundle identifier matches, ignoring case
+    pub name: Option<Regex>,
+    /// Only apps with all of these architectures
+    pub architectures: Vec<Arch>,
+    /// Only apps with this LSApplicationCategoryType, such as
+    /// `public.app-category.games` or just `games`
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    /// Not a size like `50MB`
+    Size(String),
+    /// Not a valid regular expression
+    Name { pattern: String, error: String },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Size(size) => {
+                write!(
+                    f,
+                    "invalid size {:?}, expected e.g. 500KB, 50MB or 1.5GB",
+                    size
+                )
+            }
+            FilterError::Name { pattern, error } => {
+                write!(f, "invalid name pattern {:?}: {}", pattern, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl AppFilter {
+    /// Parses a filter as typed into the interface: `>50MB` (or `size>50MB`),
+    /// `arch:x86_64` and `cat:games` terms, with the remaining words making up the
+    /// name pattern, such as `slack|zoom >50MB`.
+    pub fn parse(query: &str) -> Result<Self, FilterError> {
+        let mut filter = AppFilter::default();
+        let mut name = Vec::new();
+        for term in query.split_whitespace() {
+            if let Some(size) = term
+                .strip_prefix("size>")
+                .or_else(|| term.strip_prefix('>'))
+            {
+                filter.min_size = Some(parse_size(size)?);
+            } else if let Some(arch) = term.strip_prefix("arch:") {
+                filter.architectures.push(Arch::from_name(arch));
+            } else if let Some(category) = term.strip_prefix("cat:") {
+                filter.category = Some(category.to_string());
+            } else {
+                name.push(term);
+            }
+        }
+        if !name.is_empty() {
+            filter.set_name(&name.join(" "))?;
+        }
+        Ok(filter)
+    }
+
+    pub fn set_name(&mut self, pattern: &str) -> Result<(), FilterError> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| FilterError::Name {
+                pattern: pattern.to_string(),
+                error: err.to_string(),
+            })?;
+        self.name = Some(regex);
+        Ok(())
+    }
+
+    pub fn matches(&self, app: &AppInfo) -> bool {
+        self.min_size
+            .is_none_or(|min_size| app.prune_size().is_some_and(|size| size >= min_size))
+            && self.name.as_ref().is_none_or(|name| {
+                name.is_match(&app.name)
+                    || app
+                        .bundle_id
+                        .as_deref()
+                        .is_some_and(|bundle_id| name.is_match(bundle_id))
+            })
+            && self
+                .architectures
+                .iter()
+                .all(|wanted| app.architectures.iter().any(|arch| arch.arch == *wanted))
+            && self.category.as_deref().is_none_or(|category| {
+                app.category.as_deref().is_some_and(|app_category| {
+                    app_category == category
+                        || app_category.strip_prefix(CATEGORY_PREFIX) == Some(category)
+                })
+            })
+    }
+
+    /// Does the filter narrow the apps down at all?
+    pub fn is_active(&self) -> bool {
+        self.min_size.is_some()
+            || self.name.is_some()
+            || !self.architectures.is_empty()
+            || self.category.is_some()
+    }
+
+    /// The filter in the syntax of [`AppFilter::parse`], to edit it in the interface.
+    pub fn query(&self) -> String {
+        let mut terms = Vec::new();
+        if let Some(name) = &self.name {
+            terms.push(name.as_str().to_string());
+        }
+        if let Some(min_size) = self.min_size {
+            terms.push(format!(">{}", format_size(min_size.bytes())));
+        }
+        terms.extend(
+            self.architectures
+                .iter()
+                .map(|arch| format!("arch:{}", arch)),
+        );
+        if let Some(category) = &self.category {
+            terms.push(format!("cat:{}", category));
+        }
+        terms.join(" ")
+    }
+}
+
+/// Parses a size such as `50MB`, `1.5G`, `500k` or `1024` (bytes). Units are
+/// powers of 1024 like the sizes bintrim shows.
+pub fn parse_size(text: &str) -> Result<ByteSize, FilterError> {
+    let invalid = || FilterError::Size(text.to_string());
+    let trimmed = text.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let factor: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        _ => return Err(invalid()),
+    };
+    Ok(ByteSize((number * factor as f64) as u64))
+}
+
+/// Formats a size for [`parse_size`] in the biggest unit that keeps it whole.
+fn format_size(bytes: u64) -> String {
+    for (unit, factor) in [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10)] {
+        if bytes >= factor && bytes.is_multiple_of(factor) {
+            return format!("{}{}", bytes / factor, unit);
+        }
+    }
+    bytes.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(ByteSize(1024)));
+        assert_eq!(parse_size("500k"), Ok(ByteSize(500 * 1024)));
+        assert_eq!(parse_size("50MB"), Ok(ByteSize(50 * 1024 * 1024)));
+        assert_eq!(parse_size("1.5G"), Ok(ByteSize(1536 * 1024 * 1024)));
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("50TB").is_err());
+    }
+
+    #[test]
+    fn test_parse_and_query() {
+        let filter = AppFilter::parse("slack|zoom >50MB arch:x86_64 cat:games").unwrap();
+        assert_eq!(filter.min_size, Some(ByteSize(50 * 1024 * 1024)));
+        assert_eq!(filter.architectures, [Arch::X86_64]);
+        assert_eq!(filter.category.as_deref(), Some("games"));
+        assert_eq!(filter.query(), "slack|zoom >50MB arch:x86_64 cat:games");
+        assert!(filter.is_active());
+        assert!(AppFilter::parse("").unwrap().query().is_empty());
+        assert!(!AppFilter::parse("").unwrap().is_active());
+        assert!(AppFilter::parse("cat:games").unwrap().is_active());
+        assert!(matches!(
+            AppFilter::parse("(unclosed"),
+            Err(FilterError::Name { .. })
+        ));
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", Some(80)), ("x86_64", Some(60))]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        slack.category = Some("public.app-category.business".to_string());
+        let zoom = AppInfo::for_test("zoom.us", &[("arm64", Some(30)), ("x86_64", Some(20))]);
+        let native = AppInfo::for_test("Native", &[("arm64", Some(30))]);
+        let apps = [slack, zoom, native];
+
+        let names = |query: &str| {
+            let filter = AppFilter::parse(query).unwrap();
+            apps.iter()
+                .filter(|app| filter.matches(app))
+                .map(|app| app.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(""), ["Slack", "zoom.us", "Native"]);
+        assert_eq!(names("SLACK"), ["Slack"]);
+        assert_eq!(names("tinyspeck"), ["Slack"]);
+        assert_eq!(names("^z"), ["zoom.us"]);
+        assert_eq!(names("arch:x86_64"), ["Slack", "zoom.us"]);
+        assert_eq!(names(">40"), ["Slack"]);
+        assert_eq!(names("cat:business"), ["Slack"]);
+        assert_eq!(names("cat:public.app-category.business"), ["Slack"]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::Path;
+
+/// Stored with transparent APFS/HFS+ compression.
+const UF_COMPRESSED: u32 = 0x0000_0020;
+/// `chflags uchg`: the owner made the file immutable.
+const UF_IMMUTABLE: u32 = 0x0000_0002;
+/// `chflags hidden`: hidden from the Finder.
+const UF_HIDDEN: u32 = 0x0000_8000;
+/// `chflags schg`: immutable until the system is booted into single user mode.
+const SF_IMMUTABLE: u32 = 0x0002_0000;
+
+/// BSD file flags of a path, as set by `chflags`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileFlags(u32);
+
+impl FileFlags {
+    /// Reads the flags of `path` without following a trailing symlink.
+    pub fn for_path(path: &Path) -> Option<Self> {
+        fs::symlink_metadata(path)
+            .ok()
+            .map(|metadata| Self(st_flags(&metadata)))
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.0 & UF_HIDDEN != 0
+    }
+
+    pub fn compressed(&self) -> bool {
+        self.0 & UF_COMPRESSED != 0
+    }
+
+    /// Is the user or system immutable flag set? Even root can't modify the file then.
+    pub fn immutable(&self) -> bool {
+        self.0 & (UF_IMMUTABLE | SF_IMMUTABLE) != 0
+    }
+}
+
+/// Is `binary` or the directory holding it immutable? lipo can't replace the
+/// binary in either case.
+pub fn is_immutable(binary: &Path) -> bool {
+    FileFlags::for_path(binary).is_some_and(|flags| flags.immutable())
+        || binary
+            .parent()
+            .and_then(FileFlags::for_path)
+            .is_some_and(|flags| flags.immutable())
+}
+
+#[cfg(target_os = "macos")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::macos::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn st_flags(_metadata: &fs::Metadata) -> u32 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        assert!(FileFlags(UF_HIDDEN).hidden());
+        assert!(!FileFlags(UF_HIDDEN).immutable());
+        assert!(FileFlags(UF_IMMUTABLE).immutable());
+        assert!(FileFlags(SF_IMMUTABLE | UF_HIDDEN).immutable());
+        assert!(FileFlags(UF_COMPRESSED).compressed());
+        assert_eq!(
+            FileFlags::for_path(&std::env::temp_dir()).map(|flags| flags.immutable()),
+            Some(false)
+        );
+        assert!(!is_immutable(Path::new("/nonexistent/bintrim")));
+    }
+}
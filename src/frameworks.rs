@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner;
+
+/// Universal binaries in the app's `Contents/Frameworks`, with the bytes trimming
+/// each one frees: the executable of every framework, loose dylibs and the
+/// executables of helper apps such as Electron's `Slack Helper (Renderer).app`.
+pub fn fat_binaries(app_path: &Path) -> Vec<(PathBuf, u64)> {
+    let mut binaries: Vec<_> = entries(&app_path.join("Contents/Frameworks"))
+        .into_iter()
+        .flat_map(|path| match path.extension().and_then(|ext| ext.to_str()) {
+            Some("framework") => framework_binary(&path).into_iter().collect(),
+            Some("dylib") => vec![path],
+            Some("app") => entries(&path.join("Contents/MacOS")),
+            _ => Vec::new(),
+        })
+        .filter(|path| path.is_file())
+        .filter_map(|path| Some((path.clone(), scanner::nested_savings(&path)?)))
+        .collect();
+    binaries.sort();
+    binaries.dedup_by(|a, b| a.0 == b.0);
+    binaries
+}
+
+/// The executable of a framework, usually `Versions/Current/<name>`, with the
+/// links inside the framework resolved so each one is trimmed once.
+fn framework_binary(framework: &Path) -> Option<PathBuf> {
+    let name = framework.file_stem()?;
+    let binary = fs::canonicalize(framework.join(name)).ok()?;
+    let relative = binary
+        .strip_prefix(fs::canonicalize(framework).ok()?)
+        .ok()?
+        .to_path_buf();
+    Some(framework.join(relative))
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fat(path: &Path) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_fat_binaries() {
+        let app =
+            std::env::temp_dir().join(format!("bintrim-frameworks-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&app);
+        let frameworks = app.join("Contents/Frameworks");
+        let electron = frameworks.join("Electron Framework.framework");
+        fs::create_dir_all(electron.join("Versions/A")).unwrap();
+        write_fat(&electron.join("Versions/A/Electron Framework"));
+        std::os::unix::fs::symlink("A", electron.join("Versions/Current")).unwrap();
+        std::os::unix::fs::symlink(
+            "Versions/Current/Electron Framework",
+            electron.join("Electron Framework"),
+        )
+        .unwrap();
+        let helper = frameworks.join("Slack Helper.app/Contents/MacOS");
+        fs::create_dir_all(&helper).unwrap();
+        write_fat(&helper.join("Slack Helper"));
+        write_fat(&frameworks.join("libffmpeg.dylib"));
+        fs::write(frameworks.join("thin.dylib"), [0u8; 100]).unwrap();
+
+        let binaries = fat_binaries(&app);
+        fs::remove_dir_all(&app).unwrap();
+
+        let paths: Vec<_> = binaries.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                frameworks.join("Electron Framework.framework/Versions/A/Electron Framework"),
+                frameworks.join("Slack Helper.app/Contents/MacOS/Slack Helper"),
+                frameworks.join("libffmpeg.dylib"),
+            ]
+        );
+        assert!(binaries.iter().all(|(_, bytes)| *bytes == 16384 * 2 - 1000));
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner::AppInfo;
+
+/// Group of helpers in the app list.
+pub const HELPERS_GROUP: &str = "Helpers";
+
+/// Where installers put the helper tools that launchd runs as root for an app.
+const PRIVILEGED_TOOLS_DIR: &str = "/Library/PrivilegedHelperTools";
+
+/// Programs of launchd jobs under these are part of macOS and protected by SIP.
+const SYSTEM_PREFIXES: [&str; 4] = ["/System/", "/usr/", "/bin/", "/sbin/"];
+
+/// A helper daemon or launch agent that an app installed outside of its bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Helper {
+    /// launchd label, or the file name of a privileged helper tool (usually the same)
+    pub label: String,
+    pub binary_path: PathBuf,
+    /// Name of the app that installed the helper, if it could be told
+    pub parent: Option<String>,
+}
+
+/// Finds the privileged helper tools and the programs of third-party launch agents
+/// and daemons, including the current user's.
+pub fn find_helpers() -> Vec<Helper> {
+    let mut launchd_dirs = vec![
+        PathBuf::from("/Library/LaunchAgents"),
+        PathBuf::from("/Library/LaunchDaemons"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        launchd_dirs.push(PathBuf::from(home).join("Library/LaunchAgents"));
+    }
+    find_helpers_in(Path::new(PRIVILEGED_TOOLS_DIR), &launchd_dirs)
+}
+
+/// Finds the privileged helper tools only, which are trimmed along with the app
+/// they belong to unless helpers are listed on their own.
+pub fn privileged_tools() -> Vec<Helper> {
+    tools_in(Path::new(PRIVILEGED_TOOLS_DIR))
+}
+
+fn find_helpers_in(tools_dir: &Path, launchd_dirs: &[PathBuf]) -> Vec<Helper> {
+    let mut helpers = tools_in(tools_dir);
+
+    for dir in launchd_dirs {
+        for plist in sorted_entries(dir) {
+            // A job for a privileged helper tool names the same binary
+            if let Some(helper) = read_launchd_plist(&plist)
+                && !helpers
+                    .iter()
+                    .any(|known| known.binary_path == helper.binary_path)
+            {
+                helpers.push(helper);
+            }
+        }
+    }
+    helpers
+}
+
+/// The files in `dir`, named after their launchd labels.
+fn tools_in(dir: &Path) -> Vec<Helper> {
+    sorted_entries(dir)
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            Some(Helper {
+                label: path.file_name()?.to_string_lossy().into_owned(),
+                binary_path: path,
+                parent: None,
+            })
+        })
+        .collect()
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// The program a launchd job runs, from `Program` or the first of `ProgramArguments`.
+fn read_launchd_plist(path: &Path) -> Option<Helper> {
+    if path
+        .extension()
+        .is_none_or(|extension| extension != "plist")
+    {
+        return None;
+    }
+    let job = plist::Value::from_file(path).ok()?.into_dictionary()?;
+    let label = job.get("Label")?.as_string()?;
+    let program = job
+        .get("Program")
+        .and_then(plist::Value::as_string)
+        .or_else(|| {
+            job.get("ProgramArguments")?
+                .as_array()?
+                .first()?
+                .as_string()
+        })?;
+
+    (program.starts_with('/')
+        && !SYSTEM_PREFIXES
+            .iter()
+            .any(|prefix| program.starts_with(prefix)))
+    .then(|| Helper {
+        label: label.to_string(),
+        binary_path: PathBuf::from(program),
+        parent: None,
+    })
+}
+
+/// The app a helper belongs to: the one whose bundle contains it, or else the one
+/// whose bundle identifier shares the most leading components with the helper's
+/// label, like `com.docker.docker` with `com.docker.vmnetd`. The organization has
+/// to match at least.
+pub fn parent_app<'a>(helper: &Helper, apps: &'a [AppInfo]) -> Option<&'a AppInfo> {
+    if let Some(app) = apps
+        .iter()
+        .find(|app| helper.binary_path.starts_with(&app.path))
+    {
+        return Some(app);
+    }
+    apps.iter()
+        .filter_map(|app| {
+            let shared = app
+                .bundle_id
+                .as_deref()?
+                .split('.')
+                .zip(helper.label.split('.'))
+                .take_while(|(a, b)| a == b)
+                .count();
+            (shared >= 2).then_some((shared, app))
+        })
+        .max_by_key(|(shared, _)| *shared)
+        .map(|(_, app)| app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_helpers() {
+        let root = std::env::temp_dir().join(format!("bintrim-helpers-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let tools = root.join("PrivilegedHelperTools");
+        let agents = root.join("LaunchAgents");
+        fs::create_dir_all(&tools).unwrap();
+        fs::create_dir_all(&agents).unwrap();
+        let vmnetd = tools.join("com.docker.vmnetd");
+        fs::write(&vmnetd, b"").unwrap();
+
+        let job = |label: &str, program: &Path| {
+            let mut job = plist::Dictionary::new();
+            job.insert("Label".to_string(), label.into());
+            job.insert(
+                "ProgramArguments".to_string(),
+                vec![plist::Value::from(program.to_string_lossy().as_ref())].into(),
+            );
+            plist::Value::Dictionary(job)
+                .to_file_xml(agents.join(format!("{}.plist", label)))
+                .unwrap();
+        };
+        job("com.docker.vmnetd", &vmnetd);
+        job(
+            "com.google.keystone.agent",
+            Path::new("/Library/Google/GoogleSoftwareUpdateAgent"),
+        );
+        job("com.example.system", Path::new("/usr/bin/true"));
+
+        let helpers = find_helpers_in(&tools, std::slice::from_ref(&agents));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            helpers
+                .iter()
+                .map(|helper| helper.label.as_str())
+                .collect::<Vec<_>>(),
+            ["com.docker.vmnetd", "com.google.keystone.agent"]
+        );
+    }
+
+    #[test]
+    fn test_parent_app() {
+        let mut docker = AppInfo::for_test("Docker", &[("arm64", None)]);
+        docker.bundle_id = Some("com.docker.docker".to_string());
+        let mut chrome = AppInfo::for_test("Google Chrome", &[("arm64", None)]);
+        chrome.bundle_id = Some("com.google.Chrome".to_string());
+        let apps = [docker, chrome];
+        let helper = |label: &str, binary_path: &str| Helper {
+            label: label.to_string(),
+            binary_path: PathBuf::from(binary_path),
+            parent: None,
+        };
+        let parent = |helper: Helper| parent_app(&helper, &apps).map(|app| app.name.as_str());
+
+        assert_eq!(
+            parent(helper(
+                "com.docker.vmnetd",
+                "/Library/PrivilegedHelperTools/com.docker.vmnetd"
+            )),
+            Some("Docker")
+        );
+        assert_eq!(
+            parent(helper(
+                "com.google.keystone.agent",
+                "/Library/Google/GoogleSoftwareUpdateAgent"
+            )),
+            Some("Google Chrome")
+        );
+        assert_eq!(
+            parent(helper(
+                "org.example.agent",
+                "/Applications/Google Chrome.app/Contents/Helpers/agent"
+            )),
+            Some("Google Chrome")
+        );
+        assert_eq!(parent(helper("com.example.agent", "/opt/agent")), None);
+    }
+}
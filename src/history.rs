@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::state;
+
+/// Bundles bintrim has trimmed before, stored in `~/.local/state/bintrim/trimmed.toml`,
+/// so an app that an update made universal again stands out in the next scan.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TrimHistory {
+    apps: BTreeSet<PathBuf>,
+}
+
+impl TrimHistory {
+    /// Loads the history. A missing or unreadable file means nothing was trimmed yet.
+    pub fn load() -> Self {
+        history_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn contains(&self, bundle: &Path) -> bool {
+        self.apps.contains(bundle)
+    }
+
+    /// Adds `bundles` to the stored history.
+    pub fn record<'a>(bundles: impl IntoIterator<Item = &'a Path>) -> color_eyre::Result<()> {
+        let mut history = Self::load();
+        let before = history.apps.len();
+        history
+            .apps
+            .extend(bundles.into_iter().map(Path::to_path_buf));
+        let Some(path) = history_path().filter(|_| history.apps.len() > before) else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, toml::to_string(&history)?)
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    state::state_dir().map(|dir| dir.join("trimmed.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let history = TrimHistory {
+            apps: BTreeSet::from([PathBuf::from("/Applications/Slack.app")]),
+        };
+        let serialized = toml::to_string(&history).unwrap();
+        assert_eq!(serialized, "apps = [\"/Applications/Slack.app\"]\n");
+
+        let parsed: TrimHistory = toml::from_str(&serialized).unwrap();
+        assert!(parsed.contains(Path::new("/Applications/Slack.app")));
+        assert!(!parsed.contains(Path::new("/Applications/Figma.app")));
+    }
+}
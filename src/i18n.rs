@@ -0,0 +1,932 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::devtools::ArtifactKind;
+use crate::inspect::Risk;
+use crate::scanner::Badge;
+use crate::xcode::Protection;
+
+/// Every string the TUI shows. Log messages and error details from `lipo` and
+/// `sudo` stay in English.
+///
+/// To add a translation, fill in another `Strings` and match its language code in
+/// [`for_language`].
+pub struct Strings {
+    // Loading and trimming screens
+    pub scanning_title: &'static str,
+    pub initializing: &'static str,
+    pub scan_skipped: fn(usize, usize) -> String,
+    pub trimming_title: &'static str,
+    pub rescanning_title: &'static str,
+    pub trim_freed: fn(&str) -> String,
+    pub rescanning: fn(&str) -> String,
+    pub trimming: fn(&str) -> String,
+    pub preparing_to_trim: fn(&str) -> String,
+    pub preparing: &'static str,
+    pub cancelling: &'static str,
+
+    // Header and status line
+    pub apps_shown: fn(usize, usize) -> String,
+    pub hidden_note: fn(usize, &str) -> String,
+    pub hidden_arm64_only: fn(usize) -> String,
+    pub hidden_not_writable: fn(usize) -> String,
+    pub hidden_excluded: fn(usize) -> String,
+    pub hidden_protected: fn(usize) -> String,
+    pub hidden_other: fn(usize) -> String,
+    pub sorted_by_size: &'static str,
+    pub sorted_by_name: &'static str,
+    pub sorted_by_potential: &'static str,
+    pub update_available: fn(&str) -> String,
+    pub audit_mode_header: &'static str,
+    pub audit_mode_plain: &'static str,
+    pub hint_toggle: &'static str,
+    pub hint_all: &'static str,
+    pub hint_undo: &'static str,
+    pub hint_trim: &'static str,
+    pub hint_sort_by_name: &'static str,
+    pub hint_sort_by_size: &'static str,
+    pub hint_sort_by_potential: &'static str,
+    pub potential_savings: fn(&str, &str, &str, &str, &str) -> String,
+    pub size_history: fn(&str, &str, u64) -> String,
+    pub hint_show_prunable: &'static str,
+    pub hint_show_all: &'static str,
+    pub hint_skipped: &'static str,
+    pub hint_summary: &'static str,
+    pub hint_columns: &'static str,
+    pub hint_breakdown: &'static str,
+    pub hint_vendors: &'static str,
+    pub hint_developer: &'static str,
+    pub hint_category: &'static str,
+    pub hint_filter: &'static str,
+    pub hint_savings: &'static str,
+    pub trimmed_off_estimate: fn(usize, usize) -> String,
+    pub savings_title: &'static str,
+    pub savings_header: &'static str,
+    pub savings_off: &'static str,
+    pub savings_off_hint: &'static str,
+    pub hint_copy: &'static str,
+    pub hint_legend: &'static str,
+    pub hint_strategy: &'static str,
+    pub copied: fn(usize) -> String,
+    pub strategy_changed: fn(&str, &str) -> String,
+    pub strategy_unchangeable: fn(&str) -> String,
+    pub copy_failed: fn(&str) -> String,
+    pub filter_title: &'static str,
+    pub filter_syntax: &'static str,
+    pub hint_errors: &'static str,
+    pub hint_nav: &'static str,
+    pub hint_next_selected: &'static str,
+    pub hint_quit: &'static str,
+    pub hint_close: &'static str,
+    pub hint_retry: &'static str,
+    pub hint_scroll: &'static str,
+    pub hint_esc_close: &'static str,
+    pub hint_trim_anyway: &'static str,
+    pub hint_confirm_trim: &'static str,
+    pub hint_cancel: &'static str,
+    pub hint_confirm: &'static str,
+    pub hint_clear: &'static str,
+    pub hint_terminal_prompt: &'static str,
+    pub hint_esc_cancel: &'static str,
+    pub trimmed: fn(usize) -> String,
+    pub trim_failed_status: fn(usize) -> String,
+    pub trim_cancelled_status: fn(usize) -> String,
+    pub scan_cancelled: &'static str,
+    pub sudo_failed: &'static str,
+    pub lipo_missing: &'static str,
+    pub lipo_missing_status: &'static str,
+    pub report_failed: fn(&str) -> String,
+    pub nothing_to_undo: &'static str,
+
+    // App list
+    pub column_name: &'static str,
+    pub column_architectures: &'static str,
+    pub column_slices: &'static str,
+    pub column_size: &'static str,
+    pub column_notes: &'static str,
+    pub column_badges: &'static str,
+    pub not_available: &'static str,
+    pub volume_free: fn(&str) -> String,
+    pub network_volume: &'static str,
+    pub badge_description: fn(Badge) -> &'static str,
+    pub risk_description: fn(Risk) -> &'static str,
+
+    // Summary
+    pub summary_title: &'static str,
+    pub prunable_apps: &'static str,
+    pub total_size: &'static str,
+    pub architectures_label: &'static str,
+    pub stats_universal: fn(usize) -> String,
+    pub stats_arm64_only: fn(usize) -> String,
+    pub stats_x86_64_only: fn(usize) -> String,
+    pub stats_median_slice: fn(&str) -> String,
+    pub selected: &'static str,
+    pub prune_size: &'static str,
+    pub of_total: fn(&str) -> String,
+    pub shown: fn(&str) -> String,
+    pub links_to: fn(&Path) -> String,
+    pub helper_of: fn(&str) -> String,
+    pub helper_unknown_app: &'static str,
+
+    // Popups
+    pub skipped_title: fn(usize) -> String,
+    pub no_skipped: &'static str,
+    pub columns_title: &'static str,
+    pub legend_title: &'static str,
+    pub breakdown_title: fn(&str) -> String,
+    pub breakdown_analyzing: &'static str,
+    pub breakdown_total: fn(&str) -> String,
+    pub breakdown_dirs: &'static str,
+    pub breakdown_files: &'static str,
+    pub breakdown_fat: &'static str,
+    pub breakdown_localizations: fn(usize) -> String,
+    pub breakdown_savings: &'static str,
+    pub breakdown_none: &'static str,
+    pub breakdown_xcode: &'static str,
+    pub breakdown_xcode_tools: fn(usize) -> String,
+    pub breakdown_xcode_kept: &'static str,
+    pub xcode_protection: fn(Protection) -> &'static str,
+    pub vendors_title: &'static str,
+    pub vendor_line: fn(&str, &str, usize) -> String,
+    pub vendor_unknown: &'static str,
+    pub no_prunable_vendors: &'static str,
+    pub developer_files: &'static str,
+    pub developer_files_total: fn(&str) -> String,
+    pub developer_title: &'static str,
+    pub artifact_kind: fn(ArtifactKind) -> &'static str,
+    pub artifact_removal: fn(ArtifactKind) -> &'static str,
+    pub strategy_lipo_remove: &'static str,
+    pub strategy_keep_only_native: &'static str,
+    pub strategy_nested_only: &'static str,
+    pub error_title: &'static str,
+    pub press_to_continue: &'static str,
+    pub warning_title: &'static str,
+    pub no_selection: &'static str,
+    pub select_one: &'static str,
+    pub managed_title: &'static str,
+    pub managed_selected: fn(usize) -> String,
+    pub managed_warning: [&'static str; 2],
+    pub managed_prompt: &'static str,
+    pub confirm_title: &'static str,
+    pub confirm_question: fn(usize, &str) -> String,
+    pub confirm_prompt: &'static str,
+    pub confirm_typed_prompt: fn(usize) -> String,
+    pub confirm_rejected: &'static str,
+    pub password_title: &'static str,
+    pub will_be_trimmed: fn(usize) -> String,
+    pub requires_sudo: &'static str,
+    pub enter_password: &'static str,
+    pub caps_lock_on: &'static str,
+    pub password_keys: &'static str,
+    pub password_terminal_hint: &'static str,
+
+    // Errors
+    pub trim_failed_details: fn(usize) -> String,
+    pub trim_failed_transient: fn(usize) -> String,
+    pub session_trimmed: fn(usize, &str) -> String,
+    pub session_nothing_trimmed: &'static str,
+    pub scan_crashed: fn(&str) -> String,
+    pub scan_start_failed: fn(&str) -> String,
+    pub trim_crashed: fn(&str) -> String,
+    pub breakdown_crashed: fn(&str) -> String,
+    pub breakdown_start_failed: fn(&str) -> String,
+    pub trim_start_failed: fn(&str) -> String,
+    pub sudo_run_failed: fn(&str) -> String,
+    pub sudo_needed: fn(usize) -> String,
+    /// Passed to `sudo -p`, `%u` is replaced with the user name
+    pub sudo_prompt: &'static str,
+
+    // Line-based mode
+    pub plain_scanning: &'static str,
+    pub plain_nothing_to_trim: &'static str,
+    pub plain_choose: &'static str,
+    pub plain_invalid: fn(&str) -> String,
+    pub plain_confirm: &'static str,
+    pub plain_confirm_typed: fn(usize) -> String,
+    pub target_not_found: fn(&str) -> String,
+    pub pick_filter: &'static str,
+    pub pick_no_matches: fn(&str) -> String,
+    pub pick_choose: &'static str,
+    pub target_not_trimmable: fn(&str, &str) -> String,
+    pub list_header_app: &'static str,
+    pub list_header_prunable: &'static str,
+    pub list_header_potential: &'static str,
+    pub list_header_architectures: &'static str,
+    pub list_total: fn(usize, usize, &str) -> String,
+    pub helper_installed: &'static str,
+    pub helper_uninstalled: &'static str,
+}
+
+pub static ENGLISH: Strings = Strings {
+    scanning_title: "Scanning",
+    initializing: "Initializing...",
+    scan_skipped: |skipped, errors| format!(" ({} skipped, {} with errors)", skipped, errors),
+    trimming_title: "Trimming Applications",
+    rescanning_title: "Rescanning",
+    trim_freed: |size| format!("{} freed", size),
+    rescanning: |name| format!("Rescanning: {}", name),
+    trimming: |name| format!("Trimming: {}", name),
+    preparing_to_trim: |name| format!("Preparing to trim: {}", name),
+    preparing: "Preparing...",
+    cancelling: "Stopping after the current app or binary...",
+
+    apps_shown: |shown, total| format!("{} of {} applications", shown, total),
+    hidden_note: |count, reasons| format!("{} app(s) hidden ({})", count, reasons),
+    hidden_arm64_only: |count| format!("{} arm64-only", count),
+    hidden_not_writable: |count| format!("{} not writable", count),
+    hidden_excluded: |count| format!("{} excluded", count),
+    hidden_protected: |count| format!("{} protected", count),
+    hidden_other: |count| format!("{} other", count),
+    sorted_by_size: " · sorted by size",
+    sorted_by_name: " · sorted by name",
+    sorted_by_potential: " · sorted by potential savings",
+    update_available: |version| format!(" · v{} available", version),
+    audit_mode_header: " · audit mode",
+    audit_mode_plain: "Audit mode: nothing is trimmed.",
+    hint_toggle: "Space: toggle",
+    hint_all: "a: all",
+    hint_undo: "u: undo",
+    hint_trim: "Enter: trim",
+    hint_sort_by_name: "s: sort by name",
+    hint_sort_by_size: "s: sort by size",
+    hint_sort_by_potential: "s: sort by potential",
+    potential_savings: |total, slices, nested, languages, compression| {
+        format!(
+            "potential {}: slices {}, nested {}, languages {}, compression ~{}",
+            total, slices, nested, languages, compression
+        )
+    },
+    size_history: |binary, prunable, days| {
+        format!(
+            "binary {}, prunable {} within {} day(s)",
+            binary, prunable, days
+        )
+    },
+    hint_show_prunable: "h: show prunable",
+    hint_show_all: "h: show all",
+    hint_skipped: "d: skipped",
+    hint_summary: "i: summary",
+    hint_columns: "c: columns",
+    hint_breakdown: "b: breakdown",
+    hint_vendors: "g: by vendor",
+    hint_developer: "x: developer files",
+    hint_category: "f: category",
+    hint_filter: "/: filter",
+    hint_savings: "t: estimate vs actual",
+    trimmed_off_estimate: |count, off| {
+        format!(
+            "Trimmed {} application(s), {} freed a different amount than estimated (t: details)",
+            count, off
+        )
+    },
+    savings_title: "Estimated vs. Actual - ↑/↓: scroll | Esc: close",
+    savings_header: " Estimated      Actual  Difference  Application",
+    savings_off: "differs",
+    savings_off_hint: "Large differences usually mean nested binaries bintrim doesn't handle yet. Please include them when reporting a problem.",
+    hint_copy: "y: copy",
+    hint_legend: "?: legend",
+    hint_strategy: "m: strategy",
+    copied: |count| format!("Copied {} application(s) as CSV", count),
+    strategy_changed: |name, strategy| {
+        format!(
+            "{} will be trimmed with: {} (until bintrim exits)",
+            name, strategy
+        )
+    },
+    strategy_unchangeable: |name| format!("{} can't be trimmed", name),
+    copy_failed: |err| format!("Couldn't copy to the clipboard: {}", err),
+    filter_title: "Filter",
+    filter_syntax: "name regex, >50MB, arch:x86_64, cat:games",
+    hint_errors: "e: errors",
+    hint_nav: "↑/↓: nav",
+    hint_next_selected: "n/N: next selected",
+    hint_quit: "q: quit",
+    hint_close: "Enter/Esc: close",
+    hint_retry: "r: scan again",
+    hint_scroll: "↑/↓: scroll",
+    hint_esc_close: "Esc: close",
+    hint_trim_anyway: "y: trim anyway",
+    hint_confirm_trim: "y/Enter: trim",
+    hint_cancel: "n/Esc: cancel",
+    hint_confirm: "Enter: confirm",
+    hint_clear: "Ctrl-U: clear",
+    hint_terminal_prompt: "Ctrl-T: terminal prompt",
+    hint_esc_cancel: "Esc: cancel",
+    trimmed: |count| format!("Trimmed {} app(s)", count),
+    trim_failed_status: |count| format!("{} app(s) failed to trim (press e for errors)", count),
+    trim_cancelled_status: |count| {
+        format!(
+            "Cancelled, {} app(s) not finished (press e for details)",
+            count
+        )
+    },
+    scan_cancelled: "Scan cancelled, only the apps scanned so far are listed",
+    sudo_failed: "sudo authentication failed, nothing was trimmed",
+    lipo_missing: "lipo isn't installed, so apps can't be trimmed. Install the Xcode Command Line Tools with `xcode-select --install` and restart bintrim.",
+    lipo_missing_status: "lipo isn't installed: apps were scanned without it, but can't be trimmed",
+    report_failed: |err| format!("Couldn't deliver the report: {}", err),
+    nothing_to_undo: "Nothing to undo",
+
+    column_name: "Name",
+    column_architectures: "Architectures",
+    column_slices: "Slice Sizes",
+    column_size: "Pruneable Size",
+    column_notes: "Notes",
+    column_badges: "Badges",
+    not_available: "N/A",
+    volume_free: |free| format!("{} free", free),
+    network_volume: ", network volume",
+    badge_description: |badge| match badge {
+        Badge::IosApp => "iOS/iPadOS app wrapper, not modifiable",
+        Badge::Managed => "managed by MDM/Munki/Jamf, may be reinstalled or flagged",
+        Badge::AppStore => "App Store updates restore the full binary",
+        Badge::Setapp => "Setapp may re-download or refuse to update modified apps",
+        Badge::ReadOnly => "stored on a read-only volume, can't be trimmed",
+        Badge::Hidden => "hidden from the Finder",
+        Badge::Immutable => "binary is locked (uchg/schg), can't be trimmed",
+        Badge::Excluded => "set to skip in the config",
+        Badge::Risky => "deep inspection predicts trimming may break it",
+        Badge::AutoUpdates => "updates itself (Sparkle/Squirrel), the next update restores x86_64",
+        Badge::Xcode => "its toolchain is trimmed too, SDKs and simulators are kept (see b)",
+        Badge::Wine => {
+            "runs Windows programs with Wine, which needs its Intel code (set a strategy to trim anyway)"
+        }
+        Badge::Shared => "binary is hard-linked or an APFS clone, trimming frees less",
+        Badge::Copy => "translocated or disk image copy, trim the installed app instead",
+        Badge::Running => "running, keeps using the universal binary until restarted",
+        Badge::TrimmedBefore => "trimmed by bintrim before, an update restored x86_64",
+        Badge::Protected => "system or security software, never trimmed (see unprotect)",
+    },
+    risk_description: |risk| match risk {
+        Risk::RestrictSegment => "has a __RESTRICT segment, may check itself for tampering",
+        Risk::SliceSignature => "only some slices are signed",
+        Risk::LaunchConstraints => "signed with launch constraints",
+    },
+
+    summary_title: "Summary",
+    prunable_apps: "Prunable Applications: ",
+    total_size: "Total pruneable size: ",
+    architectures_label: "Architectures: ",
+    stats_universal: |apps| format!("{} universal", apps),
+    stats_arm64_only: |apps| format!("{} arm64 only", apps),
+    stats_x86_64_only: |apps| format!("{} x86_64 only", apps),
+    stats_median_slice: |size| format!("median slice {}", size),
+    selected: "Selected: ",
+    prune_size: "Prune size: ",
+    of_total: |total| format!("  of {}", total),
+    shown: |shown| format!("  ({} shown)", shown),
+    links_to: |path| format!("links to {}", path.display()),
+    helper_of: |app| format!("helper of {}", app),
+    helper_unknown_app: "helper of an unknown app",
+
+    skipped_title: |count| {
+        format!(
+            "Skipped Applications ({}) - ↑/↓: scroll | Esc: close",
+            count
+        )
+    },
+    no_skipped: "No applications were skipped.",
+    columns_title: "Columns",
+    legend_title: "Badges",
+    breakdown_title: |name| format!("{} - ↑/↓: scroll | Esc: close", name),
+    breakdown_analyzing: "Analyzing bundle...",
+    breakdown_total: |size| format!("Total: {}", size),
+    breakdown_dirs: "Largest directories",
+    breakdown_files: "Largest files",
+    breakdown_fat: "Universal binaries",
+    breakdown_localizations: |count| format!("Localizations ({})", count),
+    breakdown_savings: "Estimated savings",
+    breakdown_none: "none",
+    breakdown_xcode: "Xcode",
+    breakdown_xcode_tools: |count| format!("trimmed: {} universal toolchain binaries", count),
+    breakdown_xcode_kept: "kept",
+    xcode_protection: |protection| match protection {
+        Protection::TargetCode => {
+            "SDKs, simulators and device support for building and running Intel code"
+        }
+        Protection::TargetLibraries => "Swift runtimes and libraries linked into x86_64 builds",
+        Protection::Integrity => "loaded by Xcode itself and covered by its integrity checks",
+    },
+    vendors_title: "Prunable by Vendor - ↑/↓: scroll | Esc: close",
+    vendor_line: |vendor, size, count| {
+        format!("{}: {} prunable across {} app(s)", vendor, size, count)
+    },
+    vendor_unknown: "Unknown vendor",
+    no_prunable_vendors: "No applications can be trimmed.",
+    developer_files: "Developer files: ",
+    developer_files_total: |size| format!("{} (not trimmable, x: details)", size),
+    developer_title: "Developer Files - ↑/↓: scroll | Esc: close",
+    artifact_kind: |kind| match kind {
+        ArtifactKind::SimulatorRuntime => "Simulator runtime",
+        ArtifactKind::SimulatorDevices => "Simulator devices",
+        ArtifactKind::DeviceSupport => "Device support files",
+        ArtifactKind::Archives => "Xcode archives",
+    },
+    artifact_removal: |kind| match kind {
+        ArtifactKind::SimulatorRuntime => {
+            "Remove in Xcode > Settings > Platforms, or with `xcrun simctl runtime delete`"
+        }
+        ArtifactKind::SimulatorDevices => {
+            "Delete unused simulators with `xcrun simctl delete unavailable`"
+        }
+        ArtifactKind::DeviceSupport => {
+            "Delete the folders of OS versions your devices no longer run, Xcode copies them again when needed"
+        }
+        ArtifactKind::Archives => "Delete old archives in Xcode's Organizer (Window > Organizer)",
+    },
+    strategy_lipo_remove: "remove x86_64",
+    strategy_keep_only_native: "keep only arm64",
+    strategy_nested_only: "only nested binaries and frameworks",
+    error_title: "Error",
+    press_to_continue: "Press Enter or Esc to continue",
+    warning_title: "Warning",
+    no_selection: "No applications selected",
+    select_one: "Please select at least one application to trim.",
+    managed_title: "Managed Applications",
+    managed_selected: |count| format!("{} selected application(s) are managed", count),
+    managed_warning: [
+        "Your management system (MDM, Munki, Jamf) may reinstall",
+        "these apps or report them as modified.",
+    ],
+    managed_prompt: "Press y to trim anyway, n or Esc to cancel",
+    confirm_title: "Confirm Trim",
+    confirm_question: |count, size| {
+        format!("Trim {} application(s) and free about {}?", count, size)
+    },
+    confirm_prompt: "Press y or Enter to trim, n or Esc to cancel",
+    confirm_typed_prompt: |count| {
+        format!(
+            "Type trim or {} and press Enter to confirm, Esc to cancel",
+            count
+        )
+    },
+    confirm_rejected: "That doesn't match, try again",
+    password_title: "Sudo Authentication",
+    will_be_trimmed: |count| format!("{} Application(s) will be trimmed", count),
+    requires_sudo: "This operation requires sudo privileges",
+    enter_password: "Enter your password",
+    caps_lock_on: "Caps Lock is on",
+    password_keys: "Press Enter to confirm, Ctrl-U to clear, Esc to cancel",
+    password_terminal_hint: "Ctrl-T: type it into sudo's own prompt instead",
+
+    trim_failed_details: |count| format!("{} application(s) failed to trim:", count),
+    trim_failed_transient: |count| {
+        format!(
+            "{} application(s) failed with a temporary error, trying again later may work:",
+            count
+        )
+    },
+    session_trimmed: |count, freed| format!("Trimmed {} application(s), freeing {}:", count, freed),
+    session_nothing_trimmed: "No applications were trimmed.",
+    scan_crashed: |message| format!("The scan thread crashed: {}", message),
+    scan_start_failed: |message| format!("Could not start the scan: {}", message),
+    breakdown_crashed: |message| format!("Analyzing the bundle crashed: {}", message),
+    breakdown_start_failed: |err| format!("Failed to start analyzing the bundle: {}", err),
+    trim_crashed: |message| {
+        format!(
+            "The trim thread crashed: {}\n\nSome applications may not have been trimmed.",
+            message
+        )
+    },
+    trim_start_failed: |message| format!("Failed to start trimming: {}", message),
+    sudo_run_failed: |message| format!("Failed to run sudo: {}", message),
+    sudo_needed: |count| {
+        format!(
+            "bintrim needs administrator rights to trim {} application(s).",
+            count
+        )
+    },
+    sudo_prompt: "Password for %u: ",
+
+    plain_scanning: "Scanning applications...",
+    plain_nothing_to_trim: "No applications can be trimmed.",
+    plain_choose: "Numbers to trim (e.g. 1 3 5-7, a for all, Enter to quit): ",
+    plain_invalid: |input| format!("Not a valid selection: {}", input),
+    plain_confirm: "Trim anyway? [y/N] ",
+    plain_confirm_typed: |count| format!("Type trim or {} to confirm: ", count),
+    target_not_found: |target| format!("No application found for {}", target),
+    pick_filter: "Filter (Enter for all, Ctrl-D to quit): ",
+    pick_no_matches: |query| format!("Nothing matches {}", query),
+    pick_choose: "Numbers to trim (e.g. 1 3 5-7, a for all, Enter to filter again): ",
+    target_not_trimmable: |target, name| format!("{} ({}) can't be trimmed", name, target),
+    list_header_app: "App",
+    list_header_prunable: "Prunable",
+    list_header_potential: "Potential",
+    list_header_architectures: "Architectures",
+    list_total: |shown, total, size| {
+        format!("{} of {} prunable apps, {} to free", shown, total, size)
+    },
+    helper_installed: "Installed the helper. bintrim no longer asks for a password to trim.",
+    helper_uninstalled: "Removed the helper.",
+};
+
+pub static GERMAN: Strings = Strings {
+    scanning_title: "Suche",
+    initializing: "Initialisiere...",
+    scan_skipped: |skipped, errors| format!(" ({} übersprungen, {} mit Fehlern)", skipped, errors),
+    trimming_title: "Programme werden verkleinert",
+    rescanning_title: "Erneute Suche",
+    trim_freed: |size| format!("{} freigegeben", size),
+    rescanning: |name| format!("Erneute Suche: {}", name),
+    trimming: |name| format!("Verkleinere: {}", name),
+    preparing_to_trim: |name| format!("Bereite Verkleinern vor: {}", name),
+    preparing: "Vorbereitung...",
+    cancelling: "Wird nach dem aktuellen Programm oder Binary angehalten...",
+
+    apps_shown: |shown, total| format!("{} von {} Programmen", shown, total),
+    hidden_note: |count, reasons| format!("{} Programm(e) ausgeblendet ({})", count, reasons),
+    hidden_arm64_only: |count| format!("{} nur arm64", count),
+    hidden_not_writable: |count| format!("{} schreibgeschützt", count),
+    hidden_excluded: |count| format!("{} ausgeschlossen", count),
+    hidden_protected: |count| format!("{} geschützt", count),
+    hidden_other: |count| format!("{} sonstige", count),
+    sorted_by_size: " · nach Größe sortiert",
+    sorted_by_name: " · nach Name sortiert",
+    sorted_by_potential: " · nach möglicher Einsparung sortiert",
+    update_available: |version| format!(" · v{} verfügbar", version),
+    audit_mode_header: " · Prüfmodus",
+    audit_mode_plain: "Prüfmodus: es wird nichts verkleinert.",
+    hint_toggle: "Leertaste: auswählen",
+    hint_all: "a: alle",
+    hint_undo: "u: rückgängig",
+    hint_trim: "Enter: verkleinern",
+    hint_sort_by_name: "s: nach Name sortieren",
+    hint_sort_by_size: "s: nach Größe sortieren",
+    hint_sort_by_potential: "s: nach Potenzial sortieren",
+    potential_savings: |total, slices, nested, languages, compression| {
+        format!(
+            "möglich {}: Slices {}, eingebettet {}, Sprachen {}, Komprimierung ~{}",
+            total, slices, nested, languages, compression
+        )
+    },
+    size_history: |binary, prunable, days| {
+        format!(
+            "Binary {}, verkleinerbar {} in {} Tag(en)",
+            binary, prunable, days
+        )
+    },
+    hint_show_prunable: "h: nur verkleinerbare",
+    hint_show_all: "h: alle zeigen",
+    hint_skipped: "d: übersprungen",
+    hint_summary: "i: Übersicht",
+    hint_columns: "c: Spalten",
+    hint_breakdown: "b: Aufschlüsselung",
+    hint_vendors: "g: nach Hersteller",
+    hint_developer: "x: Entwicklerdateien",
+    hint_category: "f: Kategorie",
+    hint_filter: "/: filtern",
+    hint_savings: "t: Schätzung vs. Ergebnis",
+    trimmed_off_estimate: |count, off| {
+        format!(
+            "{} Programm(e) verkleinert, bei {} weicht die Ersparnis von der Schätzung ab (t: Details)",
+            count, off
+        )
+    },
+    savings_title: "Schätzung vs. Ergebnis - ↑/↓: blättern | Esc: schließen",
+    savings_header: " Geschätzt    Ergebnis   Differenz  Programm",
+    savings_off: "weicht ab",
+    savings_off_hint: "Große Abweichungen deuten meist auf eingebettete Binärdateien hin, die bintrim noch nicht behandelt. Bitte bei Fehlerberichten angeben.",
+    hint_copy: "y: kopieren",
+    hint_legend: "?: Legende",
+    hint_strategy: "m: Strategie",
+    copied: |count| format!("{} Programm(e) als CSV kopiert", count),
+    strategy_changed: |name, strategy| {
+        format!(
+            "{} wird verkleinert mit: {} (bis bintrim beendet wird)",
+            name, strategy
+        )
+    },
+    strategy_unchangeable: |name| format!("{} kann nicht verkleinert werden", name),
+    copy_failed: |err| format!("Kopieren in die Zwischenablage fehlgeschlagen: {}", err),
+    filter_title: "Filter",
+    filter_syntax: "Name (Regex), >50MB, arch:x86_64, cat:games",
+    hint_errors: "e: Fehler",
+    hint_nav: "↑/↓: bewegen",
+    hint_next_selected: "n/N: nächste Auswahl",
+    hint_quit: "q: beenden",
+    hint_close: "Enter/Esc: schließen",
+    hint_retry: "r: erneut suchen",
+    hint_scroll: "↑/↓: blättern",
+    hint_esc_close: "Esc: schließen",
+    hint_trim_anyway: "y: trotzdem verkleinern",
+    hint_confirm_trim: "y/Enter: verkleinern",
+    hint_cancel: "n/Esc: abbrechen",
+    hint_confirm: "Enter: bestätigen",
+    hint_clear: "Ctrl-U: löschen",
+    hint_terminal_prompt: "Ctrl-T: Terminal-Eingabe",
+    hint_esc_cancel: "Esc: abbrechen",
+    trimmed: |count| format!("{} Programm(e) verkleinert", count),
+    trim_failed_status: |count| {
+        format!(
+            "{} Programm(e) konnten nicht verkleinert werden (e für Details)",
+            count
+        )
+    },
+    trim_cancelled_status: |count| {
+        format!(
+            "Abgebrochen, {} Programm(e) nicht fertig verkleinert (e für Details)",
+            count
+        )
+    },
+    scan_cancelled: "Suche abgebrochen, nur die bisher gefundenen Programme werden angezeigt",
+    sudo_failed: "sudo-Anmeldung fehlgeschlagen, nichts wurde verkleinert",
+    lipo_missing: "lipo ist nicht installiert, daher können keine Programme verkleinert werden. Installiere die Xcode Command Line Tools mit `xcode-select --install` und starte bintrim neu.",
+    lipo_missing_status: "lipo ist nicht installiert: Programme wurden ohne lipo gesucht, können aber nicht verkleinert werden",
+    report_failed: |err| format!("Bericht konnte nicht zugestellt werden: {}", err),
+    nothing_to_undo: "Nichts rückgängig zu machen",
+
+    column_name: "Name",
+    column_architectures: "Architekturen",
+    column_slices: "Slice-Größen",
+    column_size: "Einsparung",
+    column_notes: "Hinweise",
+    column_badges: "Marken",
+    not_available: "k. A.",
+    volume_free: |free| format!("{} frei", free),
+    network_volume: ", Netzwerkvolume",
+    badge_description: |badge| match badge {
+        Badge::IosApp => "iOS/iPadOS-App, darf nicht verändert werden",
+        Badge::Managed => "von MDM/Munki/Jamf verwaltet, wird evtl. neu installiert oder gemeldet",
+        Badge::AppStore => "App-Store-Updates stellen das vollständige Programm wieder her",
+        Badge::Setapp => "Setapp lädt veränderte Apps evtl. neu oder aktualisiert sie nicht",
+        Badge::ReadOnly => "liegt auf einem schreibgeschützten Volume, nicht verkleinerbar",
+        Badge::Hidden => "im Finder ausgeblendet",
+        Badge::Immutable => "Programmdatei ist gesperrt (uchg/schg), nicht verkleinerbar",
+        Badge::Excluded => "in der Konfiguration auf skip gesetzt",
+        Badge::Risky => "laut Tiefenprüfung kann Verkleinern es beschädigen",
+        Badge::AutoUpdates => {
+            "aktualisiert sich selbst (Sparkle/Squirrel), das nächste Update stellt x86_64 wieder her"
+        }
+        Badge::Xcode => {
+            "die Toolchain wird mit verkleinert, SDKs und Simulatoren bleiben (siehe b)"
+        }
+        Badge::Wine => {
+            "führt Windows-Programme mit Wine aus, das seinen Intel-Code braucht (Strategie setzen, um trotzdem zu verkleinern)"
+        }
+        Badge::Shared => {
+            "Programmdatei ist fest verlinkt oder ein APFS-Klon, Verkleinern gibt weniger frei"
+        }
+        Badge::Copy => {
+            "verschobene Kopie oder Kopie auf einem Disk-Image, stattdessen die installierte App verkleinern"
+        }
+        Badge::Running => "läuft und nutzt bis zum Neustart das universelle Programm",
+        Badge::TrimmedBefore => {
+            "wurde schon einmal verkleinert, ein Update hat x86_64 wiederhergestellt"
+        }
+        Badge::Protected => {
+            "System- oder Sicherheitssoftware, wird nie verkleinert (siehe unprotect)"
+        }
+    },
+    risk_description: |risk| match risk {
+        Risk::RestrictSegment => "hat ein __RESTRICT-Segment, prüft sich evtl. auf Manipulation",
+        Risk::SliceSignature => "nur einige Slices sind signiert",
+        Risk::LaunchConstraints => "mit Startbeschränkungen signiert",
+    },
+
+    summary_title: "Übersicht",
+    prunable_apps: "Verkleinerbare Programme: ",
+    total_size: "Mögliche Einsparung: ",
+    architectures_label: "Architekturen: ",
+    stats_universal: |apps| format!("{} universal", apps),
+    stats_arm64_only: |apps| format!("{} nur arm64", apps),
+    stats_x86_64_only: |apps| format!("{} nur x86_64", apps),
+    stats_median_slice: |size| format!("Slice-Median {}", size),
+    selected: "Ausgewählt: ",
+    prune_size: "Einsparung: ",
+    of_total: |total| format!("  von {}", total),
+    shown: |shown| format!("  ({} angezeigt)", shown),
+    links_to: |path| format!("verweist auf {}", path.display()),
+    helper_of: |app| format!("Hilfsprogramm von {}", app),
+    helper_unknown_app: "Hilfsprogramm einer unbekannten App",
+
+    skipped_title: |count| {
+        format!(
+            "Übersprungene Programme ({}) - ↑/↓: blättern | Esc: schließen",
+            count
+        )
+    },
+    no_skipped: "Es wurden keine Programme übersprungen.",
+    columns_title: "Spalten",
+    legend_title: "Marken",
+    breakdown_title: |name| format!("{} - ↑/↓: blättern | Esc: schließen", name),
+    breakdown_analyzing: "Bundle wird analysiert...",
+    breakdown_total: |size| format!("Gesamt: {}", size),
+    breakdown_dirs: "Größte Verzeichnisse",
+    breakdown_files: "Größte Dateien",
+    breakdown_fat: "Universelle Binärdateien",
+    breakdown_localizations: |count| format!("Lokalisierungen ({})", count),
+    breakdown_savings: "Geschätzte Ersparnis",
+    breakdown_none: "keine",
+    breakdown_xcode: "Xcode",
+    breakdown_xcode_tools: |count| {
+        format!("verkleinert: {} universelle Toolchain-Programme", count)
+    },
+    breakdown_xcode_kept: "bleibt",
+    xcode_protection: |protection| match protection {
+        Protection::TargetCode => {
+            "SDKs, Simulatoren und Gerätesupport zum Bauen und Ausführen von Intel-Code"
+        }
+        Protection::TargetLibraries => "Swift-Laufzeiten und Bibliotheken für x86_64-Builds",
+        Protection::Integrity => {
+            "von Xcode selbst geladen und durch seine Integritätsprüfung abgedeckt"
+        }
+    },
+    vendors_title: "Einsparung nach Hersteller - ↑/↓: blättern | Esc: schließen",
+    vendor_line: |vendor, size, count| {
+        format!("{}: {} einsparbar in {} Programm(en)", vendor, size, count)
+    },
+    vendor_unknown: "Unbekannter Hersteller",
+    no_prunable_vendors: "Keine Programme können verkleinert werden.",
+    developer_files: "Entwicklerdateien: ",
+    developer_files_total: |size| format!("{} (nicht verkleinerbar, x: Details)", size),
+    developer_title: "Entwicklerdateien - ↑/↓: blättern | Esc: schließen",
+    artifact_kind: |kind| match kind {
+        ArtifactKind::SimulatorRuntime => "Simulator-Laufzeit",
+        ArtifactKind::SimulatorDevices => "Simulator-Geräte",
+        ArtifactKind::DeviceSupport => "Gerätesupport-Dateien",
+        ArtifactKind::Archives => "Xcode-Archive",
+    },
+    artifact_removal: |kind| match kind {
+        ArtifactKind::SimulatorRuntime => {
+            "Entfernen in Xcode > Einstellungen > Plattformen oder mit `xcrun simctl runtime delete`"
+        }
+        ArtifactKind::SimulatorDevices => {
+            "Unbenutzte Simulatoren mit `xcrun simctl delete unavailable` löschen"
+        }
+        ArtifactKind::DeviceSupport => {
+            "Ordner von OS-Versionen löschen, die deine Geräte nicht mehr nutzen, Xcode kopiert sie bei Bedarf erneut"
+        }
+        ArtifactKind::Archives => {
+            "Alte Archive im Organizer von Xcode löschen (Fenster > Organizer)"
+        }
+    },
+    strategy_lipo_remove: "x86_64 entfernen",
+    strategy_keep_only_native: "nur arm64 behalten",
+    strategy_nested_only: "nur eingebettete Programme und Frameworks",
+    error_title: "Fehler",
+    press_to_continue: "Enter oder Esc zum Fortfahren",
+    warning_title: "Warnung",
+    no_selection: "Keine Programme ausgewählt",
+    select_one: "Bitte mindestens ein Programm zum Verkleinern auswählen.",
+    managed_title: "Verwaltete Programme",
+    managed_selected: |count| format!("{} ausgewählte(s) Programm(e) werden verwaltet", count),
+    managed_warning: [
+        "Ihre Geräteverwaltung (MDM, Munki, Jamf) installiert diese",
+        "Programme evtl. neu oder meldet sie als verändert.",
+    ],
+    managed_prompt: "y: trotzdem verkleinern, n oder Esc: abbrechen",
+    confirm_title: "Verkleinern bestätigen",
+    confirm_question: |count, size| {
+        format!(
+            "{} Programm(e) verkleinern und etwa {} freigeben?",
+            count, size
+        )
+    },
+    confirm_prompt: "y oder Enter: verkleinern, n oder Esc: abbrechen",
+    confirm_typed_prompt: |count| {
+        format!(
+            "Zum Bestätigen trim oder {} eingeben und Enter drücken, Esc zum Abbrechen",
+            count
+        )
+    },
+    confirm_rejected: "Das stimmt nicht, bitte noch einmal",
+    password_title: "sudo-Anmeldung",
+    will_be_trimmed: |count| format!("{} Programm(e) werden verkleinert", count),
+    requires_sudo: "Dafür sind sudo-Rechte nötig",
+    enter_password: "Passwort eingeben",
+    caps_lock_on: "Feststelltaste ist aktiv",
+    password_keys: "Enter: bestätigen, Ctrl-U: löschen, Esc: abbrechen",
+    password_terminal_hint: "Ctrl-T: stattdessen in die sudo-Abfrage tippen",
+
+    trim_failed_details: |count| format!("{} Programm(e) konnten nicht verkleinert werden:", count),
+    trim_failed_transient: |count| {
+        format!(
+            "{} Programm(e) scheiterten an einem vorübergehenden Fehler, ein späterer Versuch kann klappen:",
+            count
+        )
+    },
+    session_trimmed: |count, freed| {
+        format!("{} Programm(e) verkleinert, {} freigegeben:", count, freed)
+    },
+    session_nothing_trimmed: "Es wurden keine Programme verkleinert.",
+    scan_crashed: |message| format!("Die Suche ist abgestürzt: {}", message),
+    scan_start_failed: |message| format!("Die Suche konnte nicht starten: {}", message),
+    breakdown_crashed: |message| format!("Die Analyse des Bundles ist abgestürzt: {}", message),
+    breakdown_start_failed: |err| {
+        format!(
+            "Die Analyse des Bundles konnte nicht gestartet werden: {}",
+            err
+        )
+    },
+    trim_crashed: |message| {
+        format!(
+            "Das Verkleinern ist abgestürzt: {}\n\nEinige Programme wurden evtl. nicht verkleinert.",
+            message
+        )
+    },
+    trim_start_failed: |message| format!("Verkleinern konnte nicht gestartet werden: {}", message),
+    sudo_run_failed: |message| format!("sudo konnte nicht ausgeführt werden: {}", message),
+    sudo_needed: |count| {
+        format!(
+            "bintrim braucht Administratorrechte, um {} Programm(e) zu verkleinern.",
+            count
+        )
+    },
+    sudo_prompt: "Passwort für %u: ",
+
+    plain_scanning: "Suche Programme...",
+    plain_nothing_to_trim: "Keine Programme können verkleinert werden.",
+    plain_choose: "Nummern zum Verkleinern (z.B. 1 3 5-7, a für alle, Enter zum Beenden): ",
+    plain_invalid: |input| format!("Keine gültige Auswahl: {}", input),
+    plain_confirm: "Trotzdem verkleinern? [j/N] ",
+    plain_confirm_typed: |count| format!("Zum Bestätigen trim oder {} eingeben: ", count),
+    target_not_found: |target| format!("Kein Programm gefunden für {}", target),
+    pick_filter: "Filter (Enter für alle, Ctrl-D zum Beenden): ",
+    pick_no_matches: |query| format!("Nichts passt zu {}", query),
+    pick_choose: "Nummern zum Verkleinern (z.B. 1 3 5-7, a für alle, Enter für neuen Filter): ",
+    target_not_trimmable: |target, name| {
+        format!("{} ({}) kann nicht verkleinert werden", name, target)
+    },
+    list_header_app: "Programm",
+    list_header_prunable: "Entfernbar",
+    list_header_potential: "Potenzial",
+    list_header_architectures: "Architekturen",
+    list_total: |shown, total, size| {
+        format!(
+            "{} von {} verkleinerbaren Programmen, {} freizugeben",
+            shown, total, size
+        )
+    },
+    helper_installed: "Helfer installiert. bintrim fragt zum Verkleinern nicht mehr nach dem Passwort.",
+    helper_uninstalled: "Helfer entfernt.",
+};
+
+static STRINGS: OnceLock<&'static Strings> = OnceLock::new();
+
+/// Picks the UI language from `language` (e.g. the config file), falling back to
+/// the locale environment variables and then English.
+pub fn init(language: Option<&str>) {
+    let strings = language
+        .map(str::to_string)
+        .or_else(locale_from_env)
+        .and_then(|tag| for_language(&tag))
+        .unwrap_or(&ENGLISH);
+    let _ = STRINGS.set(strings);
+}
+
+/// The strings for the language chosen in [`init`].
+pub fn t() -> &'static Strings {
+    STRINGS.get().copied().unwrap_or(&ENGLISH)
+}
+
+/// Finds the translation for a locale such as `de`, `de_AT.UTF-8` or `en-US`.
+fn for_language(tag: &str) -> Option<&'static Strings> {
+    let language = tag
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "en" | "c" | "posix" => Some(&ENGLISH),
+        "de" => Some(&GERMAN),
+        _ => None,
+    }
+}
+
+/// Language of the system locale, such as `de` for `de_AT.UTF-8`.
+pub fn system_language() -> Option<String> {
+    let locale = locale_from_env()?;
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()?
+        .to_ascii_lowercase();
+    (!language.is_empty() && language != "c" && language != "posix").then_some(language)
+}
+
+fn locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_language() {
+        assert!(std::ptr::eq(for_language("de_AT.UTF-8").unwrap(), &GERMAN));
+        assert!(std::ptr::eq(for_language("DE").unwrap(), &GERMAN));
+        assert!(std::ptr::eq(for_language("en-US").unwrap(), &ENGLISH));
+        assert!(std::ptr::eq(for_language("C").unwrap(), &ENGLISH));
+        assert!(for_language("xx").is_none());
+    }
+
+    #[test]
+    fn test_formatted_strings() {
+        assert_eq!((ENGLISH.apps_shown)(3, 10), "3 of 10 applications");
+        assert_eq!((GERMAN.apps_shown)(3, 10), "3 von 10 Programmen");
+        assert_eq!((ENGLISH.trimmed)(2), "Trimmed 2 app(s)");
+    }
+}
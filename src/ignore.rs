@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+/// A glob from the `ignore` setting or `--ignore`. Patterns starting with `/` or
+/// `~/` match the bundle's path, any other pattern its bundle identifier.
+///
+/// `*` matches any number of characters and `?` a single one, including `/`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IgnorePattern {
+    Path(String),
+    BundleId(String),
+}
+
+impl IgnorePattern {
+    pub fn parse(pattern: &str) -> Self {
+        if let Some(rest) = pattern.strip_prefix("~/")
+            && let Some(home) = std::env::var_os("HOME")
+        {
+            IgnorePattern::Path(
+                PathBuf::from(home)
+                    .join(rest)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        } else if pattern.starts_with('/') {
+            IgnorePattern::Path(pattern.to_string())
+        } else {
+            IgnorePattern::BundleId(pattern.to_string())
+        }
+    }
+
+    /// Does this pattern ignore the bundle at `path`? Bundle identifier patterns
+    /// never match before the identifier is known.
+    pub fn matches(&self, path: &Path, bundle_id: Option<&str>) -> bool {
+        match self {
+            IgnorePattern::Path(pattern) => glob_match(pattern, &path.to_string_lossy()),
+            IgnorePattern::BundleId(pattern) => {
+                bundle_id.is_some_and(|bundle_id| glob_match(pattern, bundle_id))
+            }
+        }
+    }
+}
+
+/// Matches `text` against a glob with `*` and `?` wildcards.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently matches up to
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("com.apple.*", "com.apple.Safari"));
+        assert!(!glob_match("com.apple.*", "com.microsoft.Word"));
+        assert!(glob_match(
+            "/Applications/Adobe*",
+            "/Applications/Adobe Photoshop.app"
+        ));
+        assert!(glob_match("*.Word", "com.microsoft.Word"));
+        assert!(glob_match("com.?pple.*", "com.apple.Music"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let by_id = IgnorePattern::parse("com.apple.*");
+        assert_eq!(by_id, IgnorePattern::BundleId("com.apple.*".to_string()));
+        let safari = Path::new("/Applications/Safari.app");
+        assert!(by_id.matches(safari, Some("com.apple.Safari")));
+        assert!(!by_id.matches(safari, None));
+
+        let by_path = IgnorePattern::parse("/Applications/Adobe*");
+        assert!(by_path.matches(Path::new("/Applications/Adobe Illustrator.app"), None));
+        assert!(!by_path.matches(safari, Some("com.apple.Safari")));
+    }
+}
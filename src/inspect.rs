@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use tracing::{debug, trace};
+
+use crate::arch::Arch;
+use crate::command;
+use crate::macho;
+use crate::scanner::ArchInfo;
+use crate::tools;
+
+/// Magic number of the code signature's SuperBlob, big endian.
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+/// SuperBlob slots holding launch constraints on the process itself, its parent,
+/// the process responsible for it and the libraries it may load (macOS 13.3+)
+const CSSLOT_LAUNCH_CONSTRAINTS: std::ops::RangeInclusive<u32> = 8..=11;
+
+/// Why deep inspection predicts that trimming an app may break it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Risk {
+    /// A `__RESTRICT` segment, which some apps check for to detect tampering
+    RestrictSegment,
+    /// Only some slices carry a code signature, so it belongs to a specific slice
+    SliceSignature,
+    /// The signature restricts who may launch the app or what it may load
+    LaunchConstraints,
+}
+
+/// What `otool -l` reports about the load commands of one slice.
+#[derive(Debug, Default, PartialEq)]
+struct LoadCommands {
+    restrict_segment: bool,
+    /// File offset and size of the code signature, relative to the slice
+    code_signature: Option<(u64, u64)>,
+}
+
+/// Inspects the load commands and code signature of each slice of `binary_path`
+/// with `otool`, for risks a plain scan can't see. Slices that can't be inspected
+/// are left out rather than counted as risky.
+pub fn inspect(binary_path: &Path, architectures: &[ArchInfo], timeout: Duration) -> Vec<Risk> {
+    let mut risks = Vec::new();
+    let mut signed = Vec::new();
+    for arch in architectures {
+        let Some(commands) = load_commands(binary_path, &arch.arch, timeout) else {
+            continue;
+        };
+        if commands.restrict_segment && !risks.contains(&Risk::RestrictSegment) {
+            risks.push(Risk::RestrictSegment);
+        }
+        if let Some((offset, size)) = commands.code_signature
+            && !risks.contains(&Risk::LaunchConstraints)
+            && macho::slice_offset(binary_path, &arch.arch)
+                .is_some_and(|slice| has_launch_constraints(binary_path, slice + offset, size))
+        {
+            risks.push(Risk::LaunchConstraints);
+        }
+        signed.push(commands.code_signature.is_some());
+    }
+    if signed.contains(&true) && signed.contains(&false) {
+        risks.push(Risk::SliceSignature);
+    }
+    debug!(binary = %binary_path.display(), ?risks, "deep inspection");
+    risks
+}
+
+fn load_commands(binary_path: &Path, arch: &Arch, timeout: Duration) -> Option<LoadCommands> {
+    let output = command::run(
+        Command::new(tools::otool())
+            .arg("-l")
+            .arg("-arch")
+            .arg(arch.as_str())
+            .arg(binary_path),
+        None,
+        timeout,
+    )
+    .ok()
+    .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    trace!(binary = %binary_path.display(), %arch, lines = stdout.lines().count(), "otool -l");
+    Some(parse_load_commands(&stdout))
+}
+
+/// Picks the segment names and the code signature out of `otool -l` output.
+fn parse_load_commands(output: &str) -> LoadCommands {
+    let mut commands = LoadCommands::default();
+    let mut current = "";
+    let mut data_offset = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "cmd" => current = value,
+            "segname" if value == "__RESTRICT" => commands.restrict_segment = true,
+            "dataoff" if current == "LC_CODE_SIGNATURE" => data_offset = value.parse().ok(),
+            "datasize" if current == "LC_CODE_SIGNATURE" => {
+                commands.code_signature = data_offset.zip(value.parse().ok());
+            }
+            _ => {}
+        }
+    }
+    commands
+}
+
+fn has_launch_constraints(binary_path: &Path, offset: u64, size: u64) -> bool {
+    let read = || -> std::io::Result<Vec<u8>> {
+        let mut file = File::open(binary_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut blob = Vec::new();
+        file.take(size).read_to_end(&mut blob)?;
+        Ok(blob)
+    };
+    read().is_ok_and(|blob| superblob_has_launch_constraints(&blob))
+}
+
+/// Does the SuperBlob index list one of the launch constraint slots?
+fn superblob_has_launch_constraints(blob: &[u8]) -> bool {
+    let be_u32 = |at: usize| {
+        blob.get(at..at + 4)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+    };
+    if be_u32(0) != Some(CSMAGIC_EMBEDDED_SIGNATURE) {
+        return false;
+    }
+    // The index of (slot type, offset) pairs follows magic, length and count
+    let count = be_u32(8).unwrap_or(0) as usize;
+    (0..count)
+        .map_while(|index| be_u32(12 + index * 8))
+        .any(|slot| CSSLOT_LAUNCH_CONSTRAINTS.contains(&slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_load_commands() {
+        let output = "/Applications/Example.app/Contents/MacOS/Example (architecture x86_64):
+Load command 0
+      cmd LC_SEGMENT_64
+  cmdsize 72
+  segname __PAGEZERO
+Load command 4
+      cmd LC_SEGMENT_64
+  cmdsize 152
+  segname __RESTRICT
+Section
+  sectname __restrict
+   segname __RESTRICT
+Load command 30
+      cmd LC_CODE_SIGNATURE
+  cmdsize 16
+  dataoff 1048576
+ datasize 20480
+";
+        assert_eq!(
+            parse_load_commands(output),
+            LoadCommands {
+                restrict_segment: true,
+                code_signature: Some((1_048_576, 20_480)),
+            }
+        );
+        assert_eq!(
+            parse_load_commands("      cmd LC_SEGMENT_64\n  segname __TEXT\n"),
+            LoadCommands::default()
+        );
+    }
+
+    #[test]
+    fn test_superblob_has_launch_constraints() {
+        let superblob = |slots: &[u32]| {
+            let mut blob = CSMAGIC_EMBEDDED_SIGNATURE.to_be_bytes().to_vec();
+            blob.extend(0u32.to_be_bytes());
+            blob.extend((slots.len() as u32).to_be_bytes());
+            for &slot in slots {
+                blob.extend(slot.to_be_bytes());
+                blob.extend(0u32.to_be_bytes());
+            }
+            blob
+        };
+        // Code directory, requirements, entitlements and CMS signature
+        assert!(!superblob_has_launch_constraints(&superblob(&[
+            0, 2, 5, 0x10000
+        ])));
+        assert!(superblob_has_launch_constraints(&superblob(&[0, 2, 8])));
+        assert!(!superblob_has_launch_constraints(&[0, 1, 2]));
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner;
+
+/// Where apps bundle a Java runtime: jpackage puts it in `Contents/runtime`, older
+/// launchers in a `.jre` or `.jdk` plug-in.
+fn runtime_dirs(app_path: &Path) -> Vec<PathBuf> {
+    let contents = app_path.join("Contents");
+    let mut dirs = vec![contents.join("runtime")];
+    if let Ok(entries) = fs::read_dir(contents.join("PlugIns")) {
+        dirs.extend(entries.flatten().map(|entry| entry.path()).filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "jre" || extension == "jdk")
+        }));
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs.sort();
+    dirs
+}
+
+/// Universal binaries of the Java runtimes bundled with the app, such as
+/// `libjvm.dylib`, with the bytes trimming each one frees.
+pub fn fat_binaries(app_path: &Path) -> Vec<(PathBuf, u64)> {
+    let mut binaries = Vec::new();
+    let mut pending = runtime_dirs(app_path);
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Runtimes link `Contents/Home` and friends, which would count twice
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file()
+                && let Some(savings) = scanner::nested_savings(&entry.path())
+            {
+                binaries.push((entry.path(), savings));
+            }
+        }
+    }
+    binaries.sort();
+    binaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fat(path: &Path) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_fat_binaries() {
+        let app = std::env::temp_dir().join(format!("bintrim-jvm-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&app);
+        let server = app.join("Contents/runtime/Contents/Home/lib/server");
+        let plugin = app.join("Contents/PlugIns/jdk-17.jre/Contents/Home/lib");
+        fs::create_dir_all(&server).unwrap();
+        fs::create_dir_all(&plugin).unwrap();
+        write_fat(&server.join("libjvm.dylib"));
+        write_fat(&plugin.join("libjava.dylib"));
+        fs::write(plugin.join("modules"), b"not mach-o").unwrap();
+        // Outside of any runtime
+        write_fat(&app.join("Contents/libother.dylib"));
+
+        let binaries = fat_binaries(&app);
+        fs::remove_dir_all(&app).unwrap();
+
+        let names: Vec<_> = binaries
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["libjava.dylib", "libjvm.dylib"]);
+        assert!(binaries.iter().all(|(_, savings)| *savings > 0));
+    }
+}
@@ -0,0 +1,44 @@
+use crossterm::event::{KeyEvent, KeyEventState};
+
+/// Is caps lock on? Uses the lock state reported with `key` when the terminal
+/// supports it, and asks the window server otherwise.
+pub fn caps_lock_on(key: Option<&KeyEvent>) -> bool {
+    key.is_some_and(|key| key.state.contains(KeyEventState::CAPS_LOCK)) || system_caps_lock()
+}
+
+#[cfg(target_os = "macos")]
+fn system_caps_lock() -> bool {
+    /// `kCGEventSourceStateCombinedSessionState`
+    const COMBINED_SESSION_STATE: i32 = 0;
+    /// `kCGEventFlagMaskAlphaShift`
+    const ALPHA_SHIFT_MASK: u64 = 0x0001_0000;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    unsafe extern "C" {
+        fn CGEventSourceFlagsState(state_id: i32) -> u64;
+    }
+
+    unsafe { CGEventSourceFlagsState(COMBINED_SESSION_STATE) & ALPHA_SHIFT_MASK != 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_caps_lock() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
+    #[test]
+    fn test_caps_lock_from_key_state() {
+        let key = KeyEvent {
+            code: KeyCode::Char('A'),
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::CAPS_LOCK,
+        };
+        assert!(caps_lock_on(Some(&key)));
+    }
+}
@@ -0,0 +1,187 @@
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::command;
+
+/// Where the data volume is mounted. Firmlinks make `/Applications` and
+/// `/System/Volumes/Data/Applications` the same folder.
+const DATA_VOLUME: &str = "/System/Volumes/Data";
+
+/// Folder under which Gatekeeper runs quarantined apps from a randomized,
+/// read-only mount.
+const TRANSLOCATION_DIR: &str = "AppTranslocation";
+
+const HDIUTIL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where an app bundle is run from. Trimming only helps the copy that stays
+/// installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Installed,
+    /// A translocated copy of a quarantined app, with the path of the original if
+    /// it could be found
+    Translocated {
+        original: Option<PathBuf>,
+    },
+    /// Inside a mounted disk image
+    DiskImage,
+}
+
+impl Location {
+    pub fn of(path: &Path) -> Self {
+        if is_translocated(path) {
+            Location::Translocated {
+                original: translocation_original(path),
+            }
+        } else if path.starts_with("/Volumes") && on_disk_image(path) {
+            Location::DiskImage
+        } else {
+            Location::Installed
+        }
+    }
+}
+
+/// The path an app is installed at: symlinks resolved, the data volume's
+/// firmlinked prefix removed and translocated copies replaced by their original.
+pub fn installed_path(path: &Path) -> PathBuf {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path = without_firmlink(&path);
+    match Location::of(&path) {
+        Location::Translocated {
+            original: Some(original),
+        } => original,
+        _ => path,
+    }
+}
+
+/// `/System/Volumes/Data/Applications/Slack.app` as `/Applications/Slack.app`,
+/// if both are the same bundle.
+fn without_firmlink(path: &Path) -> PathBuf {
+    let Ok(rest) = path.strip_prefix(DATA_VOLUME) else {
+        return path.to_path_buf();
+    };
+    let short = Path::new("/").join(rest);
+    match (path.metadata(), short.metadata()) {
+        (Ok(long), Ok(short_metadata))
+            if long.dev() == short_metadata.dev() && long.ino() == short_metadata.ino() =>
+        {
+            short
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn is_translocated(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == TRANSLOCATION_DIR)
+}
+
+/// The quarantined bundle a translocated one was mounted from.
+fn translocation_original(path: &Path) -> Option<PathBuf> {
+    let source = mount_source(path)?;
+    // The mount is either of the bundle itself or of the folder it was in
+    let original = if source.extension().is_some_and(|ext| ext == "app") {
+        source
+    } else {
+        source.join(path.file_name()?)
+    };
+    original.is_dir().then_some(original)
+}
+
+/// What the file system `path` lives on was mounted from.
+#[cfg(target_os = "macos")]
+fn mount_source(path: &Path) -> Option<PathBuf> {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let source = unsafe { CStr::from_ptr(stat.f_mntfromname.as_ptr()) };
+    let source = Path::new(std::ffi::OsStr::from_bytes(source.to_bytes()));
+    source.is_absolute().then(|| source.to_path_buf())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn mount_source(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// Whether `path` is on one of the disk images `hdiutil` has attached. They're
+/// only looked up once.
+fn on_disk_image(path: &Path) -> bool {
+    static MOUNT_POINTS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    MOUNT_POINTS
+        .get_or_init(|| {
+            let mut command = Command::new("hdiutil");
+            command.arg("info").arg("-plist");
+            command::run(&mut command, None, HDIUTIL_TIMEOUT)
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| mount_points(&output.stdout))
+                .unwrap_or_default()
+        })
+        .iter()
+        .any(|mount_point| path.starts_with(mount_point))
+}
+
+/// Mount points of the volumes in the property list `hdiutil info -plist` prints.
+fn mount_points(plist: &[u8]) -> Vec<PathBuf> {
+    let Ok(value) = plist::Value::from_reader(std::io::Cursor::new(plist)) else {
+        return Vec::new();
+    };
+    value
+        .as_dictionary()
+        .and_then(|info| info.get("images"))
+        .and_then(plist::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|image| image.as_dictionary()?.get("system-entities")?.as_array())
+        .flatten()
+        .filter_map(|entity| entity.as_dictionary()?.get("mount-point")?.as_string())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let translocated =
+            Path::new("/private/var/folders/xy/abc/T/AppTranslocation/0F3C1E2D-1234/d/Slack.app");
+        assert!(is_translocated(translocated));
+        assert!(!is_translocated(Path::new("/Applications/Slack.app")));
+        assert_eq!(
+            Location::of(Path::new("/Applications/Slack.app")),
+            Location::Installed
+        );
+
+        let plist = br#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>images</key>
+    <array>
+        <dict>
+            <key>system-entities</key>
+            <array>
+                <dict><key>dev-entry</key><string>/dev/disk4</string></dict>
+                <dict>
+                    <key>dev-entry</key><string>/dev/disk4s1</string>
+                    <key>mount-point</key><string>/Volumes/Slack</string>
+                </dict>
+            </array>
+        </dict>
+    </array>
+</dict>
+</plist>"#;
+        assert_eq!(mount_points(plist), [PathBuf::from("/Volumes/Slack")]);
+        assert!(mount_points(b"not a plist").is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use std::ffi::{CStr, OsStr};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{WrapErr, bail};
+
+use crate::state;
+
+/// Advisory lock on the state directory, held while bintrim may trim so that
+/// two instances (say the TUI and a scheduled run) don't trim at the same time.
+///
+/// Under sudo the lock is taken in the state directory of the user who ran sudo,
+/// as that user, so it excludes their own runs and leaves nothing of root's
+/// behind. The lock is released when this is dropped or the process exits.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Takes the lock, failing with a hint about `--force` if another instance has it.
+    /// Without a state directory there is nothing to lock and `None` is returned.
+    pub fn acquire() -> color_eyre::Result<Option<Self>> {
+        let uid = unsafe { libc::getuid() };
+        let sudo_user = sudo_user(
+            uid,
+            std::env::var("SUDO_UID").ok().as_deref(),
+            std::env::var("SUDO_GID").ok().as_deref(),
+        );
+        let Some((uid, gid)) = sudo_user else {
+            return match state::state_dir() {
+                Some(dir) => Self::acquire_in(&dir).map(Some),
+                None => Ok(None),
+            };
+        };
+        let Some(home) = home_dir(uid) else {
+            return Ok(None);
+        };
+        as_user(uid, gid, || Self::acquire_in(&state::state_dir_in(&home))).map(Some)
+    }
+
+    fn acquire_in(dir: &Path) -> color_eyre::Result<Self> {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        Self::acquire_at(&dir.join("bintrim.lock"))
+    }
+
+    /// Takes the lock at `path`, which must be a file of this user's that isn't
+    /// linked anywhere else.
+    fn acquire_at(path: &Path) -> color_eyre::Result<Self> {
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(path)
+            .wrap_err_with(|| format!("failed to open {}", path.display()))?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() || metadata.nlink() != 1 {
+            bail!("{} is not a regular file", path.display());
+        }
+        if metadata.uid() != unsafe { libc::geteuid() } {
+            bail!("{} belongs to another user", path.display());
+        }
+
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::WouldBlock {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                match pid.trim() {
+                    "" => bail!("another bintrim instance is running, use --force to run anyway"),
+                    pid => bail!(
+                        "another bintrim instance is running (pid {}), use --force to run anyway",
+                        pid
+                    ),
+                }
+            }
+            return Err(err).wrap_err_with(|| format!("failed to lock {}", path.display()));
+        }
+
+        // The pid is only informational, the lock itself is what counts
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// User and group ID of whoever ran sudo, from `$SUDO_UID` and `$SUDO_GID`, if
+/// `uid` is root and they're set.
+fn sudo_user(uid: u32, sudo_uid: Option<&str>, sudo_gid: Option<&str>) -> Option<(u32, u32)> {
+    if uid != 0 {
+        return None;
+    }
+    Some((sudo_uid?.parse().ok()?, sudo_gid?.parse().ok()?))
+}
+
+/// The home directory of `uid` from the user database, as sudo may not pass the
+/// user's `HOME` on.
+fn home_dir(uid: u32) -> Option<PathBuf> {
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0; 4096];
+    let mut result = std::ptr::null_mut();
+    let status = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+    let home = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    Some(PathBuf::from(OsStr::from_bytes(home.to_bytes())))
+}
+
+/// Runs `f` as the user `uid` and group `gid` and goes back to root afterwards,
+/// so that what it creates is theirs and it can't touch what they couldn't.
+fn as_user<T>(
+    uid: u32,
+    gid: u32,
+    f: impl FnOnce() -> color_eyre::Result<T>,
+) -> color_eyre::Result<T> {
+    if unsafe { libc::setegid(gid) } != 0 || unsafe { libc::seteuid(uid) } != 0 {
+        return Err(io::Error::last_os_error()).wrap_err("failed to switch to the sudo user");
+    }
+    let result = f();
+    if unsafe { libc::seteuid(0) } != 0 || unsafe { libc::setegid(0) } != 0 {
+        return Err(io::Error::last_os_error()).wrap_err("failed to switch back to root");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_instance() {
+        let path = std::env::temp_dir().join(format!("bintrim-lock-{}.lock", std::process::id()));
+        let first = InstanceLock::acquire_at(&path).unwrap();
+        // flock locks belong to the open file, so a second open conflicts even in
+        // the same process
+        let err = InstanceLock::acquire_at(&path).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("another bintrim instance is running")
+        );
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        drop(first);
+        InstanceLock::acquire_at(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_root() {
+        // Under sudo the lock is the invoking user's, not root's
+        assert_eq!(sudo_user(0, Some("501"), Some("20")), Some((501, 20)));
+        assert_eq!(sudo_user(0, None, None), None);
+        assert_eq!(sudo_user(501, Some("0"), Some("0")), None);
+        assert!(home_dir(0).is_some());
+
+        // Links planted in place of the lock file are refused
+        let dir = std::env::temp_dir().join(format!("bintrim-lock-root-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target");
+        fs::write(&target, "keep").unwrap();
+        let symlink = dir.join("symlink.lock");
+        std::os::unix::fs::symlink(&target, &symlink).unwrap();
+        assert!(InstanceLock::acquire_at(&symlink).is_err());
+        let hard_link = dir.join("hard-link.lock");
+        fs::hard_link(&target, &hard_link).unwrap();
+        assert!(InstanceLock::acquire_at(&hard_link).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+
+        // So is someone else's, which root doesn't take over either
+        let foreign = dir.join("foreign.lock");
+        fs::write(&foreign, "").unwrap();
+        let other = if unsafe { libc::geteuid() } == 0 {
+            501
+        } else {
+            0
+        };
+        if std::os::unix::fs::chown(&foreign, Some(other), None).is_ok() {
+            assert!(InstanceLock::acquire_at(&foreign).is_err());
+            assert_eq!(fs::metadata(&foreign).unwrap().uid(), other);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
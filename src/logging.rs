@@ -0,0 +1,46 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+
+use color_eyre::eyre::WrapErr;
+use tracing::Level;
+
+/// Sends log output to `path`, appending to an existing file.
+///
+/// The terminal is owned by the TUI, so without a log file nothing is logged.
+/// `verbosity` is the number of `-v` flags: info by default, then debug and trace.
+pub fn init(path: &Path, verbosity: u8) -> color_eyre::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .wrap_err_with(|| format!("failed to open log file {}", path.display()))?;
+
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(level(verbosity))
+        .init();
+
+    Ok(())
+}
+
+fn level(verbosity: u8) -> Level {
+    match verbosity {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0), Level::INFO);
+        assert_eq!(level(1), Level::DEBUG);
+        assert_eq!(level(5), Level::TRACE);
+    }
+}
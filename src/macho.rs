@@ -0,0 +1,232 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use crate::arch::Arch;
+use crate::scanner::ArchInfo;
+
+/// Magic numbers of fat files, as stored on disk (big endian).
+const FAT_MAGIC: u32 = 0xcafe_babe;
+pub const FAT_MAGIC_64: u32 = 0xcafe_babf;
+/// Magic numbers of thin 32- and 64-bit Mach-O files, in the file's byte order.
+const MH_MAGIC: u32 = 0xfeed_face;
+const MH_MAGIC_64: u32 = 0xfeed_facf;
+/// Java class files share the fat magic, lipo tells them apart the same way:
+/// their class file version is at least 45 where a fat file has its slice count
+const MAX_FAT_ARCHS: u32 = 45;
+
+const CPU_ARCH_ABI64: u32 = 0x0100_0000;
+const CPU_TYPE_X86: u32 = 7;
+const CPU_TYPE_ARM: u32 = 12;
+const CPU_TYPE_POWERPC: u32 = 18;
+const CPU_SUBTYPE_ARM64E: u32 = 2;
+const CPU_SUBTYPE_MASK: u32 = 0x00ff_ffff;
+
+/// Slices of a Mach-O binary read from its headers, like `lipo -detailed_info`
+/// but without needing the Xcode Command Line Tools. `None` if it isn't Mach-O.
+pub fn architectures(path: &Path) -> Option<Vec<ArchInfo>> {
+    fat_architectures(path).or_else(|| thin_architecture(path).map(|arch| vec![arch]))
+}
+
+/// Slices of a universal binary from its fat header, `None` for any other file.
+pub fn fat_architectures(path: &Path) -> Option<Vec<ArchInfo>> {
+    Some(
+        fat_slices(path)?
+            .into_iter()
+            .map(|slice| ArchInfo {
+                arch: slice.arch,
+                size_bytes: Some(slice.size),
+                align: 1u64.checked_shl(slice.align),
+            })
+            .collect(),
+    )
+}
+
+/// Where the slice for `arch` starts in the file, 0 for a thin binary of that
+/// architecture.
+pub fn slice_offset(path: &Path, arch: &Arch) -> Option<u64> {
+    match fat_slices(path) {
+        Some(slices) => slices
+            .into_iter()
+            .find(|slice| slice.arch == *arch)
+            .map(|slice| slice.offset),
+        None => thin_architecture(path)
+            .filter(|thin| thin.arch == *arch)
+            .map(|_| 0),
+    }
+}
+
+/// A `fat_arch` entry of a universal binary.
+struct FatSlice {
+    arch: Arch,
+    offset: u64,
+    size: u64,
+    /// Alignment as a power of two
+    align: u32,
+}
+
+fn fat_slices(path: &Path) -> Option<Vec<FatSlice>> {
+    let mut file = File::open(path).ok()?;
+    let mut header = [0u8; 8];
+    file.read_exact(&mut header).ok()?;
+    let magic = be_u32(&header[..4]);
+    let count = be_u32(&header[4..]);
+    if !matches!(magic, FAT_MAGIC | FAT_MAGIC_64) || count == 0 || count >= MAX_FAT_ARCHS {
+        return None;
+    }
+
+    // fat_arch is cputype, cpusubtype, offset, size and align, all 32-bit; fat_arch_64
+    // has 64-bit offset and size and a reserved field at the end
+    let arch_size = if magic == FAT_MAGIC_64 { 32 } else { 20 };
+    let mut archs = vec![0u8; arch_size * count as usize];
+    file.read_exact(&mut archs).ok()?;
+    Some(
+        archs
+            .chunks(arch_size)
+            .map(|entry| {
+                let (offset, size, align) = if magic == FAT_MAGIC_64 {
+                    (
+                        be_u64(&entry[8..16]),
+                        be_u64(&entry[16..24]),
+                        be_u32(&entry[24..28]),
+                    )
+                } else {
+                    (
+                        u64::from(be_u32(&entry[8..12])),
+                        u64::from(be_u32(&entry[12..16])),
+                        be_u32(&entry[16..20]),
+                    )
+                };
+                FatSlice {
+                    arch: arch(be_u32(&entry[..4]), be_u32(&entry[4..8])),
+                    offset,
+                    size,
+                    align,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The single slice of a thin Mach-O file, which is the whole file.
+fn thin_architecture(path: &Path) -> Option<ArchInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header).ok()?;
+    let magic = u32::from_le_bytes(header[..4].try_into().ok()?);
+    let big_endian = match magic {
+        MH_MAGIC | MH_MAGIC_64 => false,
+        _ if matches!(magic.swap_bytes(), MH_MAGIC | MH_MAGIC_64) => true,
+        _ => return None,
+    };
+    let read = |bytes: &[u8]| {
+        let bytes = bytes.try_into().unwrap();
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    Some(ArchInfo {
+        arch: arch(read(&header[4..8]), read(&header[8..12])),
+        size_bytes: fs::metadata(path).ok().map(|metadata| metadata.len()),
+        align: None,
+    })
+}
+
+/// Architecture of a CPU type and subtype from a Mach-O header.
+pub fn arch(cpu_type: u32, cpu_subtype: u32) -> Arch {
+    match cpu_type {
+        CPU_TYPE_X86 => Arch::I386,
+        t if t == CPU_TYPE_X86 | CPU_ARCH_ABI64 => Arch::X86_64,
+        t if t == CPU_TYPE_ARM | CPU_ARCH_ABI64 => {
+            if cpu_subtype & CPU_SUBTYPE_MASK == CPU_SUBTYPE_ARM64E {
+                Arch::Arm64e
+            } else {
+                Arch::Arm64
+            }
+        }
+        CPU_TYPE_POWERPC => Arch::Other("ppc".to_string()),
+        _ => Arch::Other(format!("cpu {:#x}", cpu_type)),
+    }
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().unwrap())
+}
+
+fn be_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fat_architectures() {
+        let path = std::env::temp_dir().join(format!("bintrim-macho-fat-{}", std::process::id()));
+        let mut contents = Vec::new();
+        contents.extend(FAT_MAGIC.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (cpu_type, offset, size) in [
+            (0x0100_0007u32, 16384u32, 500u32),
+            (0x0100_000c, 32768, 400),
+        ] {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(3u32.to_be_bytes());
+            contents.extend(offset.to_be_bytes());
+            contents.extend(size.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        fs::write(&path, &contents).unwrap();
+        let archs = architectures(&path).unwrap();
+        let arm64_offset = slice_offset(&path, &Arch::Arm64);
+        let ppc_offset = slice_offset(&path, &Arch::from_name("ppc"));
+
+        // A Java class file, not a universal binary
+        let mut class = FAT_MAGIC.to_be_bytes().to_vec();
+        class.extend([0, 0, 0, 52]);
+        fs::write(&path, class).unwrap();
+        let class_archs = architectures(&path);
+        fs::remove_file(&path).unwrap();
+
+        let summary: Vec<_> = archs
+            .iter()
+            .map(|arch| (arch.arch.clone(), arch.size_bytes, arch.align))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Arch::X86_64, Some(500), Some(16384)),
+                (Arch::Arm64, Some(400), Some(16384))
+            ]
+        );
+        assert_eq!(arm64_offset, Some(32768));
+        assert_eq!(ppc_offset, None);
+        assert!(class_archs.is_none());
+    }
+
+    #[test]
+    fn test_thin_architecture() {
+        let path = std::env::temp_dir().join(format!("bintrim-macho-thin-{}", std::process::id()));
+        let mut contents = MH_MAGIC_64.to_le_bytes().to_vec();
+        contents.extend(0x0100_000cu32.to_le_bytes());
+        contents.extend(0u32.to_le_bytes());
+        contents.resize(64, 0);
+        fs::write(&path, &contents).unwrap();
+        let archs = architectures(&path);
+        fs::remove_file(&path).unwrap();
+
+        let archs = archs.unwrap();
+        assert_eq!(archs.len(), 1);
+        assert_eq!(archs[0].arch, Arch::Arm64);
+        assert_eq!(archs[0].size_bytes, Some(64));
+    }
+
+    #[test]
+    fn test_arch() {
+        assert_eq!(arch(0x0100_000c, 0x8000_0002), Arch::Arm64e);
+        assert_eq!(arch(7, 3), Arch::I386);
+    }
+}
@@ -1,42 +1,440 @@
+mod alias;
+mod app;
+mod appex;
+mod arch;
+mod breakdown;
+mod bytesize;
+mod cancel;
+mod cli;
+mod clipboard;
+mod columns;
+mod command;
+mod config;
+mod confirm;
+mod daemon;
+mod denylist;
+mod devtools;
+mod display;
+mod elevated;
+mod extensions;
+mod filter;
+mod flags;
+mod frameworks;
+mod helpers;
+mod history;
+mod i18n;
+mod ignore;
+mod inspect;
+mod jvm;
+mod keyboard;
+mod location;
+mod lock;
+mod logging;
+mod macho;
+mod managed;
+mod outcome;
+mod pick;
+mod plain;
+mod priority;
+mod processes;
+mod registry;
+mod report;
+mod savings;
 mod scanner;
-
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, poll};
+mod session;
+mod sharing;
+mod sizes;
+mod state;
+mod stats;
+mod terminal;
+mod tools;
+mod trimmer;
+mod update;
+mod vendor;
+mod view;
+mod volume;
+mod worker;
+mod xcode;
+
+use app::{Action, AppState, ListFilter, SelectionHistory};
+use arch::Arch;
+use breakdown::Breakdown;
+use bytesize::ByteSize;
+use cancel::CancelToken;
+use clap::Parser;
+use cli::Cli;
+use color_eyre::eyre::WrapErr;
+use columns::Column;
+use config::Config;
+use confirm::Confirmation;
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEvent, KeyEventKind, poll,
+};
+use denylist::Denylist;
+use devtools::Artifact;
+use display::DisplayOptions;
+use filter::AppFilter;
+use i18n::t;
+use ignore::IgnorePattern;
+use lock::InstanceLock;
+use priority::Priority;
 use ratatui::{
     DefaultTerminal, Frame,
     layout::{Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph},
+    widgets::{
+        Block, Borders, Cell, Clear, Gauge, HighlightSpacing, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
 };
-use scanner::{AppInfo, scan_applications_with_progress};
-use std::io::Write;
-use std::process::{Command, Stdio};
+use registry::{AppKey, Registry};
+use report::ReportOptions;
+use scanner::{
+    AppInfo, Badge, ScanOptions, ScanProgress, SkipReason, scan_applications_with_progress,
+};
+use serde::{Deserialize, Serialize};
+use session::SessionLog;
+use sizes::SizeHistory;
+use state::UiState;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::{Command, ExitCode};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+use trimmer::{BatchReport, Savings, Strategy, TrimError, TrimEvent, TrimFailure, TrimOptions};
+use view::View;
+use worker::Supervisor;
 
-fn main() -> color_eyre::Result<()> {
+fn main() -> color_eyre::Result<ExitCode> {
     color_eyre::install()?;
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // --help and --version end up here too
+        Err(err) => {
+            err.print()?;
+            return Ok(if err.use_stderr() {
+                ExitCode::from(outcome::USAGE_EXIT_CODE)
+            } else {
+                ExitCode::SUCCESS
+            });
+        }
+    };
+    let config = Config::load()?;
+    i18n::init(config.language.as_deref());
+    config.tools.verify()?;
+    tools::init(config.tools.clone());
+
+    if let Some(log_file) = cli.log_file.as_ref().or(config.log_file.as_ref()) {
+        logging::init(log_file, cli.verbose)?;
+    }
+
+    // The config's lock can't be lifted from the command line
+    let audit = cli.audit || config.audit;
+    if audit {
+        match &cli.command {
+            Some(cli::Command::Trim(_)) => {
+                color_eyre::eyre::bail!("bintrim trim is disabled in audit mode")
+            }
+            Some(cli::Command::Pick(args)) if !args.print => {
+                color_eyre::eyre::bail!("bintrim pick is disabled in audit mode, use --print")
+            }
+            Some(cli::Command::Helper(args))
+                if matches!(args.action, cli::HelperAction::Install) =>
+            {
+                color_eyre::eyre::bail!("the privileged helper is disabled in audit mode")
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(cli::Command::Helper(args)) = &cli.command {
+        return helper_command(&args.action, &config);
+    }
+
+    let elevated = unsafe { libc::geteuid() } == 0;
+    // Held until bintrim exits, and only by runs that can trim
+    let read_only = audit
+        || matches!(
+            &cli.command,
+            Some(cli::Command::List(_))
+                | Some(cli::Command::Pick(cli::PickArgs { print: true, .. }))
+        );
+    let _lock = if cli.force || read_only {
+        None
+    } else {
+        InstanceLock::acquire()?
+    };
+    let mut scan_options = ScanOptions::default();
+    if cli.all_users {
+        if !elevated {
+            color_eyre::eyre::bail!("--all-users must be run with sudo");
+        }
+        scan_options.roots.extend(scanner::user_roots());
+    }
+    if cli.volumes {
+        scan_options.roots.extend(scanner::volume_roots());
+    }
+    if cli.games || config.games {
+        let libraries = scanner::steam_library()
+            .into_iter()
+            .chain(config.game_dirs.clone());
+        scan_options
+            .roots
+            .extend(libraries.flat_map(|library| scanner::game_roots(&library)));
+    }
+    if cli.derived_data || config.derived_data {
+        scan_options.roots.extend(scanner::derived_data_roots());
+    }
+    scan_options.roots.extend(
+        config
+            .build_dirs
+            .iter()
+            .chain(&cli.build_dir)
+            .map(|path| scanner::build_root(path.clone())),
+    );
+    if let Some(secs) = config.scan_timeout_secs {
+        scan_options.command_timeout = Duration::from_secs(secs);
+    }
+    scan_options.strategies = config.strategies();
+    scan_options.ignore = config
+        .ignore
+        .iter()
+        .chain(&cli.ignore)
+        .map(|pattern| IgnorePattern::parse(pattern))
+        .collect();
+    scan_options.denylist = Denylist::new(
+        config
+            .unprotect
+            .iter()
+            .map(|pattern| IgnorePattern::parse(pattern))
+            .collect(),
+    );
+    scan_options.deep = cli.deep || config.deep_inspection;
+    scan_options.keep_languages = i18n::system_language().into_iter().collect();
+    scan_options.helpers = cli.helpers || config.helpers;
+
+    let mut trim_options = TrimOptions {
+        elevated,
+        helper: !audit && !elevated && daemon::available(),
+        audit,
+        confirmation: config.confirm,
+        report_out: cli.report_out.clone(),
+        lipo_available: trimmer::lipo_available(),
+        ownership: config.ownership,
+        app_ownership: config.app_ownership(),
+        ..TrimOptions::default()
+    };
+    if let Some(secs) = config.trim_timeout_secs {
+        trim_options.timeout = Duration::from_secs(secs);
+    }
+
+    let mut filter = AppFilter {
+        min_size: cli.min_size,
+        architectures: cli.arch.iter().map(|arch| Arch::from_name(arch)).collect(),
+        category: cli.category.clone(),
+        ..AppFilter::default()
+    };
+    if let Some(name) = &cli.name {
+        filter.set_name(name)?;
+    }
+
+    if cli.plain || cli.command.is_some() || !std::io::stdout().is_terminal() {
+        config.priority.apply_to_current_thread();
+        let report_options = ReportOptions {
+            path: cli.report.or(config.report_path),
+            webhook: cli.webhook.or(config.report_webhook),
+        };
+        let outcome = match cli.command {
+            Some(cli::Command::Trim(args)) => {
+                let mut targets = args.apps;
+                if args.stdin {
+                    for line in std::io::stdin().lines() {
+                        let line = line?;
+                        let line = line.trim();
+                        if !line.is_empty() && !line.starts_with('#') {
+                            targets.push(line.to_string());
+                        }
+                    }
+                }
+                plain::run_targets(&targets, &scan_options, &trim_options, &report_options)?
+            }
+            Some(cli::Command::Pick(args)) if args.print => {
+                plain::print_candidates(&filter, &scan_options);
+                return Ok(ExitCode::SUCCESS);
+            }
+            Some(cli::Command::List(args)) => {
+                plain::print_list(&filter, &scan_options, args.sort, args.top);
+                return Ok(ExitCode::SUCCESS);
+            }
+            Some(cli::Command::Pick(args)) => plain::run_pick(
+                args.builtin,
+                &filter,
+                &scan_options,
+                &trim_options,
+                &report_options,
+            )?,
+            Some(cli::Command::Helper(_)) => unreachable!("helper commands return early"),
+            None => plain::run(&filter, &scan_options, &trim_options, &report_options)?,
+        };
+        println!("{}", outcome.summary_line());
+        return Ok(outcome.exit_code());
+    }
+
+    let display = DisplayOptions {
+        ascii: cli.ascii || config.ascii,
+        no_color: cli.no_color
+            || config.no_color
+            || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+    };
+
     let terminal = ratatui::init();
-    let result = App::new().run(terminal);
+    worker::install_panic_hook();
+    // Lets the password popup accept pastes from password managers in one piece
+    crossterm::execute!(std::io::stdout(), EnableBracketedPaste)?;
+    let mut app = App::new()
+        .with_scan_options(scan_options)
+        .with_trim_options(trim_options)
+        .with_sudo_prompt(cli.sudo_prompt || config.sudo_prompt)
+        .with_display(display)
+        .with_hidden_columns(config.hidden_columns)
+        .with_filter(filter)
+        .with_priority(config.priority)
+        .with_update_check(config.check_for_updates)
+        .with_size_history(SizeHistory::load())
+        .with_ui_state(UiState::load());
+    let result = app.run(terminal);
+    crossterm::execute!(std::io::stdout(), DisableBracketedPaste)?;
     ratatui::restore();
-    result
+
+    // Running as root would leave a root-owned state file in the user's home
+    if !elevated && let Err(err) = app.ui_state().save() {
+        warn!("failed to save UI state: {:#}", err);
+    }
+    if !elevated && let Err(err) = app.size_history.save() {
+        warn!("failed to save the size history: {:#}", err);
+    }
+
+    if cli.summary_on_exit || config.summary_on_exit {
+        println!("{}", app.session_log.summary());
+    }
+
+    if cli.verbose > 0 {
+        for (name, reason) in &app.scan_progress.skipped {
+            eprintln!("skipped {}: {}", name, reason);
+        }
+    }
+
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+/// Color of a badge's symbol: red for those that block trimming, other colors
+/// for warnings and plain information.
+fn badge_color(badge: Badge) -> Color {
+    match badge {
+        Badge::AppStore => Color::Blue,
+        Badge::Managed => Color::Yellow,
+        Badge::Running => Color::Green,
+        Badge::AutoUpdates => Color::Cyan,
+        Badge::Risky => Color::LightRed,
+        Badge::Excluded => Color::Gray,
+        Badge::TrimmedBefore => Color::LightMagenta,
+        Badge::Setapp => Color::Magenta,
+        Badge::Shared => Color::LightCyan,
+        Badge::Xcode => Color::LightBlue,
+        Badge::Hidden => Color::DarkGray,
+        Badge::Wine => Color::LightYellow,
+        Badge::IosApp | Badge::ReadOnly | Badge::Immutable | Badge::Copy | Badge::Protected => {
+            Color::Red
+        }
+    }
+}
+
+fn strategy_label(strategy: Strategy) -> &'static str {
+    let strings = t();
+    match strategy {
+        Strategy::KeepOnlyNative => strings.strategy_keep_only_native,
+        Strategy::NestedOnly => strings.strategy_nested_only,
+        Strategy::LipoRemove | Strategy::Skip => strings.strategy_lipo_remove,
+    }
 }
 
-enum AppState {
-    Loading,
-    Ready,
-    PopupNoSelection,
-    PopupPasswordInput,
-    Trimming,
+/// `bintrim helper install`, `uninstall` and `serve`.
+fn helper_command(action: &cli::HelperAction, config: &Config) -> color_eyre::Result<ExitCode> {
+    if unsafe { libc::geteuid() } != 0 {
+        color_eyre::eyre::bail!("bintrim helper must be run with sudo");
+    }
+    match action {
+        cli::HelperAction::Install => {
+            daemon::install(&daemon::roots(&config.helper_roots))
+                .wrap_err("failed to install the helper")?;
+            println!("{}", t().helper_installed);
+        }
+        cli::HelperAction::Uninstall => {
+            daemon::uninstall().wrap_err("failed to remove the helper")?;
+            println!("{}", t().helper_uninstalled);
+        }
+        cli::HelperAction::Serve { roots } => daemon::serve(roots)?,
+    }
+    Ok(ExitCode::SUCCESS)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum SortMode {
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
     Size,
+    #[value(alias = "name")]
     Alphabetical,
+    /// By what every approach together could free, see [`savings::PotentialSavings`]
+    Potential,
+}
+
+impl SortMode {
+    pub fn sort(self, apps: &mut [AppInfo]) {
+        match self {
+            SortMode::Size => {
+                // Sort by prunable size (largest first), non-prunable apps at the end
+                apps.sort_by(|a, b| match (a.prune_size(), b.prune_size()) {
+                    (Some(size_a), Some(size_b)) => size_b.cmp(&size_a),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => a.name.cmp(&b.name),
+                });
+            }
+            SortMode::Alphabetical => {
+                apps.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+            SortMode::Potential => {
+                // Largest first, apps without an estimate at the end
+                apps.sort_by(|a, b| {
+                    let total = |app: &AppInfo| app.potential.map(|potential| potential.total());
+                    total(b).cmp(&total(a)).then_with(|| a.name.cmp(&b.name))
+                });
+            }
+        }
+    }
 }
 
+/// How long a transient status message stays visible.
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+/// How often the screen is redrawn while gauges and spinners move.
+const RENDER_TICK: Duration = Duration::from_millis(50);
+/// How often background results and expiring status messages are checked
+/// otherwise. Nothing is redrawn unless one of them changed something.
+const IDLE_TICK: Duration = Duration::from_millis(250);
+
+/// How long an app has to take before the scan screen shows its elapsed time.
+const SLOW_APP_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// The trimmed apps re-analyzed after a trim batch, by path.
+type TrimResult = Vec<(PathBuf, Result<AppInfo, SkipReason>)>;
+
+/// The scanned apps and the developer files found next to them.
+type ScanResult = (Vec<AppInfo>, Vec<Artifact>);
+
 /// The main application which holds the state and logic of the application.
 pub struct App {
     /// Is the application running?
@@ -48,27 +446,109 @@ pub struct App {
     /// Current state of the app
     state: AppState,
     /// List state for scrolling
-    list_state: ListState,
+    table_state: TableState,
     /// Current scan progress
-    scan_progress: usize,
-    /// Total items to scan
-    scan_total: usize,
+    scan_progress: ScanProgress,
     /// Current trim progress
     trim_progress: usize,
     /// Total items to trim
     trim_total: usize,
     /// Current app being trimmed
     trim_current: String,
-    /// Shared state for trimming progress
-    trim_progress_state: Option<Arc<Mutex<(usize, usize, String)>>>,
+    /// Bytes freed so far by the running trim batch
+    trim_freed: u64,
+    /// Events of the running trim batch
+    trim_events: Option<Receiver<TrimEvent>>,
+    /// Report of the trim batch, kept until its apps are re-analyzed
+    trim_report: Option<BatchReport>,
     /// Shared state for trim result
-    trim_result_state: Option<Arc<Mutex<Option<Vec<AppInfo>>>>>,
+    trim_result_state: Option<Arc<Mutex<Option<TrimResult>>>>,
+    /// Estimated and actual savings of the apps trimmed last
+    last_savings: Vec<Savings>,
+    /// Everything trimmed so far, for the summary on exit
+    session_log: SessionLog,
+    /// Shared state for re-analyzing the trimmed apps, `None` until it starts
+    rescan_progress_state: Option<Arc<Mutex<Option<ScanProgress>>>>,
+    /// Progress of re-analyzing the trimmed apps, once the trim worker got to it
+    rescan_progress: Option<ScanProgress>,
     /// Password input buffer
     password_input: String,
+    /// Was caps lock on while typing the password?
+    caps_lock: bool,
+    /// Ask for the password with sudo's own prompt instead of the popup
+    sudo_prompt: bool,
+    /// Suspend the TUI for sudo's prompt once the current event is handled
+    sudo_prompt_pending: bool,
+    /// Bundle to put the cursor on once the scan finishes, and its key in case it
+    /// moved
+    restore_selection: Option<(PathBuf, Option<AppKey>)>,
+    /// Earlier selections for `u`
+    selection_history: SelectionHistory,
+    /// Columns turned off in the column chooser
+    hidden_columns: Vec<Column>,
+    /// Priority of the scan and trim threads
+    priority: Priority,
+    /// Look for a newer release in the background
+    update_check: bool,
+    /// Newer release found by the update check
+    available_update: Option<String>,
+    /// Position in the column chooser
+    column_cursor: usize,
+    /// Name of the app shown in the breakdown popup
+    breakdown_app: String,
+    /// Breakdown of that app, once analyzed
+    breakdown: Option<Breakdown>,
+    /// Simulator runtimes and other developer files found by the scan
+    artifacts: Vec<Artifact>,
+    /// Shared state for the breakdown being analyzed
+    breakdown_state: Option<Arc<Mutex<Option<Breakdown>>>>,
     /// Show non-toggleable apps
     show_non_toggleable: bool,
+    /// What the list and the summary show, see [`View`]
+    view: View,
+    /// Must `view` be worked out again before the next frame?
+    view_stale: bool,
+    /// Only show the apps this filter matches, from the command line, the filter
+    /// prompt and the category key
+    filter: AppFilter,
+    /// Text in the filter prompt
+    filter_input: String,
+    /// Why the text in the filter prompt couldn't be applied
+    filter_error: Option<String>,
+    /// Is the summary block collapsed into a single status line?
+    summary_collapsed: bool,
     /// Current sort mode
     sort_mode: SortMode,
+    /// Where and how applications are scanned
+    scan_options: ScanOptions,
+    /// How applications are trimmed
+    trim_options: TrimOptions,
+    /// ASCII-only and no-color rendering
+    display: DisplayOptions,
+    /// Scroll offset of the skipped applications, breakdown and vendor popups
+    diagnostics_scroll: u16,
+    /// The scan, trim and breakdown threads
+    workers: Supervisor,
+    /// Shared state for the progress of the scan
+    scan_progress_state: Arc<Mutex<ScanProgress>>,
+    /// Shared state for the scan result
+    scan_result_state: Arc<Mutex<Option<ScanResult>>>,
+    /// Can `r` in the error popup scan again? Set when the scan or trim thread crashed.
+    retry_scan: bool,
+    /// Sizes of the apps in earlier scans
+    size_history: SizeHistory,
+    /// What was typed into the strict confirmation popup
+    confirm_input: String,
+    /// Did the last attempt at the strict confirmation not match?
+    confirm_rejected: bool,
+    /// Stops the running scan or trim, a new one for each
+    cancel: CancelToken,
+    /// Message shown in the error popup
+    error_message: String,
+    /// Apps that failed in the last trim batch
+    trim_failures: Vec<TrimFailure>,
+    /// Transient message for the status line and when it was shown
+    status_message: Option<(String, Instant)>,
 }
 
 impl Default for App {
@@ -85,66 +565,216 @@ impl App {
             apps: Vec::new(),
             selected_index: 0,
             state: AppState::Loading,
-            list_state: ListState::default(),
-            scan_progress: 0,
-            scan_total: 0,
+            table_state: TableState::default(),
+            scan_progress: ScanProgress::default(),
             trim_progress: 0,
             trim_total: 0,
+            last_savings: Vec::new(),
+            session_log: SessionLog::default(),
             trim_current: String::new(),
-            trim_progress_state: None,
+            trim_freed: 0,
+            trim_events: None,
+            trim_report: None,
             trim_result_state: None,
+            rescan_progress_state: None,
+            rescan_progress: None,
             password_input: String::new(),
+            caps_lock: false,
+            sudo_prompt: false,
+            sudo_prompt_pending: false,
+            restore_selection: None,
+            selection_history: SelectionHistory::default(),
+            hidden_columns: Vec::new(),
+            priority: Priority::default(),
+            update_check: false,
+            available_update: None,
+            column_cursor: 0,
+            breakdown_app: String::new(),
+            breakdown: None,
+            artifacts: Vec::new(),
+            breakdown_state: None,
             show_non_toggleable: false,
+            view: View::default(),
+            view_stale: true,
+            filter: AppFilter::default(),
+            filter_input: String::new(),
+            filter_error: None,
+            summary_collapsed: false,
             sort_mode: SortMode::Size,
+            scan_options: ScanOptions::default(),
+            trim_options: TrimOptions::default(),
+            display: DisplayOptions::default(),
+            diagnostics_scroll: 0,
+            workers: Supervisor::default(),
+            scan_progress_state: Arc::default(),
+            scan_result_state: Arc::default(),
+            retry_scan: false,
+            size_history: SizeHistory::default(),
+            confirm_input: String::new(),
+            confirm_rejected: false,
+            cancel: CancelToken::default(),
+            error_message: String::new(),
+            trim_failures: Vec::new(),
+            status_message: None,
         }
     }
 
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
-        self.running = true;
-        let progress = Arc::new(Mutex::new((0usize, 0usize)));
-        let apps_result = Arc::new(Mutex::new(None));
+    /// Sets where and how applications are scanned.
+    pub fn with_scan_options(mut self, scan_options: ScanOptions) -> Self {
+        self.scan_options = scan_options;
+        self
+    }
 
-        let progress_clone = Arc::clone(&progress);
-        let apps_clone = Arc::clone(&apps_result);
-        thread::spawn(move || {
-            let apps = scan_applications_with_progress(|current, total, _name| {
-                if let Ok(mut p) = progress_clone.lock() {
-                    *p = (current, total);
-                }
-            });
-            if let Ok(mut result) = apps_clone.lock() {
-                *result = Some(apps);
+    /// Sets how applications are trimmed. When running as root the password prompt is skipped.
+    pub fn with_trim_options(mut self, trim_options: TrimOptions) -> Self {
+        self.trim_options = trim_options;
+        self
+    }
+
+    /// Compares each scan with the sizes from earlier ones.
+    pub fn with_size_history(mut self, size_history: SizeHistory) -> Self {
+        self.size_history = size_history;
+        self
+    }
+
+    /// Restores the sort order, toggles and cursor position of an earlier session.
+    pub fn with_ui_state(mut self, ui_state: UiState) -> Self {
+        if let Some(sort_mode) = ui_state.sort_mode {
+            self.sort_mode = sort_mode;
+        }
+        self.show_non_toggleable = ui_state.show_all;
+        self.summary_collapsed = ui_state.summary_collapsed;
+        if let Some(hidden_columns) = ui_state.hidden_columns {
+            self.hidden_columns = hidden_columns;
+        }
+        self.restore_selection = ui_state
+            .selected_app
+            .map(|path| (path, ui_state.selected_app_key));
+        self
+    }
+
+    /// The state to restore on the next launch.
+    pub fn ui_state(&self) -> UiState {
+        UiState {
+            sort_mode: Some(self.sort_mode),
+            show_all: self.show_non_toggleable,
+            summary_collapsed: self.summary_collapsed,
+            hidden_columns: Some(self.hidden_columns.clone()),
+            selected_app: self
+                .apps
+                .get(self.selected_index)
+                .map(|app| app.path.clone())
+                .or_else(|| {
+                    self.restore_selection
+                        .as_ref()
+                        .map(|(path, _)| path.clone())
+                }),
+            selected_app_key: self
+                .apps
+                .get(self.selected_index)
+                .map(AppKey::of)
+                .or_else(|| {
+                    self.restore_selection
+                        .as_ref()
+                        .and_then(|(_, key)| key.clone())
+                }),
+        }
+    }
+
+    /// Uses sudo's own prompt in the terminal instead of the password popup.
+    pub fn with_sudo_prompt(mut self, sudo_prompt: bool) -> Self {
+        self.sudo_prompt = sudo_prompt;
+        self
+    }
+
+    /// Sets the columns hidden by default, before any saved UI state is applied.
+    pub fn with_hidden_columns(mut self, hidden_columns: Vec<Column>) -> Self {
+        self.hidden_columns = hidden_columns;
+        self
+    }
+
+    /// Starts with only the apps `filter` matches shown.
+    pub fn with_filter(mut self, filter: AppFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the priority the scan and trim threads run at.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Checks for a newer release on startup (opt-in).
+    pub fn with_update_check(mut self, update_check: bool) -> Self {
+        self.update_check = update_check;
+        self
+    }
+
+    /// Sets whether to draw with ASCII characters only and without colors.
+    pub fn with_display(mut self, display: DisplayOptions) -> Self {
+        self.display = display;
+        self
+    }
+
+    pub fn run(&mut self, mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
+        self.running = true;
+        self.start_scan()?;
+
+        let update_result = Arc::new(Mutex::new(None));
+        if self.update_check {
+            let update_clone = Arc::clone(&update_result);
+            // Not a worker: a failed check only means no notice
+            let spawned = std::thread::Builder::new()
+                .name("update-check".to_string())
+                .spawn(move || {
+                    if let Some(version) = update::available_update()
+                        && let Ok(mut result) = update_clone.lock()
+                    {
+                        *result = Some(version);
+                    }
+                });
+            if let Err(err) = spawned {
+                warn!("failed to start update check: {}", err);
             }
-        });
+        }
 
+        let mut needs_redraw = true;
         while self.running {
+            // Whatever animates is redrawn on every tick, including the one it stops on
+            needs_redraw |= self.is_animating();
+            if self.available_update.is_none()
+                && let Ok(mut result) = update_result.lock()
+                && let Some(version) = result.take()
+            {
+                self.available_update = Some(version);
+                needs_redraw = true;
+            }
+            for crash in self.workers.reap() {
+                self.worker_crashed(crash);
+                needs_redraw = true;
+            }
             if matches!(self.state, AppState::Loading) {
-                if let Ok(p) = progress.lock() {
-                    self.scan_progress = p.0;
-                    self.scan_total = p.1;
+                if let Ok(p) = self.scan_progress_state.lock() {
+                    self.scan_progress = p.clone();
                 }
-                if let Ok(mut result) = apps_result.lock()
-                    && let Some(apps) = result.take()
-                {
-                    self.apps = apps;
-                    self.sort_apps();
-                    // Start with first prunable app selected
-                    for (i, app) in self.apps.iter().enumerate() {
-                        if app.has_x86_64() {
-                            self.selected_index = i;
-                            break;
-                        }
-                    }
-                    self.state = AppState::Ready;
+                let finished = self
+                    .scan_result_state
+                    .lock()
+                    .ok()
+                    .and_then(|mut result| result.take());
+                if let Some((apps, artifacts)) = finished {
+                    self.artifacts = artifacts;
+                    self.finish_scan(apps);
                 }
             }
             if matches!(self.state, AppState::Trimming) {
-                if let Some(ref progress_state) = self.trim_progress_state
-                    && let Ok(p) = progress_state.lock()
+                self.poll_trim_events();
+                if let Some(ref rescan_state) = self.rescan_progress_state
+                    && let Ok(p) = rescan_state.lock()
+                    && let Some(p) = p.as_ref()
                 {
-                    self.trim_progress = p.0;
-                    self.trim_total = p.1;
-                    self.trim_current = p.2.clone();
+                    self.rescan_progress = Some(p.clone());
                 }
 
                 // Check if trimming is complete
@@ -158,19 +788,21 @@ impl App {
                     None
                 };
 
-                let trimming_done = if let Some(apps) = new_apps {
-                    self.apps = apps;
-                    self.sort_apps();
-                    self.selected_index = 0;
-                    // Find first prunable app if not showing all
-                    if !self.show_non_toggleable {
-                        for (i, app) in self.apps.iter().enumerate() {
-                            if app.has_x86_64() {
-                                self.selected_index = i;
-                                break;
-                            }
-                        }
+                let trimming_done = if let Some(results) = new_apps {
+                    let mut report = self.trim_report.take().unwrap_or_default();
+                    report.record.scan_duration_ms = report::millis(self.scan_progress.duration);
+                    if let Err(err) = report.record.save(
+                        self.trim_options.report_out.as_deref(),
+                        self.trim_options.elevated,
+                    ) {
+                        warn!("failed to save the batch report: {:#}", err);
                     }
+                    self.session_log.record(&report.savings, &report.failures);
+                    self.last_savings = report.savings;
+                    self.trim_failures = report.failures;
+                    // The trimmed apps are no longer prunable, so there's nothing to go back to
+                    self.selection_history.clear();
+                    self.apply_reanalyzed(results);
                     true
                 } else {
                     false
@@ -178,22 +810,218 @@ impl App {
 
                 if trimming_done {
                     self.state = AppState::Ready;
-                    self.trim_progress_state = None;
+                    self.trim_events = None;
                     self.trim_result_state = None;
+                    self.rescan_progress_state = None;
+                    self.rescan_progress = None;
+                    let off = self.last_savings.iter().filter(|s| s.is_off()).count();
+                    let cancelled = self
+                        .trim_failures
+                        .iter()
+                        .filter(|failure| matches!(failure.error, TrimError::Cancelled))
+                        .count();
+                    if cancelled > 0 {
+                        self.error_message = self.trim_failures_message();
+                        self.set_status((t().trim_cancelled_status)(cancelled));
+                    } else if self.trim_failures.is_empty() && off > 0 {
+                        self.error_message.clear();
+                        self.set_status((t().trimmed_off_estimate)(self.trim_total, off));
+                    } else if self.trim_failures.is_empty() {
+                        self.error_message.clear();
+                        self.set_status((t().trimmed)(self.trim_total));
+                    } else {
+                        self.error_message = self.trim_failures_message();
+                        self.set_status((t().trim_failed_status)(self.trim_failures.len()));
+                    }
                 }
             }
-            terminal.draw(|frame| self.render(frame))?;
-            if matches!(self.state, AppState::Loading | AppState::Trimming) {
-                if poll(Duration::from_millis(50))? {
-                    self.handle_crossterm_events()?;
-                }
+            if self.breakdown_state.is_some() {
+                self.poll_breakdown();
+            }
+            if self
+                .status_message
+                .as_ref()
+                .is_some_and(|(_, shown_at)| shown_at.elapsed() >= STATUS_MESSAGE_DURATION)
+            {
+                self.status_message = None;
+                needs_redraw = true;
+            }
+            if needs_redraw {
+                terminal.draw(|frame| self.draw(frame))?;
+                needs_redraw = false;
+            }
+
+            let tick = if self.is_animating() {
+                RENDER_TICK
             } else {
+                IDLE_TICK
+            };
+            if poll(tick)? {
+                // Handle everything that queued up, such as held keys, before drawing
                 self.handle_crossterm_events()?;
+                while poll(Duration::ZERO)? {
+                    self.handle_crossterm_events()?;
+                }
+                needs_redraw = true;
+            }
+            if self.sudo_prompt_pending {
+                self.sudo_prompt_pending = false;
+                self.prompt_sudo_in_terminal(&mut terminal)?;
+                needs_redraw = true;
             }
         }
         Ok(())
     }
 
+    /// Is something on screen moving, such as a progress gauge or the spinner of
+    /// a breakdown being analyzed?
+    fn is_animating(&self) -> bool {
+        matches!(self.state, AppState::Loading | AppState::Trimming)
+            || self.breakdown_state.is_some()
+    }
+
+    /// Analyzes the app under the cursor in the background and opens the breakdown popup.
+    fn show_breakdown(&mut self) {
+        let Some(app) = self.apps.get(self.selected_index).cloned() else {
+            return;
+        };
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = Arc::clone(&result);
+        let priority = self.priority;
+        let name = app.name.clone();
+        let spawned = self.workers.spawn("breakdown", move || {
+            priority.apply_to_current_thread();
+            let breakdown = breakdown::analyze(&app);
+            if let Ok(mut result) = result_clone.lock() {
+                *result = Some(breakdown);
+            }
+        });
+
+        match spawned {
+            Ok(()) => {
+                self.breakdown_app = name;
+                self.breakdown = None;
+                self.breakdown_state = Some(result);
+                self.diagnostics_scroll = 0;
+                self.state = AppState::PopupBreakdown;
+            }
+            Err(err) => self.show_error((t().breakdown_start_failed)(&err.to_string())),
+        }
+    }
+
+    /// Picks up the finished breakdown.
+    fn poll_breakdown(&mut self) {
+        let finished = self
+            .breakdown_state
+            .as_ref()
+            .and_then(|state| state.lock().ok()?.take());
+        if let Some(breakdown) = finished {
+            self.breakdown = Some(breakdown);
+            self.breakdown_state = None;
+        }
+    }
+
+    /// Scans in the background, with the progress and result picked up on each tick.
+    fn start_scan(&mut self) -> std::io::Result<()> {
+        self.scan_progress_state = Arc::default();
+        self.scan_result_state = Arc::default();
+        self.scan_progress = ScanProgress::default();
+        self.state = AppState::Loading;
+        self.cancel = CancelToken::default();
+
+        let progress = Arc::clone(&self.scan_progress_state);
+        let cancel = self.cancel.clone();
+        let apps_result = Arc::clone(&self.scan_result_state);
+        let scan_options = self.scan_options.clone();
+        let priority = self.priority;
+        self.workers.spawn("scanner", move || {
+            priority.apply_to_current_thread();
+            let apps = scan_applications_with_progress(&scan_options, &cancel, |event| {
+                if let Ok(mut p) = progress.lock() {
+                    p.apply(event);
+                }
+            });
+            let artifacts = devtools::find_artifacts();
+            if let Ok(mut result) = apps_result.lock() {
+                *result = Some((apps, artifacts));
+            }
+        })
+    }
+
+    /// Scans again after the scan or trim thread crashed, keeping the cursor on
+    /// the same app.
+    fn retry_scan(&mut self) {
+        self.retry_scan = false;
+        if self.workers.is_running("scanner") {
+            self.state = AppState::Loading;
+            return;
+        }
+        self.restore_selection = self
+            .apps
+            .get(self.selected_index)
+            .map(|app| (app.path.clone(), Some(AppKey::of(app))));
+        if let Err(err) = self.start_scan() {
+            self.show_error((t().scan_start_failed)(&err.to_string()));
+        }
+    }
+
+    /// Cleans up after a worker that panicked and shows what happened. The scan and
+    /// trim threads leave the list incomplete or outdated, so they offer a new scan.
+    fn worker_crashed(&mut self, crash: worker::Crash) {
+        match crash.worker {
+            "scanner" => {
+                self.show_error((t().scan_crashed)(&crash.message));
+                self.retry_scan = true;
+            }
+            "trimmer" => {
+                self.trim_events = None;
+                self.trim_report = None;
+                self.trim_result_state = None;
+                self.rescan_progress_state = None;
+                self.rescan_progress = None;
+                self.show_error((t().trim_crashed)(&crash.message));
+                self.retry_scan = true;
+            }
+            _ => {
+                self.breakdown_state = None;
+                if self.state == AppState::PopupBreakdown {
+                    self.show_error((t().breakdown_crashed)(&crash.message));
+                }
+            }
+        }
+    }
+
+    /// Shows the scanned apps and moves the cursor to where it's most useful.
+    fn finish_scan(&mut self, apps: Vec<AppInfo>) {
+        self.apps = apps;
+        self.view_stale = true;
+        self.sort_apps();
+        // Start with first prunable app selected
+        self.selected_index = app::first_visible(&self.apps, ListFilter::default());
+        // Unless the app from the last session is still visible
+        if let Some((path, key)) = self.restore_selection.take()
+            && let Some(i) = Registry::new(&self.apps).find(&path, key.as_ref())
+            && app::is_visible(&self.apps[i], self.list_filter())
+        {
+            self.selected_index = i;
+        }
+        self.state = AppState::Ready;
+        self.size_history.record(&self.apps, report::unix_time());
+        if !self.trim_options.lipo_available {
+            self.set_status(t().lipo_missing_status.to_string());
+        }
+        if self.cancel.is_cancelled() {
+            self.set_status(t().scan_cancelled.to_string());
+        }
+    }
+
+    /// Draws the current state into `frame`. Only reads the app's own fields, so
+    /// it works the same on a [`ratatui::backend::TestBackend`].
+    fn draw(&mut self, frame: &mut Frame) {
+        self.render(frame);
+        self.display.apply(frame.buffer_mut());
+    }
+
     fn render(&mut self, frame: &mut Frame) {
         let area = frame.area();
 
@@ -213,34 +1041,61 @@ impl App {
                 ])
                 .split(vertical_chunks[1]);
 
-                let content = Layout::vertical([Constraint::Length(3), Constraint::Length(3)])
-                    .split(horizontal_chunks[1]);
+                let content = Layout::vertical([
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ])
+                .split(horizontal_chunks[1]);
+                self.render_cancel_hint(frame, content[2]);
 
-                let progress_ratio = if self.scan_total > 0 {
-                    self.scan_progress as f64 / self.scan_total as f64
-                } else {
-                    0.0
-                };
+                let progress = &self.scan_progress;
+                let progress_ratio = progress.ratio();
 
-                let label = if self.scan_total > 0 {
+                if !progress.current_name.is_empty() {
+                    let skipped = if progress.skipped.is_empty() {
+                        String::new()
+                    } else {
+                        (t().scan_skipped)(progress.skipped.len(), progress.error_count())
+                    };
+                    // Show that a slow app is still being worked on
+                    let elapsed = progress
+                        .current_started
+                        .map(|started| started.elapsed())
+                        .filter(|elapsed| *elapsed >= SLOW_APP_THRESHOLD)
+                        .map(|elapsed| format!(" ({}s)", elapsed.as_secs()))
+                        .unwrap_or_default();
+                    frame.render_widget(
+                        Paragraph::new(format!("{}{}{}", progress.current_name, elapsed, skipped))
+                            .style(Style::default().fg(Color::DarkGray))
+                            .centered(),
+                        content[0],
+                    );
+                }
+
+                let label = if progress.total > 0 {
                     Span::styled(
                         format!(
                             "{}/{} ({:.0}%)",
-                            self.scan_progress,
-                            self.scan_total,
+                            progress.current,
+                            progress.total,
                             progress_ratio * 100.0
                         ),
                         Style::default().add_modifier(Modifier::BOLD),
                     )
                 } else {
                     Span::styled(
-                        "Initializing...",
+                        t().initializing,
                         Style::default().add_modifier(Modifier::ITALIC),
                     )
                 };
 
                 let gauge = Gauge::default()
-                    .block(Block::default().borders(Borders::ALL).title("Scanning"))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(t().scanning_title),
+                    )
                     .gauge_style(Style::default().fg(Color::White).bg(Color::Black))
                     .ratio(progress_ratio)
                     .label(label);
@@ -248,45 +1103,61 @@ impl App {
                 frame.render_widget(gauge, content[1]);
             }
             AppState::Ready => {
-                // Split the screen: header + main list + summary at bottom
-                let chunks = Layout::vertical([
-                    Constraint::Length(3),
-                    Constraint::Min(10),
-                    Constraint::Length(8),
-                ])
-                .split(area);
-
-                self.render_header(frame, chunks[0]);
-                self.render_app_list(frame, chunks[1]);
-                self.render_summary(frame, chunks[2]);
+                self.render_main(frame, area);
             }
             AppState::PopupNoSelection => {
-                let chunks = Layout::vertical([
-                    Constraint::Length(3),
-                    Constraint::Min(10),
-                    Constraint::Length(8),
-                ])
-                .split(area);
-
-                self.render_header(frame, chunks[0]);
-                self.render_app_list(frame, chunks[1]);
-                self.render_summary(frame, chunks[2]);
+                self.render_main(frame, area);
 
                 // Render popup on top
                 self.render_no_selection_popup(frame, area);
             }
+            AppState::PopupConfirmManaged => {
+                self.render_main(frame, area);
+                self.render_confirm_managed_popup(frame, area);
+            }
+            AppState::PopupConfirmTrim | AppState::PopupConfirmTyped => {
+                self.render_main(frame, area);
+                self.render_confirm_trim_popup(frame, area);
+            }
+            AppState::PopupError => {
+                self.render_main(frame, area);
+                self.render_error_popup(frame, area);
+            }
+            AppState::PopupDiagnostics => {
+                self.render_main(frame, area);
+                self.render_diagnostics_popup(frame, area);
+            }
+            AppState::PopupColumns => {
+                self.render_main(frame, area);
+                self.render_columns_popup(frame, area);
+            }
+            AppState::PopupBreakdown => {
+                self.render_main(frame, area);
+                self.render_breakdown_popup(frame, area);
+            }
+            AppState::PopupVendors => {
+                self.render_main(frame, area);
+                self.render_vendors_popup(frame, area);
+            }
+            AppState::PopupLegend => {
+                self.render_main(frame, area);
+                self.render_legend_popup(frame, area);
+            }
+            AppState::PopupDeveloper => {
+                self.render_main(frame, area);
+                self.render_developer_popup(frame, area);
+            }
+            AppState::PopupFilter => {
+                self.render_main(frame, area);
+                self.render_filter_popup(frame, area);
+            }
+            AppState::PopupSavings => {
+                self.render_main(frame, area);
+                self.render_savings_popup(frame, area);
+            }
             AppState::PopupPasswordInput => {
                 // Render the main UI in the background
-                let chunks = Layout::vertical([
-                    Constraint::Length(3),
-                    Constraint::Min(10),
-                    Constraint::Length(8),
-                ])
-                .split(area);
-
-                self.render_header(frame, chunks[0]);
-                self.render_app_list(frame, chunks[1]);
-                self.render_summary(frame, chunks[2]);
+                self.render_main(frame, area);
                 self.render_password_popup(frame, area);
             }
             AppState::Trimming => {
@@ -303,19 +1174,35 @@ impl App {
                 ])
                 .split(vertical_chunks[1]);
 
-                let content = Layout::vertical([Constraint::Length(3), Constraint::Length(3)])
-                    .split(horizontal_chunks[1]);
+                let content = Layout::vertical([
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ])
+                .split(horizontal_chunks[1]);
+                // Re-analyzing the trimmed apps is quick and can't be cancelled
+                if self.rescan_progress.is_none() {
+                    self.render_cancel_hint(frame, content[2]);
+                }
 
-                let progress_ratio = if self.trim_total > 0 {
-                    self.trim_progress as f64 / self.trim_total as f64
+                let (done, total, progress_ratio) = if let Some(progress) = &self.rescan_progress {
+                    (progress.current, progress.total, progress.ratio())
+                } else if self.trim_total > 0 {
+                    (
+                        self.trim_progress,
+                        self.trim_total,
+                        self.trim_progress as f64 / self.trim_total as f64,
+                    )
                 } else {
-                    0.0
+                    (0, 0, 0.0)
                 };
 
-                let title = if self.trim_total > 0 {
-                    format!("Trimming: {}", self.trim_current)
+                let title = if let Some(progress) = &self.rescan_progress {
+                    (t().rescanning)(&progress.current_name)
+                } else if self.trim_total > 0 {
+                    (t().trimming)(&self.trim_current)
                 } else {
-                    format!("Preparing to trim: {}", self.trim_current)
+                    (t().preparing_to_trim)(&self.trim_current)
                 };
 
                 frame.render_widget(
@@ -329,29 +1216,28 @@ impl App {
                     content[0],
                 );
 
-                let label = if self.trim_total > 0 {
-                    Span::styled(
-                        format!(
-                            "{}/{} ({:.0}%)",
-                            self.trim_progress,
-                            self.trim_total,
-                            progress_ratio * 100.0
-                        ),
-                        Style::default().add_modifier(Modifier::BOLD),
-                    )
+                let label = if total > 0 {
+                    let mut label = format!("{}/{} ({:.0}%)", done, total, progress_ratio * 100.0);
+                    if self.trim_freed > 0 && self.rescan_progress.is_none() {
+                        label.push_str(" · ");
+                        label.push_str(&(t().trim_freed)(&ByteSize(self.trim_freed).to_string()));
+                    }
+                    Span::styled(label, Style::default().add_modifier(Modifier::BOLD))
                 } else {
                     Span::styled(
-                        "Preparing...",
+                        t().preparing,
                         Style::default().add_modifier(Modifier::ITALIC),
                     )
                 };
 
                 let gauge = Gauge::default()
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Trimming Applications"),
-                    )
+                    .block(Block::default().borders(Borders::ALL).title(
+                        if self.rescan_progress.is_some() {
+                            t().rescanning_title
+                        } else {
+                            t().trimming_title
+                        },
+                    ))
                     .gauge_style(Style::default().fg(Color::Yellow).bg(Color::Black))
                     .ratio(progress_ratio)
                     .label(label);
@@ -361,195 +1247,939 @@ impl App {
         }
     }
 
-    fn render_header(&self, frame: &mut Frame, area: Rect) {
-        let header_line = Line::from(vec![
-            Span::styled(format!("{:<4}", ""), Style::default()),
-            Span::styled(
-                format!("{:<30}", "Name"),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                format!("{:<20}", "Architectures"),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                "Pruneable Size",
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-        ]);
+    /// Renders the header, app list and summary that sit behind every popup.
+    fn render_main(&mut self, frame: &mut Frame, area: Rect) {
+        self.refresh_view();
+        // Split the screen: header + main list + summary at bottom
+        let chunks = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(if self.summary_collapsed { 1 } else { 9 }),
+            Constraint::Length(1),
+        ])
+        .split(area);
 
-        let sort_indicator = match self.sort_mode {
-            SortMode::Size => "name",
-            SortMode::Alphabetical => "size",
-        };
+        self.render_header(frame, chunks[0]);
+        self.render_app_list(frame, chunks[1]);
+        self.render_summary(frame, chunks[2]);
+        self.render_status_line(frame, chunks[3]);
+    }
 
-        let visibility_indicator = if self.show_non_toggleable {
-            "prunable"
+    /// Shows the transient status message if there is one, otherwise the keys that
+    /// work in the current state.
+    fn render_status_line(&self, frame: &mut Frame, area: Rect) {
+        let line = if let Some((message, _)) = &self.status_message {
+            Line::from(Span::styled(
+                format!(" {}", message),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))
         } else {
-            "all"
+            Line::from(Span::styled(
+                format!(" {}", self.key_hints().join(" | ")),
+                Style::default().fg(Color::DarkGray),
+            ))
         };
 
-        let title = format!(
-            "Usage: (Space: toggle | a: all | Enter: trim | s: sort by {} | h: show {} | ↑/↓: nav | q: quit)",
-            sort_indicator, visibility_indicator
-        );
+        frame.render_widget(Paragraph::new(line), area);
+    }
 
-        let header =
-            Paragraph::new(header_line).block(Block::default().borders(Borders::ALL).title(title));
+    fn key_hints(&self) -> Vec<&'static str> {
+        let strings = t();
+        match self.state {
+            AppState::Ready => {
+                let mut hints = vec![
+                    strings.hint_toggle,
+                    strings.hint_all,
+                    strings.hint_trim,
+                    match self.sort_mode {
+                        SortMode::Size => strings.hint_sort_by_potential,
+                        SortMode::Potential => strings.hint_sort_by_name,
+                        SortMode::Alphabetical => strings.hint_sort_by_size,
+                    },
+                    if self.show_non_toggleable {
+                        strings.hint_show_prunable
+                    } else {
+                        strings.hint_show_all
+                    },
+                    strings.hint_skipped,
+                    strings.hint_summary,
+                    strings.hint_columns,
+                    strings.hint_breakdown,
+                    strings.hint_vendors,
+                    strings.hint_category,
+                    strings.hint_filter,
+                    strings.hint_copy,
+                    strings.hint_strategy,
+                ];
+                if self.trim_options.audit {
+                    hints.retain(|hint| {
+                        *hint != strings.hint_trim && *hint != strings.hint_strategy
+                    });
+                }
+                if !self.selection_history.is_empty() {
+                    hints.insert(2, strings.hint_undo);
+                }
+                if !self.artifacts.is_empty() {
+                    hints.push(strings.hint_developer);
+                }
+                if !self.last_savings.is_empty() {
+                    hints.push(strings.hint_savings);
+                }
+                if !self.error_message.is_empty() {
+                    hints.push(strings.hint_errors);
+                }
+                hints.push(strings.hint_legend);
+                hints.push(strings.hint_nav);
+                if self.apps.iter().any(|app| app.selected) {
+                    hints.push(strings.hint_next_selected);
+                }
+                hints.push(strings.hint_quit);
+                hints
+            }
+            AppState::PopupError if self.retry_scan => {
+                vec![strings.hint_retry, strings.hint_close]
+            }
+            AppState::PopupNoSelection | AppState::PopupError => vec![strings.hint_close],
+            AppState::PopupDiagnostics
+            | AppState::PopupBreakdown
+            | AppState::PopupVendors
+            | AppState::PopupLegend
+            | AppState::PopupDeveloper
+            | AppState::PopupSavings => vec![strings.hint_scroll, strings.hint_esc_close],
+            AppState::PopupColumns => {
+                vec![
+                    strings.hint_toggle,
+                    strings.hint_nav,
+                    strings.hint_esc_close,
+                ]
+            }
+            AppState::PopupConfirmManaged => vec![strings.hint_trim_anyway, strings.hint_cancel],
+            AppState::PopupConfirmTrim => vec![strings.hint_confirm_trim, strings.hint_cancel],
+            AppState::PopupConfirmTyped => vec![strings.hint_confirm, strings.hint_esc_cancel],
+            AppState::PopupFilter => vec![strings.hint_confirm, strings.hint_esc_cancel],
+            AppState::PopupPasswordInput => vec![
+                strings.hint_confirm,
+                strings.hint_clear,
+                strings.hint_terminal_prompt,
+                strings.hint_esc_cancel,
+            ],
+            AppState::Loading | AppState::Trimming => Vec::new(),
+        }
+    }
 
-        frame.render_widget(header, area);
+    /// `Esc: cancel` below a progress gauge, or that the work is being stopped.
+    fn render_cancel_hint(&self, frame: &mut Frame, area: Rect) {
+        let hint = if self.cancel.is_cancelled() {
+            t().cancelling
+        } else {
+            t().hint_esc_cancel
+        };
+        frame.render_widget(
+            Paragraph::new(hint)
+                .style(Style::default().fg(Color::DarkGray))
+                .centered(),
+            area,
+        );
     }
 
-    fn render_app_list(&mut self, frame: &mut Frame, area: Rect) {
-        // Build list of visible indices
-        let visible_indices: Vec<usize> = self
+    /// Shows `message` in the status line for a few seconds.
+    fn set_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    fn render_header(&self, frame: &mut Frame, area: Rect) {
+        let shown = self
             .apps
             .iter()
-            .enumerate()
-            .filter(|(_, app)| self.show_non_toggleable || app.has_x86_64())
-            .map(|(i, _)| i)
-            .collect();
+            .filter(|app| app::is_visible(app, self.list_filter()))
+            .count();
+        let mut header_line = Line::from(vec![
+            Span::styled(
+                (t().apps_shown)(shown, self.apps.len()),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                match self.sort_mode {
+                    SortMode::Size => t().sorted_by_size,
+                    SortMode::Alphabetical => t().sorted_by_name,
+                    SortMode::Potential => t().sorted_by_potential,
+                },
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        if let Some(category) = &self.filter.category {
+            header_line.push_span(Span::styled(
+                format!(" · {}", scanner::category_name(category)),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        // The category is shown by its name above
+        let query = AppFilter {
+            category: None,
+            ..self.filter.clone()
+        }
+        .query();
+        if !query.is_empty() {
+            header_line.push_span(Span::styled(
+                format!(" · /{}", query),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if self.trim_options.audit {
+            header_line.push_span(Span::styled(
+                t().audit_mode_header,
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(version) = &self.available_update {
+            header_line.push_span(Span::styled(
+                (t().update_available)(version),
+                Style::default().fg(Color::Green),
+            ));
+        }
 
-        // Find the position of selected_index in the visible list
-        let visible_position = visible_indices
-            .iter()
-            .position(|&i| i == self.selected_index);
+        let header = Paragraph::new(header_line)
+            .block(Block::default().borders(Borders::ALL).title("bintrim"));
 
-        let items: Vec<ListItem> = visible_indices
-            .iter()
-            .map(|&i| {
-                let app = &self.apps[i];
-                let checkbox = if app.has_x86_64() {
-                    if app.selected { "[x]" } else { "[ ]" }
-                } else {
-                    "[-]"
-                };
+        frame.render_widget(header, area);
+    }
 
-                let arch_display = app.architectures_display();
+    /// Works out the [`View`] again if something changed since the last frame.
+    fn refresh_view(&mut self) {
+        if self.view_stale {
+            self.view = View::of(&self.apps, self.list_filter());
+            self.view_stale = false;
+        }
+    }
 
-                // Show only x86_64 size
-                let size_display = match app.x86_64_size_mb() {
-                    Some(size) if app.has_x86_64() => format!("{:.2} MB", size),
-                    _ => "N/A".to_string(),
-                };
+    fn render_app_list(&mut self, frame: &mut Frame, area: Rect) {
+        let grouped = self.scan_options.roots.len() > 1 || self.scan_options.helpers;
+        // Inside the borders
+        let columns = columns::visible(area.width.saturating_sub(2), &self.hidden_columns);
+        let widths = columns::widths(&columns, area.width.saturating_sub(2));
+        let mut rows: Vec<Row> = Vec::new();
+        // Position of selected_index in the rendered table, including group headers
+        let mut visible_position = None;
+        let mut current_group = None;
+
+        for &i in &self.view.visible {
+            let app = &self.apps[i];
+
+            if grouped && current_group != Some(&app.group) {
+                current_group = Some(&app.group);
+                let label = app.group.as_deref().unwrap_or("/Applications");
+
+                // Report free space and network status for external volumes
+                let volume_note = self
+                    .scan_options
+                    .roots
+                    .iter()
+                    .find(|root| root.group == app.group)
+                    .and_then(|root| root.volume)
+                    .map(|volume| {
+                        format!(
+                            "{}{}",
+                            (t().volume_free)(&ByteSize(volume.free_bytes).to_string()),
+                            if volume.network {
+                                t().network_volume
+                            } else {
+                                ""
+                            }
+                        )
+                    })
+                    .unwrap_or_default();
+
+                rows.push(Row::new(vec![
+                    Cell::from(""),
+                    Cell::from(Span::styled(
+                        columns::truncate(label, widths[1]),
+                        Style::default()
+                            .fg(Color::Magenta)
+                            .add_modifier(Modifier::BOLD),
+                    )),
+                    Cell::from(Span::styled(
+                        columns::truncate(&volume_note, widths.get(2).copied().unwrap_or(0)),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                ]));
+            }
 
-                let line = Line::from(vec![
-                    Span::styled(
-                        format!("{} ", checkbox),
-                        if app.has_x86_64() {
-                            Style::default().fg(Color::White)
-                        } else {
-                            Style::default().fg(Color::DarkGray)
-                        },
-                    ),
-                    Span::styled(
-                        format!("{:<30}", app.name),
-                        Style::default().fg(Color::White),
+            if i == self.selected_index {
+                visible_position = Some(rows.len());
+            }
+
+            // Apps that can't be trimmed show the badge that's in the way, if any
+            let blocking = app.badges.iter().find(|badge| badge.blocks_trim());
+            let (checkbox, checkbox_style) = match (app.is_prunable(), blocking) {
+                (true, _) => (
+                    if app.selected { "[x]" } else { "[ ]" }.to_string(),
+                    Style::default().fg(Color::White),
+                ),
+                (false, Some(badge)) => (
+                    format!("[{}]", badge.symbol()),
+                    Style::default().fg(badge_color(*badge)),
+                ),
+                (false, None) => ("[-]".to_string(), Style::default().fg(Color::DarkGray)),
+            };
+
+            // Show only x86_64 size
+            let size_display = match app.prune_size() {
+                Some(size) if app.is_prunable() => size.display_mb(),
+                _ => t().not_available.to_string(),
+            };
+
+            let cells = columns.iter().zip(&widths).map(|(column, &width)| {
+                let (text, style) = match column {
+                    Column::Checkbox => (checkbox.clone(), checkbox_style),
+                    Column::Name => (app.name.clone(), Style::default().fg(Color::White)),
+                    Column::Badges => {
+                        return Cell::from(Line::from(
+                            app.badges
+                                .iter()
+                                .take(width)
+                                .map(|badge| {
+                                    Span::styled(
+                                        badge.symbol().to_string(),
+                                        Style::default().fg(badge_color(*badge)),
+                                    )
+                                })
+                                .collect::<Vec<_>>(),
+                        ));
+                    }
+                    Column::Architectures => (
+                        app.architectures_display(),
+                        Style::default().fg(Color::Cyan),
                     ),
-                    Span::styled(
-                        format!("{:<20}", arch_display),
+                    Column::Slices => (
+                        app.slices_display(t().not_available),
                         Style::default().fg(Color::Cyan),
                     ),
-                    Span::styled(size_display, Style::default().fg(Color::Yellow)),
-                ]);
-
-                let style = if i == self.selected_index {
-                    Style::default()
-                        .bg(Color::Rgb(40, 40, 40))
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
+                    Column::Size => (size_display.clone(), Style::default().fg(Color::Yellow)),
+                    Column::Notes => (
+                        app.badges
+                            .iter()
+                            .map(|badge| badge.label())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        Style::default().fg(Color::Magenta),
+                    ),
                 };
+                Cell::from(Span::styled(columns::truncate(&text, width), style))
+            });
 
-                ListItem::new(line).style(style)
-            })
-            .collect();
-
-        let list = List::new(items).block(Block::default().borders(Borders::ALL));
+            rows.push(Row::new(cells));
+        }
 
-        self.list_state.select(visible_position);
-        frame.render_stateful_widget(list, area, &mut self.list_state);
-    }
+        let header = Row::new(columns.iter().map(|column| column.title())).style(
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        );
 
-    fn render_summary(&self, frame: &mut Frame, area: Rect) {
-        let total_apps_with_x86 = self.apps.iter().filter(|app| app.has_x86_64()).count();
+        let mut block = Block::default().borders(Borders::ALL);
+        // What the `h` toggle conceals, on the bottom border
+        if let Some(note) = &self.view.hidden_note {
+            block = block.title_bottom(Span::styled(
+                format!(" {} ", note),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
 
-        let total_x86_size: f64 = self
-            .apps
-            .iter()
-            .filter(|app| app.has_x86_64())
-            .filter_map(|app| app.x86_64_size_mb())
-            .sum();
+        let table = Table::new(rows, columns.iter().map(Column::constraint))
+            .header(header)
+            .column_spacing(columns::SPACING)
+            .highlight_symbol(columns::HIGHLIGHT_SYMBOL)
+            .highlight_spacing(HighlightSpacing::Always)
+            .row_highlight_style(if self.display.no_color {
+                // Without a background color the cursor row needs another cue
+                Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default()
+                    .bg(Color::Rgb(40, 40, 40))
+                    .add_modifier(Modifier::BOLD)
+            })
+            .block(block);
 
-        let selected_apps = self
-            .apps
-            .iter()
-            .filter(|app| app.selected && app.has_x86_64())
-            .count();
+        self.table_state.select(visible_position);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
 
-        let estimated_prune_size: f64 = self
-            .apps
-            .iter()
-            .filter(|app| app.selected && app.has_x86_64())
-            .filter_map(|app| app.x86_64_size_mb())
-            .sum();
+    fn render_summary(&self, frame: &mut Frame, area: Rect) {
+        let View {
+            prunable: total_apps_with_x86,
+            prunable_size: total_x86_size,
+            selected: selected_apps,
+            selected_size: estimated_prune_size,
+            filtered,
+            visible_prunable,
+            visible_prunable_size,
+            visible_selected,
+            visible_selected_size,
+            ..
+        } = self.view;
+        // What the filter leaves of each total, next to it
+        let shown = |text: String| {
+            Span::styled(
+                if filtered {
+                    (t().shown)(&text)
+                } else {
+                    String::new()
+                },
+                Style::default().fg(Color::DarkGray),
+            )
+        };
 
-        let prune_size_display = if estimated_prune_size > 0.0 {
-            format!("{:.2} MB", estimated_prune_size)
+        let prune_size_display = if estimated_prune_size > ByteSize(0) {
+            estimated_prune_size.display_mb()
         } else {
             "-".to_string()
         };
 
-        let summary_text = vec![
+        // Keep the key numbers visible in a single line when collapsed
+        if self.summary_collapsed {
+            let status = Line::from(vec![
+                Span::styled(
+                    format!(" {}", t().selected),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!("{}/{}", selected_apps, total_apps_with_x86),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {}", t().prune_size),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    prune_size_display,
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    (t().of_total)(&total_x86_size.display_mb()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                shown(format!(
+                    "{}/{}, {}",
+                    visible_selected,
+                    visible_prunable,
+                    visible_selected_size.display_mb()
+                )),
+            ]);
+            frame.render_widget(Paragraph::new(status), area);
+            return;
+        }
+
+        let mut summary_text = vec![
             Line::from(vec![
-                Span::styled("Prunable Applications: ", Style::default().fg(Color::White)),
+                Span::styled(t().prunable_apps, Style::default().fg(Color::White)),
                 Span::styled(
                     format!("{}", total_apps_with_x86),
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
+                shown(visible_prunable.to_string()),
             ]),
             Line::from(vec![
-                Span::styled("Total pruneable size: ", Style::default().fg(Color::White)),
+                Span::styled(t().total_size, Style::default().fg(Color::White)),
                 Span::styled(
-                    format!("{:.2} MB", total_x86_size),
+                    total_x86_size.display_mb(),
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD),
                 ),
+                shown(visible_prunable_size.display_mb()),
             ]),
-            Line::from(""),
             Line::from(vec![
-                Span::styled("Selected: ", Style::default().fg(Color::White)),
+                Span::styled(t().architectures_label, Style::default().fg(Color::White)),
+                Span::styled(
+                    self.view.arch_summary.clone(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            // Developer files are listed for reference, they can't be trimmed
+            if self.artifacts.is_empty() {
+                Line::from("")
+            } else {
+                Line::from(vec![
+                    Span::styled(t().developer_files, Style::default().fg(Color::White)),
+                    Span::styled(
+                        (t().developer_files_total)(
+                            &ByteSize(self.artifacts.iter().map(|artifact| artifact.bytes).sum())
+                                .to_string(),
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])
+            },
+            Line::from(vec![
+                Span::styled(t().selected, Style::default().fg(Color::White)),
                 Span::styled(
                     format!("{}", selected_apps),
                     Style::default()
                         .fg(Color::Green)
                         .add_modifier(Modifier::BOLD),
                 ),
+                shown(visible_selected.to_string()),
             ]),
             Line::from(vec![
-                Span::styled("Prune size: ", Style::default().fg(Color::White)),
+                Span::styled(t().prune_size, Style::default().fg(Color::White)),
                 Span::styled(
                     prune_size_display,
                     Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
                 ),
+                shown(visible_selected_size.display_mb()),
             ]),
         ];
 
-        let summary = Paragraph::new(summary_text)
-            .block(Block::default().borders(Borders::ALL).title("Summary"));
+        // Explain why the app under the cursor is marked and where linked apps live
+        if let Some(app) = self.apps.get(self.selected_index) {
+            let details: Vec<_> =
+                app.link_path
+                    .as_ref()
+                    .map(|_| (t().links_to)(&app.path))
+                    .into_iter()
+                    .chain(app.potential.map(|potential| {
+                        (t().potential_savings)(
+                            &ByteSize(potential.total()).to_string(),
+                            &ByteSize(potential.slices).to_string(),
+                            &ByteSize(potential.nested).to_string(),
+                            &ByteSize(potential.languages).to_string(),
+                            &ByteSize(potential.compression).to_string(),
+                        )
+                    }))
+                    .chain(self.size_history.summary(&app.path, report::unix_time()))
+                    .chain(app.helper.as_ref().map(|helper| match &helper.parent {
+                        Some(parent) => (t().helper_of)(parent),
+                        None => t().helper_unknown_app.to_string(),
+                    }))
+                    .chain(app.badges.iter().map(|badge| {
+                        format!("{}: {}", badge.label(), (t().badge_description)(*badge))
+                    }))
+                    .chain(
+                        app.risks
+                            .iter()
+                            .map(|risk| (t().risk_description)(*risk).to_string()),
+                    )
+                    .collect();
+            if !details.is_empty() {
+                summary_text.push(Line::from(Span::styled(
+                    details.join(" | "),
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+        }
+
+        let summary = Paragraph::new(summary_text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t().summary_title),
+        );
 
         frame.render_widget(summary, area);
     }
 
+    fn render_diagnostics_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+
+        let text: Vec<Line> = if self.scan_progress.skipped.is_empty() {
+            vec![Line::from(t().no_skipped)]
+        } else {
+            self.scan_progress
+                .skipped
+                .iter()
+                .map(|(name, reason)| {
+                    Line::from(vec![
+                        Span::styled(format!("{:<30}", name), Style::default().fg(Color::White)),
+                        Span::styled(reason.to_string(), Style::default().fg(Color::DarkGray)),
+                    ])
+                })
+                .collect()
+        };
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title((t().skipped_title)(self.scan_progress.skipped.len())),
+            )
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_breakdown_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+        let strings = t();
+        let heading = |text: &str| {
+            Line::from(Span::styled(
+                text.to_string(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        let sized = |bytes: u64, text: String| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:>10}  ", ByteSize(bytes)),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(text, Style::default().fg(Color::DarkGray)),
+            ])
+        };
+
+        let text: Vec<Line> = match &self.breakdown {
+            None => vec![Line::from(strings.breakdown_analyzing)],
+            Some(breakdown) => {
+                let mut text = vec![
+                    Line::from(Span::styled(
+                        (strings.breakdown_total)(&ByteSize(breakdown.total_bytes).to_string()),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )),
+                    Line::from(""),
+                    heading(strings.breakdown_savings),
+                ];
+                for (strategy, savings) in &breakdown.savings {
+                    let label = strategy_label(*strategy);
+                    text.push(match savings {
+                        Some(bytes) => sized(*bytes, label.to_string()),
+                        None => Line::from(format!("  {:>10}  {}", strings.not_available, label)),
+                    });
+                }
+
+                if let Some(plan) = &breakdown.xcode {
+                    text.push(Line::from(""));
+                    text.push(heading(strings.breakdown_xcode));
+                    text.push(sized(
+                        plan.savings(),
+                        (strings.breakdown_xcode_tools)(plan.binaries.len()),
+                    ));
+                    for (path, protection) in xcode::PROTECTED {
+                        text.push(Line::from(vec![
+                            Span::styled(
+                                format!(
+                                    "  {:>10}  Contents/{}: ",
+                                    strings.breakdown_xcode_kept, path
+                                ),
+                                Style::default().fg(Color::White),
+                            ),
+                            Span::styled(
+                                (strings.xcode_protection)(protection),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]));
+                    }
+                }
+
+                text.push(Line::from(""));
+                text.push(heading(strings.breakdown_dirs));
+                for (path, bytes) in &breakdown.largest_dirs {
+                    text.push(sized(*bytes, path.display().to_string()));
+                }
+
+                text.push(Line::from(""));
+                text.push(heading(strings.breakdown_files));
+                for (path, bytes) in &breakdown.largest_files {
+                    text.push(sized(*bytes, path.display().to_string()));
+                }
+
+                text.push(Line::from(""));
+                text.push(heading(strings.breakdown_fat));
+                if breakdown.fat_binaries.is_empty() {
+                    text.push(Line::from(format!("  {}", strings.breakdown_none)));
+                }
+                for (path, slices) in &breakdown.fat_binaries {
+                    text.push(Line::from(format!(
+                        "  {} [{}]",
+                        path.display(),
+                        slices
+                            .iter()
+                            .map(Arch::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )));
+                }
+
+                text.push(Line::from(""));
+                text.push(heading(&(strings.breakdown_localizations)(
+                    breakdown.localizations.len(),
+                )));
+                text.push(Line::from(format!(
+                    "  {}",
+                    if breakdown.localizations.is_empty() {
+                        strings.breakdown_none.to_string()
+                    } else {
+                        breakdown.localizations.join(", ")
+                    }
+                )));
+                text
+            }
+        };
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title((strings.breakdown_title)(&self.breakdown_app)),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_vendors_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+        let vendors = vendor::summarize(&self.apps);
+
+        let text: Vec<Line> = if vendors.is_empty() {
+            vec![Line::from(t().no_prunable_vendors)]
+        } else {
+            vendors
+                .iter()
+                .map(|summary| {
+                    Line::from(Span::styled(
+                        (t().vendor_line)(
+                            summary.name.as_deref().unwrap_or(t().vendor_unknown),
+                            &ByteSize(summary.prune_bytes).to_string(),
+                            summary.apps,
+                        ),
+                        Style::default().fg(Color::White),
+                    ))
+                })
+                .collect()
+        };
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().vendors_title),
+            )
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_legend_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+        // The longest label and a space before the description
+        let label_width = Badge::ALL
+            .iter()
+            .map(|badge| badge.label().len())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let text: Vec<Line> = Badge::ALL
+            .iter()
+            .map(|badge| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {} ", badge.symbol()),
+                        Style::default()
+                            .fg(badge_color(*badge))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{:<label_width$}", badge.label()),
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(
+                        (t().badge_description)(*badge),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])
+            })
+            .collect();
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().legend_title),
+            )
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_developer_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+        let strings = t();
+
+        let mut text = Vec::new();
+        for artifact in &self.artifacts {
+            text.push(Line::from(vec![
+                Span::styled(
+                    format!("{:>10}  ", ByteSize(artifact.bytes)),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    (strings.artifact_kind)(artifact.kind),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            text.push(Line::from(Span::styled(
+                format!("            {}", artifact.path.display()),
+                Style::default().fg(Color::DarkGray),
+            )));
+            text.push(Line::from(Span::styled(
+                format!("            {}", (strings.artifact_removal)(artifact.kind)),
+                Style::default().fg(Color::Cyan),
+            )));
+        }
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(strings.developer_title),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_savings_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(80, 70, area);
+        let strings = t();
+
+        let mut text = vec![Line::from(Span::styled(
+            strings.savings_header,
+            Style::default().fg(Color::DarkGray),
+        ))];
+        for savings in &self.last_savings {
+            let difference = savings.actual as i64 - savings.estimated as i64;
+            let line = format!(
+                "{:>10}  {:>10}  {:>10}  {}",
+                ByteSize(savings.estimated),
+                ByteSize(savings.actual),
+                format!(
+                    "{}{}",
+                    if difference < 0 { "-" } else { "+" },
+                    ByteSize(difference.unsigned_abs())
+                ),
+                savings.app,
+            );
+            if savings.is_off() {
+                text.push(Line::from(vec![
+                    Span::styled(line, Style::default().fg(Color::Yellow)),
+                    Span::styled(
+                        format!("  {}", strings.savings_off),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ]));
+            } else {
+                text.push(Line::from(Span::styled(
+                    line,
+                    Style::default().fg(Color::White),
+                )));
+            }
+        }
+        if self.last_savings.iter().any(Savings::is_off) {
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(
+                strings.savings_off_hint,
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(strings.savings_title),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((self.diagnostics_scroll, 0));
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_columns_popup(&self, frame: &mut Frame, area: Rect) {
+        let text: Vec<Line> = columns::OPTIONAL
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let checkbox = if self.hidden_columns.contains(column) {
+                    "[ ]"
+                } else {
+                    "[x]"
+                };
+                let style = if i == self.column_cursor {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Line::from(Span::styled(
+                    format!(" {} {}", checkbox, column.title()),
+                    style,
+                ))
+            })
+            .collect();
+
+        let popup_area = Self::fit_height(Self::centered_rect(40, 20, area), text.len(), area);
+        let popup = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t().columns_title),
+        );
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_error_popup(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Self::centered_rect(60, 40, area);
+
+        let mut text = vec![Line::from(""), Line::from("")];
+        text.extend(self.error_message.lines().map(|line| {
+            Line::from(Span::styled(
+                line.to_string(),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ))
+        }));
+        text.extend([
+            Line::from(""),
+            Line::from(Span::styled(
+                t().press_to_continue,
+                Style::default().fg(Color::DarkGray),
+            )),
+        ]);
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().error_title),
+            )
+            .wrap(Wrap { trim: true })
+            .centered();
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
     fn render_no_selection_popup(&self, frame: &mut Frame, area: Rect) {
         let popup_area = Self::centered_rect(50, 30, area);
 
@@ -558,22 +2188,169 @@ impl App {
             Line::from(""),
             Line::from(""),
             Line::from(Span::styled(
-                "No applications selected",
+                t().no_selection,
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             )),
             Line::from(""),
-            Line::from("Please select at least one application to trim."),
+            Line::from(t().select_one),
             Line::from(""),
             Line::from(Span::styled(
-                "Press Enter or Esc to continue",
+                t().press_to_continue,
                 Style::default().fg(Color::DarkGray),
             )),
         ];
 
         let popup = Paragraph::new(text)
-            .block(Block::default().borders(Borders::ALL).title("Warning"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().warning_title),
+            )
+            .centered();
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_confirm_managed_popup(&self, frame: &mut Frame, area: Rect) {
+        let managed_names: Vec<&str> = self
+            .apps
+            .iter()
+            .filter(|app| {
+                app::will_trim(app, self.list_filter()) && app.badges.contains(&Badge::Managed)
+            })
+            .map(|app| app.name.as_str())
+            .collect();
+
+        let popup_area = Self::centered_rect(60, 40, area);
+
+        let text = vec![
+            Line::from(""),
+            Line::from(""),
+            Line::from(Span::styled(
+                (t().managed_selected)(managed_names.len()),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(managed_names.join(", ")),
+            Line::from(""),
+            Line::from(t().managed_warning[0]),
+            Line::from(t().managed_warning[1]),
+            Line::from(""),
+            Line::from(Span::styled(
+                t().managed_prompt,
+                Style::default().fg(Color::DarkGray),
+            )),
+        ];
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().managed_title),
+            )
+            .wrap(Wrap { trim: true })
+            .centered();
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    /// The selected apps that trimming would go through, see [`app::will_trim`].
+    fn selected_prunable(&self) -> impl Iterator<Item = &AppInfo> {
+        let filter = self.list_filter();
+        self.apps
+            .iter()
+            .filter(move |app| app::will_trim(app, filter))
+    }
+
+    /// Asks whether to trim the selected apps, with a field to type the answer
+    /// into at the strict level.
+    fn render_confirm_trim_popup(&self, frame: &mut Frame, area: Rect) {
+        let count = self.selected_prunable().count();
+        let size: ByteSize = self
+            .selected_prunable()
+            .filter_map(|app| app.prune_size())
+            .sum();
+
+        let mut text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                (t().confirm_question)(count, &size.to_string()),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        if self.state == AppState::PopupConfirmTyped {
+            text.push(Line::from(Span::styled(
+                format!("{}_", self.confirm_input),
+                Style::default().fg(Color::White),
+            )));
+            text.push(if self.confirm_rejected {
+                Line::from(Span::styled(
+                    t().confirm_rejected,
+                    Style::default().fg(Color::Red),
+                ))
+            } else {
+                Line::from("")
+            });
+            text.push(Line::from(Span::styled(
+                (t().confirm_typed_prompt)(count),
+                Style::default().fg(Color::DarkGray),
+            )));
+        } else {
+            text.push(Line::from(Span::styled(
+                t().confirm_prompt,
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().confirm_title),
+            )
+            .wrap(Wrap { trim: true })
+            .centered();
+
+        let popup_area = Self::centered_rect(60, 30, area);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(popup, popup_area);
+    }
+
+    fn render_filter_popup(&self, frame: &mut Frame, area: Rect) {
+        let text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("{}_", self.filter_input),
+                Style::default().fg(Color::White),
+            )),
+            Line::from(""),
+            match &self.filter_error {
+                Some(error) => Line::from(Span::styled(
+                    error.as_str(),
+                    Style::default().fg(Color::Red),
+                )),
+                None => Line::from(Span::styled(
+                    t().filter_syntax,
+                    Style::default().fg(Color::DarkGray),
+                )),
+            },
+        ];
+
+        let popup_area = Self::fit_height(Self::centered_rect(70, 30, area), text.len(), area);
+        let popup = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t().filter_title),
+            )
+            .wrap(Wrap { trim: false })
             .centered();
 
         frame.render_widget(Clear, popup_area);
@@ -581,28 +2358,24 @@ impl App {
     }
 
     fn render_password_popup(&self, frame: &mut Frame, area: Rect) {
-        let selected_count = self
-            .apps
-            .iter()
-            .filter(|app| app.selected && app.has_x86_64())
-            .count();
+        let selected_count = self.selected_prunable().count();
 
-        let popup_area = Self::centered_rect(60, 40, area);
-        let password_display = "*".repeat(self.password_input.len());
+        let password_length = self.password_input.chars().count();
+        let password_display = "*".repeat(password_length);
 
         let text = vec![
             Line::from(""),
             Line::from(""),
             Line::from(""),
             Line::from(Span::styled(
-                format!("{} Application(s) will be trimmed", selected_count),
+                (t().will_be_trimmed)(selected_count),
                 Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
             )),
             Line::from(""),
-            Line::from("This operation requires sudo privileges"),
+            Line::from(t().requires_sudo),
             Line::from(""),
             Line::from(Span::styled(
-                "Enter your password",
+                t().enter_password,
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
@@ -612,24 +2385,54 @@ impl App {
                 password_display,
                 Style::default().fg(Color::White),
             )),
-            Line::from(""),
+            if self.caps_lock {
+                Line::from(Span::styled(
+                    t().caps_lock_on,
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from("")
+            },
             Line::from(Span::styled(
-                "Press Enter to confirm, Esc to cancel",
+                t().password_keys,
+                Style::default().fg(Color::DarkGray),
+            )),
+            Line::from(Span::styled(
+                t().password_terminal_hint,
                 Style::default().fg(Color::DarkGray),
             )),
-            Line::from(""),
         ];
 
+        let popup_area = Self::fit_height(Self::centered_rect(60, 40, area), text.len(), area);
         let popup = Paragraph::new(text)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Sudo Authentication"),
+                    .title(t().password_title),
             )
             .centered();
 
         frame.render_widget(Clear, popup_area);
         frame.render_widget(popup, popup_area);
+
+        // Place the cursor after the centered asterisks, which are on the tenth line
+        let inner_width = popup_area.width.saturating_sub(2);
+        let length = (password_length as u16).min(inner_width);
+        let x = popup_area.x + 1 + (inner_width - length) / 2 + length;
+        let y = popup_area.y + 1 + 9;
+        if y < popup_area.bottom().saturating_sub(1) {
+            frame.set_cursor_position((x.min(popup_area.right().saturating_sub(2)), y));
+        }
+    }
+
+    /// Grows `popup` so `lines` of text fit inside its borders, keeping it centered in `area`.
+    fn fit_height(popup: Rect, lines: usize, area: Rect) -> Rect {
+        let height = (lines as u16 + 2).max(popup.height).min(area.height);
+        Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..popup
+        }
     }
 
     fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -651,6 +2454,11 @@ impl App {
     fn handle_crossterm_events(&mut self) -> color_eyre::Result<()> {
         match event::read()? {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
+            Event::Paste(text) if matches!(self.state, AppState::PopupPasswordInput) => {
+                // Password managers may append a newline, which would otherwise end up in the password
+                self.password_input
+                    .extend(text.chars().filter(|c| !matches!(c, '\r' | '\n')));
+            }
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
             _ => {}
@@ -660,236 +2468,477 @@ impl App {
 
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
-        match self.state {
-            AppState::Ready => match (key.modifiers, key.code) {
-                (_, KeyCode::Esc | KeyCode::Char('q'))
-                | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
-                (_, KeyCode::Down | KeyCode::Char('j')) => self.move_down(),
-                (_, KeyCode::Up | KeyCode::Char('k')) => self.move_up(),
-                (_, KeyCode::Char(' ')) => self.toggle_selected(),
-                (_, KeyCode::Char('a')) => self.toggle_select_all(),
-                (_, KeyCode::Char('h')) => self.toggle_visibility(),
-                (_, KeyCode::Char('s')) => self.toggle_sort(),
-                (_, KeyCode::Enter) => self.start_trim(),
-                _ => {}
-            },
-            AppState::PopupNoSelection => match key.code {
-                KeyCode::Enter | KeyCode::Esc => {
-                    self.state = AppState::Ready;
+        let Some(action) = app::handle_key(self.state, &key) else {
+            return;
+        };
+        // Moving the cursor is the most frequent action and changes nothing the
+        // view holds, everything else may
+        if !matches!(action, Action::MoveDown | Action::MoveUp) {
+            self.view_stale = true;
+        }
+
+        match action {
+            Action::Quit => self.quit(),
+            Action::MoveDown => {
+                self.selected_index =
+                    app::step(&self.apps, self.selected_index, self.list_filter(), true);
+            }
+            Action::MoveUp => {
+                self.selected_index =
+                    app::step(&self.apps, self.selected_index, self.list_filter(), false);
+            }
+            Action::NextSelected | Action::PreviousSelected => {
+                let forward = action == Action::NextSelected;
+                match app::step_selected(
+                    &self.apps,
+                    self.selected_index,
+                    self.list_filter(),
+                    forward,
+                ) {
+                    Some(index) => self.selected_index = index,
+                    None => self.set_status(t().no_selection.to_string()),
                 }
-                _ => {}
-            },
-            AppState::PopupPasswordInput => match key.code {
-                KeyCode::Char(c) => {
-                    self.password_input.push(c);
+            }
+            Action::ToggleSelected => self.toggle_selected(),
+            Action::ToggleAll => {
+                self.selection_history.record(&self.apps);
+                // list_filter() would borrow all of self next to the apps
+                let filter = ListFilter {
+                    show_all: self.show_non_toggleable,
+                    filter: Some(&self.filter),
+                };
+                app::toggle_all(&mut self.apps, filter);
+            }
+            Action::Undo => {
+                if !self.selection_history.undo(&mut self.apps) {
+                    self.set_status(t().nothing_to_undo.to_string());
+                }
+            }
+            Action::ToggleVisibility => self.toggle_visibility(),
+            Action::ToggleSort => self.toggle_sort(),
+            Action::ToggleSummary => self.summary_collapsed = !self.summary_collapsed,
+            // Audit mode leaves everything as it is
+            action if self.trim_options.audit && action.modifies() => {}
+            Action::StartTrim if !self.trim_options.lipo_available => {
+                self.show_error(t().lipo_missing.to_string());
+            }
+            Action::StartTrim => {
+                self.confirm_input.clear();
+                self.confirm_rejected = false;
+                match app::trim_popup(
+                    &self.apps,
+                    self.list_filter(),
+                    self.trim_options.confirmation,
+                ) {
+                    Some(popup) => self.state = popup,
+                    None => self.request_password(),
                 }
-                KeyCode::Backspace => {
-                    self.password_input.pop();
+            }
+            Action::ShowDiagnostics => {
+                self.diagnostics_scroll = 0;
+                self.state = AppState::PopupDiagnostics;
+            }
+            Action::ShowErrors => {
+                if !self.error_message.is_empty() {
+                    self.state = AppState::PopupError;
                 }
-                KeyCode::Enter => {
-                    if !self.password_input.is_empty() {
-                        self.execute_trim();
-                    }
+            }
+            Action::ShowBreakdown => self.show_breakdown(),
+            Action::ShowSavings => {
+                if !self.last_savings.is_empty() {
+                    self.diagnostics_scroll = 0;
+                    self.state = AppState::PopupSavings;
                 }
-                KeyCode::Esc => {
-                    self.password_input.clear();
+            }
+            Action::CopyList => self.copy_list(),
+            Action::CycleStrategy => self.cycle_strategy(),
+            Action::CycleCategory => self.cycle_category(),
+            Action::EditFilter => {
+                self.filter_input = self.filter.query();
+                self.filter_error = None;
+                self.state = AppState::PopupFilter;
+            }
+            Action::TypeFilter(c) => {
+                self.filter_input.push(c);
+                self.filter_error = None;
+            }
+            Action::DeleteFilterChar => {
+                self.filter_input.pop();
+                self.filter_error = None;
+            }
+            Action::ApplyFilter => match AppFilter::parse(&self.filter_input) {
+                Ok(filter) => {
+                    self.filter = filter;
+                    self.selected_index = app::first_visible(&self.apps, self.list_filter());
                     self.state = AppState::Ready;
                 }
-                _ => {}
+                Err(err) => self.filter_error = Some(err.to_string()),
             },
-            _ => {}
-        }
-    }
-
-    fn move_down(&mut self) {
-        if self.apps.is_empty() {
-            return;
-        }
-
-        let start_index = self.selected_index;
-        let mut found_next = false;
-
-        // Try to find the next visible item
-        for offset in 1..self.apps.len() {
-            let next_index = (self.selected_index + offset) % self.apps.len();
-            if self.show_non_toggleable || self.apps[next_index].has_x86_64() {
-                self.selected_index = next_index;
-                found_next = true;
-                break;
+            Action::ShowVendors => {
+                self.diagnostics_scroll = 0;
+                self.state = AppState::PopupVendors;
             }
-        }
-
-        // If we didn't find anything (shouldn't happen), stay at current position
-        if !found_next {
-            self.selected_index = start_index;
-        }
-    }
-
-    fn move_up(&mut self) {
-        if self.apps.is_empty() {
-            return;
-        }
-
-        let start_index = self.selected_index;
-        let mut found_prev = false;
-
-        // Try to find the previous visible item (wrapping around)
-        for offset in 1..self.apps.len() {
-            let prev_index = (self.selected_index + self.apps.len() - offset) % self.apps.len();
-            if self.show_non_toggleable || self.apps[prev_index].has_x86_64() {
-                self.selected_index = prev_index;
-                found_prev = true;
-                break;
+            Action::ShowLegend => {
+                self.diagnostics_scroll = 0;
+                self.state = AppState::PopupLegend;
+            }
+            Action::ShowDeveloper => {
+                if !self.artifacts.is_empty() {
+                    self.diagnostics_scroll = 0;
+                    self.state = AppState::PopupDeveloper;
+                }
+            }
+            Action::ShowColumns => {
+                self.column_cursor = 0;
+                self.state = AppState::PopupColumns;
+            }
+            Action::ColumnDown => {
+                self.column_cursor = (self.column_cursor + 1) % columns::OPTIONAL.len();
+            }
+            Action::ColumnUp => {
+                self.column_cursor =
+                    (self.column_cursor + columns::OPTIONAL.len() - 1) % columns::OPTIONAL.len();
+            }
+            Action::ToggleColumn => {
+                let column = columns::OPTIONAL[self.column_cursor];
+                if let Some(position) = self.hidden_columns.iter().position(|&c| c == column) {
+                    self.hidden_columns.remove(position);
+                } else {
+                    self.hidden_columns.push(column);
+                }
+            }
+            Action::Close => {
+                self.retry_scan = false;
+                self.state = AppState::Ready;
+            }
+            Action::Cancel => self.cancel.cancel(),
+            Action::Retry => {
+                if self.retry_scan {
+                    self.retry_scan();
+                }
+            }
+            Action::ScrollDown => {
+                self.diagnostics_scroll = self.diagnostics_scroll.saturating_add(1);
+            }
+            Action::ScrollUp => {
+                self.diagnostics_scroll = self.diagnostics_scroll.saturating_sub(1);
+            }
+            // The warning was the confirmation, unless it has to be typed
+            Action::ConfirmManaged => match self.trim_options.confirmation {
+                Confirmation::Strict => self.state = AppState::PopupConfirmTyped,
+                Confirmation::None | Confirmation::Normal => self.request_password(),
+            },
+            Action::ConfirmTrim => {
+                if self.state == AppState::PopupConfirmTyped
+                    && !confirm::accepts(&self.confirm_input, self.selected_prunable().count())
+                {
+                    self.confirm_input.clear();
+                    self.confirm_rejected = true;
+                } else {
+                    self.request_password();
+                }
+            }
+            Action::TypeConfirm(c) => {
+                self.confirm_input.push(c);
+                self.confirm_rejected = false;
+            }
+            Action::DeleteConfirmChar => {
+                self.confirm_input.pop();
+            }
+            Action::TypePassword(c) => {
+                self.caps_lock = keyboard::caps_lock_on(Some(&key));
+                self.password_input.push(c);
+            }
+            Action::DeletePasswordChar => {
+                self.password_input.pop();
+            }
+            Action::ClearPassword => self.password_input.clear(),
+            Action::TerminalPrompt => {
+                self.password_input.clear();
+                self.sudo_prompt_pending = true;
+            }
+            Action::SubmitPassword => {
+                if !self.password_input.is_empty() {
+                    self.execute_trim();
+                }
+            }
+            Action::CancelPassword => {
+                self.password_input.clear();
+                self.state = AppState::Ready;
             }
-        }
-
-        // If we didn't find anything (shouldn't happen), stay at current position
-        if !found_prev {
-            self.selected_index = start_index;
         }
     }
 
     fn toggle_selected(&mut self) {
-        if let Some(app) = self.apps.get_mut(self.selected_index)
-            && app.has_x86_64()
+        if self
+            .apps
+            .get(self.selected_index)
+            .is_some_and(AppInfo::is_prunable)
         {
+            self.selection_history.record(&self.apps);
+            let app = &mut self.apps[self.selected_index];
             app.selected = !app.selected;
         }
     }
 
-    fn toggle_select_all(&mut self) {
-        let all_selected = self
-            .apps
+    /// The failed apps with their errors, those that may work when tried again
+    /// later listed separately.
+    fn trim_failures_message(&self) -> String {
+        let (transient, permanent): (Vec<_>, Vec<_>) = self
+            .trim_failures
             .iter()
-            .filter(|app| app.has_x86_64())
-            .all(|app| app.selected);
-        let new_state = !all_selected;
-
-        for app in &mut self.apps {
-            if app.has_x86_64() {
-                app.selected = new_state;
-            }
-        }
+            .partition(|failure| failure.error.is_transient());
+        [
+            (permanent, t().trim_failed_details),
+            (transient, t().trim_failed_transient),
+        ]
+        .into_iter()
+        .filter(|(failures, _)| !failures.is_empty())
+        .map(|(failures, header)| {
+            let lines: Vec<_> = failures
+                .iter()
+                .map(|failure| format!("{}: {}", failure.app, failure.error))
+                .collect();
+            format!("{}\n\n{}", header(failures.len()), lines.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
     }
 
-    fn start_trim(&mut self) {
-        let selected_count = self
-            .apps
-            .iter()
-            .filter(|app| app.selected && app.has_x86_64())
-            .count();
-
-        if selected_count == 0 {
-            self.state = AppState::PopupNoSelection;
+    fn request_password(&mut self) {
+        if self.trim_options.elevated || self.trim_options.helper {
+            // Already root or trimming through the helper, no password needed
+            self.password_input.clear();
+            self.execute_trim();
+        } else if self.sudo_prompt {
+            self.password_input.clear();
+            self.sudo_prompt_pending = true;
         } else {
             self.password_input.clear();
+            self.caps_lock = keyboard::caps_lock_on(None);
             self.state = AppState::PopupPasswordInput;
         }
     }
 
+    /// Suspends the TUI and lets `sudo` ask for the password in the terminal. Trimming
+    /// then relies on the credentials sudo caches.
+    fn prompt_sudo_in_terminal(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+    ) -> color_eyre::Result<()> {
+        let selected_count = self.selected_prunable().count();
+
+        let status = terminal::suspend(terminal, || {
+            println!("{}", (t().sudo_needed)(selected_count));
+            Command::new("sudo")
+                .arg("-v")
+                .arg("-p")
+                .arg(t().sudo_prompt)
+                .status()
+        })?;
+
+        match status {
+            Ok(status) if status.success() => self.execute_trim(),
+            Ok(_) => {
+                self.state = AppState::Ready;
+                self.set_status(t().sudo_failed.to_string());
+            }
+            Err(err) => self.show_error((t().sudo_run_failed)(&err.to_string())),
+        }
+        Ok(())
+    }
+
     fn execute_trim(&mut self) {
-        let apps_to_trim: Vec<_> = self
-            .apps
-            .iter()
-            .filter(|app| app.selected && app.has_x86_64())
-            .cloned()
-            .collect();
+        let apps_to_trim: Vec<_> = self.selected_prunable().cloned().collect();
 
         let password = self.password_input.clone();
         self.password_input.clear();
         self.state = AppState::Trimming;
 
-        let progress = Arc::new(Mutex::new((0usize, apps_to_trim.len(), String::new())));
+        let (events, receiver) = mpsc::channel();
         let apps_result = Arc::new(Mutex::new(None));
-
-        // Save references to Arc
-        self.trim_progress_state = Some(Arc::clone(&progress));
+        let rescan_progress = Arc::new(Mutex::new(None));
+
+        self.trim_progress = 0;
+        self.trim_total = apps_to_trim.len();
+        self.trim_current.clear();
+        self.trim_freed = 0;
+        self.trim_events = Some(receiver);
+        self.trim_report = None;
         self.trim_result_state = Some(Arc::clone(&apps_result));
+        self.rescan_progress_state = Some(Arc::clone(&rescan_progress));
+        self.rescan_progress = None;
 
-        let progress_clone = Arc::clone(&progress);
         let apps_clone = Arc::clone(&apps_result);
-        thread::spawn(move || {
-            // Trim each selected app
-            for (index, app) in apps_to_trim.iter().enumerate() {
-                if let Ok(mut p) = progress_clone.lock() {
-                    *p = (index + 1, apps_to_trim.len(), app.name.clone());
-                }
-
-                // Remove x86_64 architecture in-place (requires sudo)
-                let binary_path_str = app.binary_path.to_string_lossy();
-
-                // Get current uid and gid for restoring ownership
-                let uid = unsafe { libc::getuid() };
-                let gid = unsafe { libc::getgid() };
-
-                let lipo_cmd = Command::new("sudo")
-                    .arg("-S") // Read password from stdin
-                    .arg("lipo")
-                    .arg(&*binary_path_str)
-                    .arg("-remove")
-                    .arg("x86_64")
-                    .arg("-output")
-                    .arg(&*binary_path_str)
-                    .stdin(Stdio::piped())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .spawn();
-
-                if let Ok(mut child) = lipo_cmd {
-                    // Write password to stdin and flush
-                    if let Some(mut stdin) = child.stdin.take() {
-                        let _ = writeln!(stdin, "{}", password);
-                        let _ = stdin.flush();
-                        drop(stdin);
-                    }
+        let rescan_clone = Arc::clone(&rescan_progress);
+        let scan_options = self.scan_options.clone();
+        let trim_options = self.trim_options.clone();
+        let priority = self.priority;
+        self.cancel = CancelToken::default();
+        let cancel = self.cancel.clone();
+        let spawned = self.workers.spawn("trimmer", move || {
+            priority.apply_to_current_thread();
+            trimmer::trim_batch(&apps_to_trim, &password, &trim_options, &cancel, &events);
+
+            // Only the trimmed apps changed, so there's no need to scan everything again
+            debug!("re-analyzing trimmed apps");
+            let results = scanner::reanalyze(&apps_to_trim, &scan_options, |event| {
+                if let Ok(mut p) = rescan_clone.lock() {
+                    p.get_or_insert_with(ScanProgress::default).apply(event);
+                }
+            });
 
-                    if let Ok(status) = child.wait()
-                        && status.success()
-                    {
-                        // Restore ownership to current user (sudo credentials should be cached)
-                        let chown_cmd = Command::new("sudo")
-                            .arg("-n") // Non-interactive, use cached credentials
-                            .arg("chown")
-                            .arg(format!("{}:{}", uid, gid))
-                            .arg(&*binary_path_str)
-                            .output();
-
-                        let _ = chown_cmd;
+            if let Ok(mut result) = apps_clone.lock() {
+                *result = Some(results);
+            }
+        });
+
+        match spawned {
+            Ok(()) => {}
+            Err(err) => {
+                self.trim_events = None;
+                self.trim_result_state = None;
+                self.rescan_progress_state = None;
+                self.show_error((t().trim_start_failed)(&err.to_string()));
+            }
+        }
+    }
+
+    /// Applies the events the trim worker sent since the last tick.
+    fn poll_trim_events(&mut self) {
+        let Some(events) = &self.trim_events else {
+            return;
+        };
+        for event in events.try_iter() {
+            match event {
+                TrimEvent::BatchStarted { total } => self.trim_total = total,
+                TrimEvent::AppStarted { index, name } => {
+                    self.trim_progress = index + 1;
+                    self.trim_current = name;
+                }
+                TrimEvent::BinaryTrimmed { app, path, bytes } => {
+                    debug!(%app, path = %path.display(), bytes, "binary shrank");
+                    self.trim_freed += bytes;
+                }
+                TrimEvent::AppFailed { app, error } => error!(%app, %error, "failed to trim"),
+                TrimEvent::BatchFinished { report } => self.trim_report = Some(report),
+            }
+        }
+    }
+
+    /// Replaces the trimmed apps with their re-analyzed info, keeping the order,
+    /// selections and scroll position of the list.
+    fn apply_reanalyzed(&mut self, results: Vec<(PathBuf, Result<AppInfo, SkipReason>)>) {
+        self.view_stale = true;
+        for (path, result) in results {
+            // An app may have been moved while it was trimmed
+            let key = result.as_ref().ok().map(AppKey::of);
+            let Some(index) = Registry::new(&self.apps).find(&path, key.as_ref()) else {
+                continue;
+            };
+            match result {
+                Ok(mut app_info) => {
+                    // Apps that failed to trim stay selected for another try
+                    app_info.selected = self.apps[index].selected && app_info.is_prunable();
+                    self.apps[index] = app_info;
+                }
+                Err(reason) => {
+                    let app = self.apps.remove(index);
+                    if self.selected_index > index {
+                        self.selected_index -= 1;
                     }
+                    self.scan_progress.skipped.push((app.name, reason));
                 }
             }
+        }
 
-            // Rescan
-            let new_apps = scan_applications_with_progress(|_, _, _| {});
+        // The trimmed app under the cursor is hidden now unless all apps are shown
+        let filter = self.list_filter();
+        if !self
+            .apps
+            .get(self.selected_index)
+            .is_some_and(|app| app::is_visible(app, filter))
+        {
+            self.selected_index = if self.selected_index < self.apps.len() {
+                app::step(&self.apps, self.selected_index, filter, true)
+            } else {
+                app::first_visible(&self.apps, filter)
+            };
+        }
+    }
 
-            if let Ok(mut result) = apps_clone.lock() {
-                *result = Some(new_apps);
-            }
-        });
+    fn show_error(&mut self, message: String) {
+        error!("{}", message);
+        self.error_message = message;
+        self.retry_scan = false;
+        self.state = AppState::PopupError;
     }
 
     fn quit(&mut self) {
         self.running = false;
     }
 
+    /// Which apps the list currently shows.
+    fn list_filter(&self) -> ListFilter<'_> {
+        ListFilter {
+            show_all: self.show_non_toggleable,
+            filter: Some(&self.filter),
+        }
+    }
+
+    /// Copies the selected apps as CSV, or the visible ones if none are selected.
+    fn copy_list(&mut self) {
+        let filter = self.list_filter();
+        let selected: Vec<_> = self.apps.iter().filter(|app| app.selected).collect();
+        let apps = if selected.is_empty() {
+            self.apps
+                .iter()
+                .filter(|app| app::is_visible(app, filter))
+                .collect()
+        } else {
+            selected
+        };
+        match clipboard::copy(&clipboard::csv(&apps)) {
+            Ok(()) => self.set_status((t().copied)(apps.len())),
+            Err(err) => self.show_error((t().copy_failed)(&err.to_string())),
+        }
+    }
+
+    /// Switches the app under the cursor to the next strategy for the rest of the
+    /// session and analyzes it again for the new savings.
+    fn cycle_strategy(&mut self) {
+        let Some(app) = self.apps.get(self.selected_index) else {
+            return;
+        };
+        if !app.has_x86_64() || app.badges.iter().any(Badge::blocks_trim) {
+            self.set_status((t().strategy_unchangeable)(&app.name));
+            return;
+        }
+        let strategy = app::next_strategy(app.strategy);
+        // Helpers without a bundle identifier are configured by name
+        let key = app.bundle_id.clone().unwrap_or_else(|| app.name.clone());
+        self.scan_options.strategies.insert(key, strategy);
+        let results = scanner::reanalyze(std::slice::from_ref(app), &self.scan_options, |_| {});
+        let name = app.name.clone();
+        self.apply_reanalyzed(results);
+        self.set_status((t().strategy_changed)(&name, strategy_label(strategy)));
+    }
+
+    fn cycle_category(&mut self) {
+        self.filter.category = app::next_category(&self.apps, self.filter.category.as_deref());
+        self.selected_index = app::first_visible(&self.apps, self.list_filter());
+    }
+
     fn toggle_visibility(&mut self) {
         self.show_non_toggleable = !self.show_non_toggleable;
         // Reset to first visible item
-        self.selected_index = 0;
-        if !self.show_non_toggleable {
-            // Find first prunable app
-            for (i, app) in self.apps.iter().enumerate() {
-                if app.has_x86_64() {
-                    self.selected_index = i;
-                    break;
-                }
-            }
-        }
+        self.selected_index = app::first_visible(&self.apps, self.list_filter());
     }
 
     fn toggle_sort(&mut self) {
         self.sort_mode = match self.sort_mode {
-            SortMode::Size => SortMode::Alphabetical,
+            SortMode::Size => SortMode::Potential,
+            SortMode::Potential => SortMode::Alphabetical,
             SortMode::Alphabetical => SortMode::Size,
         };
         self.sort_apps();
@@ -897,20 +2946,413 @@ impl App {
     }
 
     fn sort_apps(&mut self) {
-        match self.sort_mode {
-            SortMode::Size => {
-                // Sort by prunable size (largest first), non-prunable apps at the end
-                self.apps
-                    .sort_by(|a, b| match (a.x86_64_size_mb(), b.x86_64_size_mb()) {
-                        (Some(size_a), Some(size_b)) => size_b.partial_cmp(&size_a).unwrap(),
-                        (Some(_), None) => std::cmp::Ordering::Less,
-                        (None, Some(_)) => std::cmp::Ordering::Greater,
-                        (None, None) => a.name.cmp(&b.name),
-                    });
-            }
-            SortMode::Alphabetical => {
-                self.apps.sort_by(|a, b| a.name.cmp(&b.name));
-            }
+        self.sort_mode.sort(&mut self.apps);
+
+        // Keep apps grouped by scan root (stable, so the order above is kept within groups)
+        self.apps.sort_by(|a, b| a.group.cmp(&b.group));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+    use devtools::ArtifactKind;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn scanned_app() -> App {
+        let mut slack = AppInfo::for_test(
+            "Slack",
+            &[("x86_64", Some(120 * MB)), ("arm64", Some(110 * MB))],
+        );
+        slack.selected = true;
+        let mut figma = AppInfo::for_test(
+            "Figma",
+            &[("x86_64", Some(80 * MB)), ("arm64", Some(75 * MB))],
+        );
+        figma.badges.push(Badge::Managed);
+        let notes = AppInfo::for_test("Notes", &[("arm64", Some(10 * MB))]);
+
+        let mut app = App::new();
+        app.finish_scan(vec![notes, figma, slack]);
+        app
+    }
+
+    fn draw(app: &mut App) -> TestBackend {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        terminal.backend().clone()
+    }
+
+    #[test]
+    fn test_loading() {
+        let mut app = App::new();
+        app.scan_progress = ScanProgress {
+            current: 4,
+            total: 10,
+            current_name: "Slack".to_string(),
+            done_bytes: 250,
+            total_bytes: 1000,
+            ..ScanProgress::default()
+        };
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_ready() {
+        insta::assert_snapshot!(draw(&mut scanned_app()));
+    }
+
+    #[test]
+    fn test_ready_ascii() {
+        let mut app = scanned_app().with_display(DisplayOptions {
+            ascii: true,
+            no_color: true,
+        });
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut app = scanned_app().with_trim_options(TrimOptions {
+            audit: true,
+            ..TrimOptions::default()
+        });
+        app.on_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.state, AppState::Ready);
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_confirm_trim() {
+        let press = |app: &mut App, code| app.on_key_event(KeyEvent::from(code));
+        let mut app = scanned_app();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupConfirmTrim);
+        insta::assert_snapshot!(draw(&mut app));
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.state, AppState::Ready);
+
+        app.trim_options.confirmation = Confirmation::Strict;
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupConfirmTyped);
+        press(&mut app, KeyCode::Char('y'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupConfirmTyped);
+        insta::assert_snapshot!("confirm_trim_rejected", draw(&mut app));
+        for c in "trim".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupPasswordInput);
+
+        app.state = AppState::Ready;
+        app.trim_options.confirmation = Confirmation::None;
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupPasswordInput);
+    }
+
+    #[test]
+    fn test_worker_crashed() {
+        let mut app = scanned_app();
+        app.worker_crashed(worker::Crash {
+            worker: "scanner",
+            message: "index out of bounds".to_string(),
+        });
+        assert_eq!(app.state, AppState::PopupError);
+        assert!(app.retry_scan);
+        insta::assert_snapshot!(draw(&mut app));
+
+        app.on_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.retry_scan);
+        app.state = AppState::PopupBreakdown;
+        app.worker_crashed(worker::Crash {
+            worker: "breakdown",
+            message: "oops".to_string(),
+        });
+        assert_eq!(app.state, AppState::PopupError);
+        assert!(!app.retry_scan);
+    }
+
+    #[test]
+    fn test_no_selection_popup() {
+        let mut app = scanned_app();
+        app.state = AppState::PopupNoSelection;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_confirm_managed_popup() {
+        let mut app = scanned_app();
+        app::toggle_all(&mut app.apps, ListFilter::default());
+        app.state = AppState::PopupConfirmManaged;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_password_popup() {
+        let mut app = scanned_app();
+        app.state = AppState::PopupPasswordInput;
+        app.password_input = "hunter2".to_string();
+        app.caps_lock = true;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_columns_popup() {
+        let mut app = scanned_app().with_hidden_columns(vec![Column::Architectures]);
+        app.state = AppState::PopupColumns;
+        app.column_cursor = 1;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_breakdown_popup() {
+        let mut app = scanned_app();
+        app.state = AppState::PopupBreakdown;
+        app.breakdown_app = "Slack".to_string();
+        app.breakdown = Some(Breakdown {
+            total_bytes: 480_000_000,
+            largest_dirs: vec![
+                (PathBuf::from("Contents/Frameworks"), 390_000_000),
+                (PathBuf::from("Contents/Resources"), 60_000_000),
+            ],
+            largest_files: vec![(
+                PathBuf::from(
+                    "Contents/Frameworks/Electron Framework.framework/Electron Framework",
+                ),
+                310_000_000,
+            )],
+            fat_binaries: vec![(
+                PathBuf::from("Contents/MacOS/Slack"),
+                vec![Arch::X86_64, Arch::Arm64],
+            )],
+            localizations: vec!["de".to_string(), "en".to_string()],
+            savings: vec![
+                (Strategy::LipoRemove, Some(150_000_000)),
+                (Strategy::KeepOnlyNative, None),
+            ],
+            xcode: None,
+        });
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_vendors_popup() {
+        let mut app = scanned_app();
+        app.apps[0].vendor = Some("Slack Technologies, Inc.".to_string());
+        app.state = AppState::PopupVendors;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_legend_popup() {
+        let mut app = scanned_app();
+        app.state = AppState::PopupLegend;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_developer_popup() {
+        let mut app = scanned_app();
+        app.artifacts = vec![
+            Artifact {
+                kind: ArtifactKind::SimulatorRuntime,
+                path: PathBuf::from("/Library/Developer/CoreSimulator/Images/iOS_21F79.dmg"),
+                bytes: 7_400_000_000,
+            },
+            Artifact {
+                kind: ArtifactKind::DeviceSupport,
+                path: PathBuf::from("/Users/me/Library/Developer/Xcode/iOS DeviceSupport"),
+                bytes: 3_100_000_000,
+            },
+        ];
+        app.state = AppState::PopupDeveloper;
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_filter() {
+        let press = |app: &mut App, code| app.on_key_event(KeyEvent::from(code));
+        let mut app = scanned_app();
+        press(&mut app, KeyCode::Char('/'));
+        for c in "slack >1MB arch:x86_64".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::Ready);
+        insta::assert_snapshot!(draw(&mut app));
+
+        // Editing starts from the current filter
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('('));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupFilter);
+        insta::assert_snapshot!("filter_popup_error", draw(&mut app));
+    }
+
+    #[test]
+    fn test_toggle_all_filtered() {
+        let press = |app: &mut App, code| app.on_key_event(KeyEvent::from(code));
+        let mut app = scanned_app();
+        press(&mut app, KeyCode::Char('/'));
+        for c in "figma".chars() {
+            press(&mut app, KeyCode::Char(c));
         }
+        press(&mut app, KeyCode::Enter);
+
+        // Slack stays selected but hidden, so it's neither toggled nor trimmed
+        press(&mut app, KeyCode::Char('a'));
+        let names = |app: &App| {
+            app.selected_prunable()
+                .map(|app| app.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&app), ["Figma"]);
+        assert!(
+            app.apps
+                .iter()
+                .any(|app| app.name == "Slack" && app.selected)
+        );
+        press(&mut app, KeyCode::Char('a'));
+        assert!(names(&app).is_empty());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::PopupNoSelection);
+    }
+
+    #[test]
+    fn test_savings_popup() {
+        let mut app = scanned_app();
+        app.last_savings = vec![
+            Savings {
+                app: "Slack".to_string(),
+                estimated: 120 * MB,
+                actual: 118 * MB,
+            },
+            Savings {
+                app: "Figma".to_string(),
+                estimated: 80 * MB,
+                actual: 140 * MB,
+            },
+        ];
+        app.on_key_event(KeyEvent::from(KeyCode::Char('t')));
+        assert_eq!(app.state, AppState::PopupSavings);
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_is_animating() {
+        let mut app = App::new();
+        assert!(app.is_animating());
+        app = scanned_app();
+        assert!(!app.is_animating());
+        app.state = AppState::Trimming;
+        assert!(app.is_animating());
+    }
+
+    #[test]
+    fn test_poll_trim_events() {
+        let mut app = scanned_app();
+        let (events, receiver) = mpsc::channel();
+        app.trim_events = Some(receiver);
+        events.send(TrimEvent::BatchStarted { total: 2 }).unwrap();
+        events
+            .send(TrimEvent::AppStarted {
+                index: 1,
+                name: "Figma".to_string(),
+            })
+            .unwrap();
+        events
+            .send(TrimEvent::BinaryTrimmed {
+                app: "Figma".to_string(),
+                path: PathBuf::from("/Applications/Figma.app/Contents/MacOS/Figma"),
+                bytes: 40 * MB,
+            })
+            .unwrap();
+
+        app.poll_trim_events();
+        assert_eq!((app.trim_progress, app.trim_total), (2, 2));
+        assert_eq!(app.trim_current, "Figma");
+        assert_eq!(app.trim_freed, 40 * MB);
+        assert!(app.trim_report.is_none());
+
+        events
+            .send(TrimEvent::BatchFinished {
+                report: BatchReport::default(),
+            })
+            .unwrap();
+        app.poll_trim_events();
+        assert!(app.trim_report.is_some());
+    }
+
+    #[test]
+    fn test_apply_reanalyzed() {
+        let mut app = scanned_app();
+        app.apps[1].selected = true;
+        let figma_path = app.apps[1].path.clone();
+        let trimmed = AppInfo::for_test("Slack", &[("arm64", Some(110 * MB))]);
+
+        app.apply_reanalyzed(vec![
+            (trimmed.path.clone(), Ok(trimmed)),
+            (figma_path, Err(SkipReason::NoArm64)),
+        ]);
+
+        let names: Vec<_> = app.apps.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(names, ["Slack", "Notes"]);
+        assert!(!app.apps[0].selected);
+        assert!(!app.apps[0].is_prunable());
+        assert_eq!(app.scan_progress.skipped.len(), 1);
+        // Nothing visible is left for the cursor, so it stays put
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_rescanning() {
+        let mut app = scanned_app();
+        app.state = AppState::Trimming;
+        app.trim_progress = 2;
+        app.trim_total = 2;
+        app.rescan_progress = Some(ScanProgress {
+            current: 1,
+            total: 4,
+            current_name: "Figma".to_string(),
+            ..ScanProgress::default()
+        });
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_error_popup() {
+        let mut app = scanned_app();
+        app.show_error("Failed to run sudo: not found".to_string());
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_trimming() {
+        let mut app = scanned_app();
+        app.state = AppState::Trimming;
+        app.trim_progress = 1;
+        app.trim_total = 2;
+        app.trim_current = "Slack".to_string();
+        insta::assert_snapshot!(draw(&mut app));
+    }
+
+    #[test]
+    fn test_cancel_trim() {
+        let mut app = scanned_app();
+        app.state = AppState::Trimming;
+        app.trim_progress = 1;
+        app.trim_total = 2;
+        app.trim_current = "Slack".to_string();
+        let cancel = app.cancel.clone();
+        app.on_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(cancel.is_cancelled());
+        assert_eq!(app.state, AppState::Trimming);
+        insta::assert_snapshot!(draw(&mut app));
     }
 }
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use plist::Value;
+
+use crate::scanner::AppInfo;
+
+const MUNKI_REPORT: &str = "/Library/Managed Installs/ManagedInstallReport.plist";
+const JAMF_RECEIPTS: &str = "/Library/Application Support/JAMF/Receipts";
+const MANAGED_PREFERENCES: &str = "/Library/Managed Preferences";
+
+/// Apps known to a management system (MDM, Munki or Jamf) on this machine.
+///
+/// Managed apps are likely to be reinstalled or flagged as tampered with after
+/// trimming, so they are marked and require an explicit confirmation.
+#[derive(Debug, Default)]
+pub struct ManagedInventory {
+    /// Bundle paths listed in the Munki install report
+    munki_paths: HashSet<String>,
+    /// Normalized names of the packages of Jamf receipts, without their versions
+    jamf_receipts: HashSet<String>,
+    /// Bundle identifiers with a managed preferences payload
+    managed_bundle_ids: HashSet<String>,
+}
+
+impl ManagedInventory {
+    /// Reads the management receipts and inventories that exist on this machine.
+    pub fn load() -> Self {
+        let mut inventory = Self::default();
+
+        if let Ok(report) = Value::from_file(MUNKI_REPORT) {
+            collect_app_paths(&report, &mut inventory.munki_paths);
+        }
+
+        if let Ok(entries) = fs::read_dir(JAMF_RECEIPTS) {
+            inventory.jamf_receipts = entries
+                .flatten()
+                .map(|entry| normalize(receipt_name(&entry.file_name().to_string_lossy())))
+                .collect();
+        }
+
+        collect_managed_preferences(Path::new(MANAGED_PREFERENCES), &mut inventory);
+        inventory
+    }
+
+    pub fn is_managed(&self, app: &AppInfo) -> bool {
+        if self
+            .munki_paths
+            .contains(app.path.to_string_lossy().as_ref())
+        {
+            return true;
+        }
+
+        if let Some(bundle_id) = &app.bundle_id
+            && self.managed_bundle_ids.contains(bundle_id)
+        {
+            return true;
+        }
+
+        let name = normalize(&app.name);
+        !name.is_empty() && self.jamf_receipts.contains(&name)
+    }
+}
+
+/// Recursively collects every string in a plist that looks like an app bundle path.
+fn collect_app_paths(value: &Value, paths: &mut HashSet<String>) {
+    match value {
+        Value::String(s) if s.starts_with('/') && s.ends_with(".app") => {
+            paths.insert(s.clone());
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_app_paths(item, paths);
+            }
+        }
+        Value::Dictionary(dict) => {
+            for item in dict.values() {
+                collect_app_paths(item, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects bundle identifiers from `<bundle id>.plist` files, both machine-wide
+/// and in the per-user subdirectories.
+fn collect_managed_preferences(dir: &Path, inventory: &mut ManagedInventory) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_managed_preferences(&path, inventory);
+        } else if path.extension().and_then(|s| s.to_str()) == Some("plist")
+            && let Some(bundle_id) = path.file_stem().and_then(|s| s.to_str())
+        {
+            inventory.managed_bundle_ids.insert(bundle_id.to_string());
+        }
+    }
+}
+
+/// The package name of a receipt file such as `GoogleChrome-120.0.pkg`, `Zoom_5.16.2.pkg`
+/// or `Firefox 121.0.dmg`: everything before the extension and the version, which
+/// starts at the first `-`, `_` or space followed by a digit or a `v` and a digit.
+fn receipt_name(file_name: &str) -> &str {
+    let stem = Path::new(file_name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|_| file_name.ends_with(".pkg") || file_name.ends_with(".dmg"))
+        .unwrap_or(file_name);
+    let bytes = stem.as_bytes();
+    let version_start = (0..bytes.len()).find(|&index| {
+        let rest = &bytes[index + 1..];
+        matches!(bytes[index], b'-' | b'_' | b' ')
+            && match rest {
+                [b'v' | b'V', digit, ..] | [digit, ..] => digit.is_ascii_digit(),
+                [] => false,
+            }
+    });
+    &stem[..version_start.unwrap_or(stem.len())]
+}
+
+/// Lowercases and strips everything but letters and digits, so that "Google Chrome"
+/// matches a receipt named "GoogleChrome-120.0.pkg".
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, bundle_id: Option<&str>) -> AppInfo {
+        let mut app = AppInfo::for_test(name, &[("x86_64", Some(1024)), ("arm64", Some(1024))]);
+        app.bundle_id = bundle_id.map(str::to_string);
+        app
+    }
+
+    #[test]
+    fn test_collect_app_paths() {
+        let mut dict = plist::Dictionary::new();
+        dict.insert(
+            "InstalledItems".to_string(),
+            Value::Array(vec![
+                Value::String("/Applications/Firefox.app".to_string()),
+                Value::String("Firefox".to_string()),
+            ]),
+        );
+
+        let mut paths = HashSet::new();
+        collect_app_paths(&Value::Dictionary(dict), &mut paths);
+        assert_eq!(paths.len(), 1);
+        assert!(paths.contains("/Applications/Firefox.app"));
+    }
+
+    #[test]
+    fn test_is_managed() {
+        let inventory = ManagedInventory {
+            munki_paths: HashSet::from(["/Applications/Firefox.app".to_string()]),
+            jamf_receipts: ["GoogleChrome-120.0.pkg", "Zoom_5.16.2.pkg"]
+                .into_iter()
+                .map(|receipt| normalize(receipt_name(receipt)))
+                .collect(),
+            managed_bundle_ids: HashSet::from(["com.tinyspeck.slackmacgap".to_string()]),
+        };
+
+        assert!(inventory.is_managed(&app("Firefox", None)));
+        assert!(inventory.is_managed(&app("Google Chrome", None)));
+        assert!(inventory.is_managed(&app("Slack", Some("com.tinyspeck.slackmacgap"))));
+        assert!(!inventory.is_managed(&app("Zed", Some("dev.zed.Zed"))));
+        assert!(inventory.is_managed(&app("Zoom", None)));
+        // Names that only start a receipt's name aren't managed
+        assert!(!inventory.is_managed(&app("Go", None)));
+        assert!(!inventory.is_managed(&app("Google", None)));
+    }
+
+    #[test]
+    fn test_receipt_name() {
+        assert_eq!(receipt_name("GoogleChrome-120.0.pkg"), "GoogleChrome");
+        assert_eq!(receipt_name("Zoom_5.16.2.pkg"), "Zoom");
+        assert_eq!(receipt_name("Firefox 121.0.dmg"), "Firefox");
+        assert_eq!(receipt_name("Docker-v4.26.pkg"), "Docker");
+        assert_eq!(receipt_name("Visual-Studio-Code.pkg"), "Visual-Studio-Code");
+        assert_eq!(receipt_name("Slack"), "Slack");
+    }
+}
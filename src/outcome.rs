@@ -0,0 +1,134 @@
+use std::process::ExitCode;
+
+use crate::report::RunReport;
+
+/// Exit code for command line usage errors, from sysexits.h. clap's default of 2
+/// would be mistaken for [`Status::PartialFailure`].
+pub const USAGE_EXIT_CODE: u8 = 64;
+
+/// How a plain run ended. The exit codes and names are part of the scripting
+/// contract documented in the README, so they must not change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Every chosen app was trimmed
+    Success,
+    /// At least one chosen app couldn't be trimmed
+    PartialFailure,
+    /// No app can be trimmed
+    NothingToDo,
+    /// sudo rejected the password
+    AuthFailed,
+    /// No app was chosen, or the confirmation was declined
+    Cancelled,
+    /// lipo isn't installed
+    LipoMissing,
+    /// The apps were listed in audit mode, which never trims
+    Audit,
+}
+
+impl Status {
+    pub fn code(self) -> u8 {
+        match self {
+            Status::Success => 0,
+            Status::PartialFailure => 2,
+            Status::NothingToDo => 3,
+            Status::AuthFailed => 4,
+            Status::Cancelled => 5,
+            Status::LipoMissing => 6,
+            Status::Audit => 0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::PartialFailure => "partial-failure",
+            Status::NothingToDo => "nothing-to-do",
+            Status::AuthFailed => "auth-failed",
+            Status::Cancelled => "cancelled",
+            Status::LipoMissing => "lipo-missing",
+            Status::Audit => "audit",
+        }
+    }
+}
+
+/// Result of a plain run, printed as the last line of stdout and turned into the
+/// exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub status: Status,
+    pub trimmed: usize,
+    pub failed: usize,
+    pub saved_bytes: u64,
+}
+
+impl Outcome {
+    /// An outcome where nothing was trimmed.
+    pub fn without_trimming(status: Status) -> Self {
+        Outcome {
+            status,
+            trimmed: 0,
+            failed: 0,
+            saved_bytes: 0,
+        }
+    }
+
+    /// Outcome of a run that trimmed the apps in `report`.
+    pub fn from_report(report: &RunReport) -> Self {
+        Outcome {
+            status: if report.failed.is_empty() {
+                Status::Success
+            } else {
+                Status::PartialFailure
+            },
+            trimmed: report.trimmed.len(),
+            failed: report.failed.len(),
+            saved_bytes: report.saved_bytes.bytes(),
+        }
+    }
+
+    /// Space separated `key=value` pairs behind a fixed prefix, such as
+    /// `bintrim-result status=success exit=0 trimmed=3 failed=0 saved-bytes=1024`.
+    /// New keys are only ever appended.
+    pub fn summary_line(&self) -> String {
+        format!(
+            "bintrim-result status={} exit={} trimmed={} failed={} saved-bytes={}",
+            self.status.name(),
+            self.status.code(),
+            self.trimmed,
+            self.failed,
+            self.saved_bytes
+        )
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.status.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_line() {
+        let outcome = Outcome {
+            status: Status::PartialFailure,
+            trimmed: 3,
+            failed: 1,
+            saved_bytes: 1024,
+        };
+        assert_eq!(
+            outcome.summary_line(),
+            "bintrim-result status=partial-failure exit=2 trimmed=3 failed=1 saved-bytes=1024"
+        );
+        assert_eq!(
+            Outcome::without_trimming(Status::NothingToDo).summary_line(),
+            "bintrim-result status=nothing-to-do exit=3 trimmed=0 failed=0 saved-bytes=0"
+        );
+        assert_eq!(
+            Outcome::without_trimming(Status::Audit).exit_code(),
+            ExitCode::SUCCESS
+        );
+    }
+}
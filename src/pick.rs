@@ -0,0 +1,140 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::bytesize::ByteSize;
+use crate::i18n::t;
+use crate::scanner::AppInfo;
+
+/// Fuzzy finders `bintrim pick` hands the list to when one is installed, in
+/// order of preference. Both take the same flags.
+const EXTERNAL_PICKERS: [&str; 2] = ["fzf", "sk"];
+
+/// One app per line for a fuzzy finder or another script: the bundle path,
+/// then name, savings and architectures, separated by tabs.
+pub fn candidate_line(app: &AppInfo) -> String {
+    let size = app
+        .prune_size()
+        .map(ByteSize::display_mb)
+        .unwrap_or_else(|| t().not_available.to_string());
+    format!(
+        "{}\t{}\t{}\t{}",
+        app.path.display(),
+        app.name,
+        size,
+        app.architectures_display()
+    )
+}
+
+/// The first of [`EXTERNAL_PICKERS`] on `PATH`.
+pub fn external_picker() -> Option<&'static str> {
+    let path = std::env::var_os("PATH")?;
+    EXTERNAL_PICKERS
+        .into_iter()
+        .find(|program| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Lets the user pick apps in `program` with multi-select, showing everything but
+/// the path. Returns the indices of the picked apps, empty if the picker was
+/// closed without picking.
+pub fn pick_external(program: &str, apps: &[AppInfo]) -> io::Result<Vec<usize>> {
+    // The picker draws on /dev/tty itself, so only the list goes through the pipes
+    let mut child = Command::new(program)
+        .arg("--multi")
+        .arg("--delimiter")
+        .arg("\t")
+        .arg("--with-nth")
+        .arg("2..")
+        .arg("--prompt")
+        .arg("bintrim> ")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The picker may be closed before it read everything
+        for app in apps {
+            if writeln!(stdin, "{}", candidate_line(app)).is_err() {
+                break;
+            }
+        }
+    }
+    let output = child.wait_with_output()?;
+    // Nonzero when nothing matched or the picker was cancelled with Esc
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    Ok(picked_indices(
+        &String::from_utf8_lossy(&output.stdout),
+        apps,
+    ))
+}
+
+/// Indices of the apps whose candidate lines a picker printed, in list order.
+fn picked_indices(output: &str, apps: &[AppInfo]) -> Vec<usize> {
+    let paths: Vec<&Path> = output
+        .lines()
+        .filter_map(|line| line.split('\t').next())
+        .map(Path::new)
+        .collect();
+    (0..apps.len())
+        .filter(|&index| paths.contains(&apps[index].path.as_path()))
+        .collect()
+}
+
+/// Indices of the apps whose name or bundle identifier contains the characters
+/// of `query` in order, ignoring case. An empty query matches every app.
+pub fn filter(query: &str, apps: &[AppInfo]) -> Vec<usize> {
+    (0..apps.len())
+        .filter(|&index| {
+            let app = &apps[index];
+            fuzzy_match(query, &app.name)
+                || app
+                    .bundle_id
+                    .as_deref()
+                    .is_some_and(|bundle_id| fuzzy_match(query, bundle_id))
+        })
+        .collect()
+}
+
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|wanted| text.any(|c| c == wanted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("", "Slack"));
+        assert!(fuzzy_match("slk", "Slack"));
+        assert!(fuzzy_match("VS code", "Visual Studio Code"));
+        assert!(!fuzzy_match("kcals", "Slack"));
+        assert!(!fuzzy_match("slacks", "Slack"));
+    }
+
+    #[test]
+    fn test_filter_and_pick() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", None), ("x86_64", None)]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        let zoom = AppInfo::for_test("zoom.us", &[("arm64", None), ("x86_64", None)]);
+        let apps = [slack, zoom];
+
+        assert_eq!(filter("tinyspeck", &apps), [0]);
+        assert_eq!(filter("zm", &apps), [1]);
+        assert_eq!(filter("", &apps), [0, 1]);
+
+        let output = format!(
+            "{}\n{}\n",
+            candidate_line(&apps[1]),
+            candidate_line(&apps[0])
+        );
+        assert_eq!(picked_indices(&output, &apps), [0, 1]);
+        assert_eq!(picked_indices("", &apps), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,626 @@
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tracing::error;
+
+use crate::SortMode;
+use crate::bytesize::ByteSize;
+use crate::cancel::CancelToken;
+use crate::confirm::{self, Confirmation};
+use crate::filter::AppFilter;
+use crate::i18n::t;
+use crate::location;
+use crate::outcome::{Outcome, Status};
+use crate::pick;
+use crate::report::{ReportOptions, RunReport, Timings, millis};
+use crate::scanner::{self, AppInfo, Badge, ScanEvent, ScanOptions};
+use crate::stats::ArchStats;
+use crate::trimmer::{self, BatchReport, TrimEvent, TrimOptions};
+
+/// Line-based fallback for when stdout isn't a terminal or `--plain` is passed:
+/// prints a numbered list of prunable apps and asks which ones to trim. When a
+/// report is configured it's delivered after trimming, or if there's nothing to trim.
+pub fn run(
+    filter: &AppFilter,
+    scan_options: &ScanOptions,
+    trim_options: &TrimOptions,
+    report_options: &ReportOptions,
+) -> color_eyre::Result<Outcome> {
+    let strings = t();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    println!("{}", strings.plain_scanning);
+    let (scanned, timings) = scan(scan_options);
+    println!(
+        "{}{}",
+        strings.architectures_label,
+        ArchStats::of(&scanned).summary()
+    );
+    let apps = prunable(scanned, filter);
+    if apps.is_empty() {
+        println!("{}", strings.plain_nothing_to_trim);
+        deliver_report(&RunReport::new(&[], &[], timings), report_options);
+        return Ok(Outcome::without_trimming(Status::NothingToDo));
+    }
+
+    for (index, app) in apps.iter().enumerate() {
+        println!("{}", list_line(index + 1, app));
+    }
+    if trim_options.audit {
+        println!("{}", strings.audit_mode_plain);
+        deliver_report(&RunReport::new(&[], &[], timings), report_options);
+        return Ok(Outcome::without_trimming(Status::Audit));
+    }
+    if !trim_options.lipo_available {
+        println!("{}", strings.lipo_missing);
+        return Ok(Outcome::without_trimming(Status::LipoMissing));
+    }
+
+    let chosen = loop {
+        let Some(line) = prompt(&mut input, strings.plain_choose)? else {
+            return Ok(Outcome::without_trimming(Status::Cancelled));
+        };
+        if line.trim().is_empty() {
+            return Ok(Outcome::without_trimming(Status::Cancelled));
+        }
+        match parse_selection(&line, apps.len()) {
+            Some(chosen) => break chosen,
+            None => println!("{}", (strings.plain_invalid)(line.trim())),
+        }
+    };
+    let apps: Vec<_> = chosen.into_iter().map(|index| &apps[index]).collect();
+
+    if !confirm_managed(&mut input, &apps)?
+        || !confirm_typed(&mut input, trim_options.confirmation, apps.len())?
+    {
+        return Ok(Outcome::without_trimming(Status::Cancelled));
+    }
+    trim(&apps, timings, trim_options, report_options)
+}
+
+/// `bintrim pick`: picks apps in fzf or sk if one is installed, or else with a
+/// filter prompt, and trims them.
+pub fn run_pick(
+    builtin: bool,
+    filter: &AppFilter,
+    scan_options: &ScanOptions,
+    trim_options: &TrimOptions,
+    report_options: &ReportOptions,
+) -> color_eyre::Result<Outcome> {
+    let strings = t();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    println!("{}", strings.plain_scanning);
+    let (scanned, timings) = scan(scan_options);
+    let apps = prunable(scanned, filter);
+    if apps.is_empty() {
+        println!("{}", strings.plain_nothing_to_trim);
+        deliver_report(&RunReport::new(&[], &[], timings), report_options);
+        return Ok(Outcome::without_trimming(Status::NothingToDo));
+    }
+    if !trim_options.lipo_available {
+        println!("{}", strings.lipo_missing);
+        return Ok(Outcome::without_trimming(Status::LipoMissing));
+    }
+
+    let chosen = match pick::external_picker().filter(|_| !builtin) {
+        Some(program) => pick::pick_external(program, &apps)?,
+        None => pick_builtin(&mut input, &apps)?,
+    };
+    if chosen.is_empty() {
+        return Ok(Outcome::without_trimming(Status::Cancelled));
+    }
+    let apps: Vec<_> = chosen.into_iter().map(|index| &apps[index]).collect();
+
+    if !confirm_managed(&mut input, &apps)?
+        || !confirm_typed(&mut input, trim_options.confirmation, apps.len())?
+    {
+        return Ok(Outcome::without_trimming(Status::Cancelled));
+    }
+    trim(&apps, timings, trim_options, report_options)
+}
+
+/// `bintrim pick --print`: the candidate lines of the prunable apps, for piping
+/// into another picker. Progress goes to stderr to keep stdout clean.
+pub fn print_candidates(filter: &AppFilter, scan_options: &ScanOptions) {
+    eprintln!("{}", t().plain_scanning);
+    for app in prunable_apps(filter, scan_options) {
+        println!("{}", pick::candidate_line(&app));
+    }
+}
+
+/// `bintrim list`: a table of the prunable apps in `sort` order, only the first
+/// `top` if given, and what they free together. Progress goes to stderr.
+pub fn print_list(
+    filter: &AppFilter,
+    scan_options: &ScanOptions,
+    sort: SortMode,
+    top: Option<usize>,
+) {
+    eprintln!("{}", t().plain_scanning);
+    let mut apps = prunable_apps(filter, scan_options);
+    sort.sort(&mut apps);
+    for line in list_table(&apps, top.unwrap_or(apps.len())) {
+        println!("{}", line);
+    }
+}
+
+/// The lines of the `bintrim list` table for the first `top` of `apps`, with the
+/// columns padded to the longest value.
+fn list_table(apps: &[AppInfo], top: usize) -> Vec<String> {
+    let strings = t();
+    let shown = &apps[..top.min(apps.len())];
+    let size = |size: Option<ByteSize>| {
+        size.map(|size| size.to_string())
+            .unwrap_or_else(|| strings.not_available.to_string())
+    };
+    let mut rows = vec![[
+        strings.list_header_app.to_string(),
+        strings.list_header_prunable.to_string(),
+        strings.list_header_potential.to_string(),
+        strings.list_header_architectures.to_string(),
+    ]];
+    rows.extend(shown.iter().map(|app| {
+        [
+            app.name.clone(),
+            size(app.prune_size()),
+            size(app.potential.map(|potential| ByteSize(potential.total()))),
+            app.architectures_display(),
+        ]
+    }));
+
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (name_width, prunable_width, potential_width) = (width(0), width(1), width(2));
+    let mut lines: Vec<_> = rows
+        .iter()
+        .map(|[name, prunable, potential, architectures]| {
+            format!(
+                "{:<name_width$}  {:>prunable_width$}  {:>potential_width$}  {}",
+                name, prunable, potential, architectures
+            )
+        })
+        .collect();
+    let freed = shown
+        .iter()
+        .filter_map(|app| app.prune_size())
+        .map(|size| size.bytes())
+        .sum();
+    lines.push((strings.list_total)(
+        shown.len(),
+        apps.len(),
+        &ByteSize(freed).to_string(),
+    ));
+    lines
+}
+
+/// Narrows the list down with a fuzzy filter until a selection is made. Returns
+/// the indices of the chosen apps, empty at the end of input.
+fn pick_builtin(input: &mut impl BufRead, apps: &[AppInfo]) -> io::Result<Vec<usize>> {
+    let strings = t();
+    loop {
+        let Some(query) = prompt(input, strings.pick_filter)? else {
+            return Ok(Vec::new());
+        };
+        let matches = pick::filter(query.trim(), apps);
+        if matches.is_empty() {
+            println!("{}", (strings.pick_no_matches)(query.trim()));
+            continue;
+        }
+        for (number, &index) in matches.iter().enumerate() {
+            println!("{}", list_line(number + 1, &apps[index]));
+        }
+
+        let Some(line) = prompt(input, strings.pick_choose)? else {
+            return Ok(Vec::new());
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_selection(&line, matches.len()) {
+            Some(chosen) => return Ok(chosen.into_iter().map(|number| matches[number]).collect()),
+            None => println!("{}", (strings.plain_invalid)(line.trim())),
+        }
+    }
+}
+
+/// Scans the apps and returns them with the time the scan took.
+fn scan(scan_options: &ScanOptions) -> (Vec<AppInfo>, Timings) {
+    let mut duration = Duration::ZERO;
+    let apps =
+        scanner::scan_applications_with_progress(scan_options, &CancelToken::default(), |event| {
+            if let ScanEvent::Finished { duration: took, .. } = event {
+                duration = took;
+            }
+        });
+    let timings = Timings {
+        scan_ms: millis(duration),
+        ..Timings::default()
+    };
+    (apps, timings)
+}
+
+/// Scans and returns the prunable apps that `filter` matches, biggest savings first.
+fn prunable_apps(filter: &AppFilter, scan_options: &ScanOptions) -> Vec<AppInfo> {
+    prunable(scan(scan_options).0, filter)
+}
+
+/// The prunable apps among `scanned` that `filter` matches, biggest savings first.
+fn prunable(scanned: Vec<AppInfo>, filter: &AppFilter) -> Vec<AppInfo> {
+    let mut apps: Vec<_> = scanned
+        .into_iter()
+        .filter(|app| app.is_prunable() && filter.matches(app))
+        .collect();
+    apps.sort_by_key(|app| std::cmp::Reverse(app.prune_size()));
+    apps
+}
+
+/// Warns about managed apps among `apps` and asks whether to trim them anyway.
+/// Returns true if there are none or the user agreed.
+fn confirm_managed(input: &mut impl BufRead, apps: &[&AppInfo]) -> io::Result<bool> {
+    let strings = t();
+    let managed = apps
+        .iter()
+        .filter(|app| app.badges.contains(&Badge::Managed))
+        .count();
+    if managed == 0 {
+        return Ok(true);
+    }
+    println!("{}", (strings.managed_selected)(managed));
+    for line in strings.managed_warning {
+        println!("{}", line);
+    }
+    let answer = prompt(input, strings.plain_confirm)?.unwrap_or_default();
+    Ok(matches!(answer.trim(), "y" | "Y" | "j" | "J"))
+}
+
+/// At the `strict` confirmation level, asks to type `trim` or the number of apps.
+/// Below it, choosing the apps is the confirmation.
+fn confirm_typed(
+    input: &mut impl BufRead,
+    confirmation: Confirmation,
+    count: usize,
+) -> io::Result<bool> {
+    if confirmation != Confirmation::Strict {
+        return Ok(true);
+    }
+    let answer = prompt(input, &(t().plain_confirm_typed)(count))?.unwrap_or_default();
+    Ok(confirm::accepts(&answer, count))
+}
+
+/// Trims the apps named by `targets`, bundle paths or bundle identifiers as given
+/// to `bintrim trim`, without asking. Targets that aren't found or can't be trimmed
+/// are reported on stderr and count as failures, the others are trimmed anyway.
+pub fn run_targets(
+    targets: &[String],
+    scan_options: &ScanOptions,
+    trim_options: &TrimOptions,
+    report_options: &ReportOptions,
+) -> color_eyre::Result<Outcome> {
+    let strings = t();
+    println!("{}", strings.plain_scanning);
+    let (scanned, timings) = scan(scan_options);
+
+    let mut apps: Vec<&AppInfo> = Vec::new();
+    let mut rejected = 0;
+    for target in targets {
+        let matches = resolve_target(target, &scanned);
+        if matches.is_empty() {
+            eprintln!("{}", (strings.target_not_found)(target));
+            rejected += 1;
+        }
+        for app in matches {
+            if !app.is_prunable() {
+                eprintln!("{}", (strings.target_not_trimmable)(target, &app.name));
+                rejected += 1;
+            } else if !apps.iter().any(|chosen| chosen.path == app.path) {
+                apps.push(app);
+            }
+        }
+    }
+
+    // The targets refused above count as failed either way
+    let untrimmed = if apps.is_empty() {
+        Some((strings.plain_nothing_to_trim, Status::NothingToDo))
+    } else if !trim_options.lipo_available {
+        Some((strings.lipo_missing, Status::LipoMissing))
+    } else {
+        None
+    };
+    if let Some((message, status)) = untrimmed {
+        println!("{}", message);
+        deliver_report(&RunReport::new(&[], &[], timings), report_options);
+        let mut outcome = Outcome::without_trimming(status);
+        outcome.failed = rejected;
+        return Ok(outcome);
+    }
+
+    // Naming an app explicitly is the confirmation, but the warning still applies
+    let managed = apps
+        .iter()
+        .filter(|app| app.badges.contains(&Badge::Managed))
+        .count();
+    if managed > 0 {
+        eprintln!("{}", (strings.managed_selected)(managed));
+        for line in strings.managed_warning {
+            eprintln!("{}", line);
+        }
+    }
+
+    let mut outcome = trim(&apps, timings, trim_options, report_options)?;
+    if rejected > 0 && outcome.status == Status::Success {
+        outcome.status = Status::PartialFailure;
+    }
+    outcome.failed += rejected;
+    Ok(outcome)
+}
+
+/// Apps a `bintrim trim` target refers to: the bundle at a path (`~/` is expanded),
+/// or every bundle with an identifier, ignoring case like Launch Services does.
+fn resolve_target<'a>(target: &str, apps: &'a [AppInfo]) -> Vec<&'a AppInfo> {
+    let path = match target.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(rest)),
+        None if target.starts_with('/') => Some(PathBuf::from(target)),
+        None => None,
+    };
+    match path {
+        Some(path) => {
+            let canonical = path.exists().then(|| location::installed_path(&path));
+            apps.iter()
+                .filter(|app| {
+                    app.path == path
+                        || canonical
+                            .as_deref()
+                            .is_some_and(|canonical| same_file(&app.path, canonical))
+                })
+                .collect()
+        }
+        None => apps
+            .iter()
+            .filter(|app| {
+                app.bundle_id
+                    .as_deref()
+                    .is_some_and(|bundle_id| bundle_id.eq_ignore_ascii_case(target))
+            })
+            .collect(),
+    }
+}
+
+fn same_file(path: &Path, canonical: &Path) -> bool {
+    path.canonicalize().is_ok_and(|path| path == canonical)
+}
+
+/// Authenticates with sudo unless already root, then trims `apps` and delivers
+/// the report with the scan's `timings`.
+fn trim(
+    apps: &[&AppInfo],
+    mut timings: Timings,
+    trim_options: &TrimOptions,
+    report_options: &ReportOptions,
+) -> color_eyre::Result<Outcome> {
+    let strings = t();
+    if !trim_options.elevated && !trim_options.helper {
+        println!("{}", (strings.sudo_needed)(apps.len()));
+        let status = Command::new("sudo")
+            .arg("-v")
+            .arg("-p")
+            .arg(strings.sudo_prompt)
+            .status();
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(_) => {
+                println!("{}", strings.sudo_failed);
+                return Ok(Outcome::without_trimming(Status::AuthFailed));
+            }
+            Err(err) => color_eyre::eyre::bail!((strings.sudo_run_failed)(&err.to_string())),
+        }
+    }
+
+    // sudo's credentials are cached now, so no password has to be passed on
+    let batch: Vec<AppInfo> = apps.iter().map(|app| (*app).clone()).collect();
+    let (events, receiver) = mpsc::channel();
+    let mut report = BatchReport::default();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            trimmer::trim_batch(&batch, "", trim_options, &CancelToken::default(), &events)
+        });
+        for event in receiver {
+            match event {
+                TrimEvent::AppStarted { name, .. } => println!("{}", (strings.trimming)(&name)),
+                TrimEvent::BatchFinished { report: finished } => report = finished,
+                _ => {}
+            }
+        }
+    });
+    timings.trim_ms = report.record.duration_ms;
+    report.record.scan_duration_ms = timings.scan_ms;
+    if let Err(err) = report
+        .record
+        .save(trim_options.report_out.as_deref(), trim_options.elevated)
+    {
+        error!(%err, "failed to save the batch report");
+        println!("{}", (strings.report_failed)(&format!("{:#}", err)));
+    }
+    let failures: Vec<_> = report
+        .failures
+        .into_iter()
+        .map(|failure| (apps[failure.index], failure.error))
+        .collect();
+
+    println!("{}", (strings.trimmed)(apps.len() - failures.len()));
+    let (transient, permanent): (Vec<_>, Vec<_>) =
+        failures.iter().partition(|(_, err)| err.is_transient());
+    for (failures, header) in [
+        (permanent, strings.trim_failed_details),
+        (transient, strings.trim_failed_transient),
+    ] {
+        if !failures.is_empty() {
+            println!("{}", header(failures.len()));
+            for (app, err) in failures {
+                println!("  {}: {}", app.name, err);
+            }
+        }
+    }
+    let report = RunReport::new(apps, &failures, timings);
+    deliver_report(&report, report_options);
+    Ok(Outcome::from_report(&report))
+}
+
+/// Delivers the report if one is configured. A failed delivery is reported but
+/// doesn't fail the run, the apps were trimmed either way.
+fn deliver_report(report: &RunReport, options: &ReportOptions) {
+    if !options.is_enabled() {
+        return;
+    }
+    if let Err(err) = report.deliver(options) {
+        error!(%err, "failed to deliver report");
+        println!("{}", (t().report_failed)(&format!("{:#}", err)));
+    }
+}
+
+fn list_line(number: usize, app: &AppInfo) -> String {
+    let size = app
+        .prune_size()
+        .map(ByteSize::display_mb)
+        .unwrap_or_else(|| t().not_available.to_string());
+    let mut line = format!(
+        "{:>3}. {}  {}  [{}]",
+        number,
+        app.name,
+        size,
+        app.architectures_display()
+    );
+    for badge in &app.badges {
+        line.push_str("  ");
+        line.push_str(badge.label());
+    }
+    line
+}
+
+/// Prints `message` and reads one line, or `None` at the end of input.
+fn prompt(input: &mut impl BufRead, message: &str) -> io::Result<Option<String>> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+/// Parses a selection such as `1 3 5-7` or `a` into zero-based indices of a
+/// list with `count` entries, in list order and without duplicates.
+fn parse_selection(line: &str, count: usize) -> Option<Vec<usize>> {
+    let line = line.trim();
+    if line.eq_ignore_ascii_case("a") || line.eq_ignore_ascii_case("all") {
+        return Some((0..count).collect());
+    }
+
+    let mut chosen = vec![false; count];
+    for part in line.split([' ', ',']).filter(|part| !part.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            None => {
+                let number = part.parse().ok()?;
+                (number, number)
+            }
+        };
+        if start == 0 || start > end || end > count {
+            return None;
+        }
+        for entry in &mut chosen[start - 1..end] {
+            *entry = true;
+        }
+    }
+
+    let chosen: Vec<_> = (0..count).filter(|&index| chosen[index]).collect();
+    (!chosen.is_empty()).then_some(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1 3 5-7\n", 8), Some(vec![0, 2, 4, 5, 6]));
+        assert_eq!(parse_selection("2,1,2", 3), Some(vec![0, 1]));
+        assert_eq!(parse_selection("a", 3), Some(vec![0, 1, 2]));
+        assert_eq!(parse_selection("0", 3), None);
+        assert_eq!(parse_selection("4", 3), None);
+        assert_eq!(parse_selection("3-1", 3), None);
+        assert_eq!(parse_selection("x", 3), None);
+        assert_eq!(parse_selection(" ", 3), None);
+    }
+
+    #[test]
+    fn test_list_table() {
+        let mut apps = vec![
+            AppInfo::for_test("Zoom", &[("arm64", None), ("x86_64", Some(1024 * 1024))]),
+            AppInfo::for_test(
+                "Slack",
+                &[("arm64", None), ("x86_64", Some(3 * 1024 * 1024))],
+            ),
+            AppInfo::for_test(
+                "Notes",
+                &[("arm64", None), ("x86_64", Some(2 * 1024 * 1024))],
+            ),
+        ];
+        SortMode::Size.sort(&mut apps);
+        let lines = list_table(&apps, 2);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("Slack  "));
+        assert!(lines[2].starts_with("Notes  "));
+        assert!(lines[0].starts_with(&format!("{:<5}  ", t().list_header_app)));
+        assert_eq!(
+            lines[3],
+            (t().list_total)(2, 3, &ByteSize(5 * 1024 * 1024).to_string())
+        );
+
+        SortMode::Alphabetical.sort(&mut apps);
+        assert_eq!(list_table(&apps, 10).len(), 5);
+        assert!(list_table(&apps, 10)[1].starts_with("Notes"));
+    }
+
+    #[test]
+    fn test_confirm_typed() {
+        let confirm = |confirmation, answer: &str| {
+            confirm_typed(&mut answer.as_bytes(), confirmation, 2).unwrap()
+        };
+        assert!(confirm(Confirmation::Normal, ""));
+        assert!(confirm(Confirmation::Strict, "trim\n"));
+        assert!(confirm(Confirmation::Strict, "2\n"));
+        assert!(!confirm(Confirmation::Strict, "y\n"));
+        assert!(!confirm(Confirmation::Strict, ""));
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", None), ("x86_64", None)]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        let zoom = AppInfo::for_test("zoom.us", &[("arm64", None), ("x86_64", None)]);
+        let apps = [slack, zoom];
+
+        let names = |target| {
+            resolve_target(target, &apps)
+                .iter()
+                .map(|app| app.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("com.tinyspeck.SlackMacGap"), ["Slack"]);
+        assert_eq!(names("/Applications/zoom.us.app"), ["zoom.us"]);
+        assert!(names("com.example.Missing").is_empty());
+        assert!(names("/Applications/Missing.app").is_empty());
+    }
+}
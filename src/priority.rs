@@ -0,0 +1,88 @@
+use serde::Deserialize;
+use tracing::warn;
+
+/// Scheduling priority of the scan and trim threads and the commands they run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Priority {
+    #[default]
+    Normal,
+    /// Background QoS on macOS (nice 10 elsewhere), so a scheduled run doesn't
+    /// compete with foreground work or keep the CPU awake on battery
+    Background,
+}
+
+/// Nice value used for [`Priority::Background`] where QoS classes don't exist.
+#[cfg(not(target_os = "macos"))]
+const BACKGROUND_NICE: libc::c_int = 10;
+
+impl Priority {
+    /// Applies the priority to the calling thread. Child processes it starts inherit it.
+    pub fn apply_to_current_thread(self) {
+        if self == Priority::Normal {
+            return;
+        }
+        if let Err(err) = lower_current_thread() {
+            warn!(%err, "failed to lower thread priority");
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn lower_current_thread() -> std::io::Result<()> {
+    let result =
+        unsafe { libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_BACKGROUND, 0) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::from_raw_os_error(result))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn lower_current_thread() -> std::io::Result<()> {
+    // On Linux the nice value is per thread when given a thread ID
+    let thread_id = unsafe { libc::gettid() } as libc::id_t;
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, thread_id, BACKGROUND_NICE) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn lower_current_thread() -> std::io::Result<()> {
+    // Lowers the whole process, the closest portable option
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICE) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            priority: Priority,
+        }
+        let wrapper: Wrapper = toml::from_str("priority = \"background\"").unwrap();
+        assert_eq!(wrapper.priority, Priority::Background);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_background_thread() {
+        let nice = std::thread::spawn(|| {
+            Priority::Background.apply_to_current_thread();
+            unsafe { libc::getpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t) }
+        })
+        .join()
+        .unwrap();
+        assert!(nice >= BACKGROUND_NICE);
+    }
+}
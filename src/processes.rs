@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::command;
+
+const PS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Executables of the processes running when a scan started. An app that runs
+/// while it's trimmed keeps its old binary until it's restarted.
+#[derive(Debug, Default)]
+pub struct RunningProcesses {
+    executables: Vec<PathBuf>,
+}
+
+impl RunningProcesses {
+    /// Lists the running processes with `ps`, which prints their full executable
+    /// paths on macOS.
+    pub fn load() -> Self {
+        let mut command = Command::new("ps");
+        command.arg("-axo").arg("comm=");
+        let executables = command::run(&mut command, None, PS_TIMEOUT)
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| parse(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default();
+        Self { executables }
+    }
+
+    /// Does a process run from inside `bundle`, such as its main binary or a helper?
+    pub fn in_bundle(&self, bundle: &Path) -> bool {
+        self.executables
+            .iter()
+            .any(|executable| executable.starts_with(bundle))
+    }
+}
+
+/// The absolute paths among the lines `ps -o comm=` printed.
+fn parse(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_bundle() {
+        let processes = RunningProcesses {
+            executables: parse(
+                "/sbin/launchd\n\
+                 (bash)\n\
+                 /Applications/Slack.app/Contents/Frameworks/Slack Helper.app/Contents/MacOS/Slack Helper\n",
+            ),
+        };
+        assert_eq!(processes.executables.len(), 2);
+        assert!(processes.in_bundle(Path::new("/Applications/Slack.app")));
+        assert!(!processes.in_bundle(Path::new("/Applications/Sla")));
+        assert!(!processes.in_bundle(Path::new("/Applications/zoom.us.app")));
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::AppInfo;
+
+/// Identifies an app across moves, e.g. from ~/Applications to /Applications: by
+/// its bundle identifier, or by its path if the bundle has none.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppKey {
+    BundleId(String),
+    Path(PathBuf),
+}
+
+impl AppKey {
+    pub fn of(app: &AppInfo) -> Self {
+        match &app.bundle_id {
+            Some(bundle_id) => AppKey::BundleId(bundle_id.clone()),
+            None => AppKey::Path(app.path.clone()),
+        }
+    }
+}
+
+/// The scanned apps by path and by [`AppKey`], so whatever is remembered about an
+/// app (the cursor, a trim in progress, its history) still finds it after it moved.
+/// Indices are only valid until the list is sorted or changed.
+#[derive(Debug, Default)]
+pub struct Registry {
+    by_path: HashMap<PathBuf, usize>,
+    by_key: HashMap<AppKey, Vec<usize>>,
+}
+
+impl Registry {
+    pub fn new(apps: &[AppInfo]) -> Self {
+        let mut registry = Registry::default();
+        for (index, app) in apps.iter().enumerate() {
+            registry.by_path.insert(app.path.clone(), index);
+            registry
+                .by_key
+                .entry(AppKey::of(app))
+                .or_default()
+                .push(index);
+        }
+        registry
+    }
+
+    /// Index of the app at `path`, or else of the only app with `key`. Copies of
+    /// an app that share a bundle identifier can only be told apart by path.
+    pub fn find(&self, path: &Path, key: Option<&AppKey>) -> Option<usize> {
+        self.by_path
+            .get(path)
+            .copied()
+            .or_else(|| match self.by_key.get(key?)?.as_slice() {
+                [index] => Some(*index),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", None)]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        let mut zoom = AppInfo::for_test("zoom.us", &[("arm64", None)]);
+        zoom.bundle_id = Some("us.zoom.xos".to_string());
+        let mut zoom_copy = zoom.clone();
+        zoom_copy.path = PathBuf::from("/Users/me/Applications/zoom.us.app");
+        let unnamed = AppInfo::for_test("Tool", &[("arm64", None)]);
+        let apps = [slack, zoom, zoom_copy, unnamed];
+        let registry = Registry::new(&apps);
+
+        // Slack moved from ~/Applications since the last session
+        let moved = Path::new("/Users/me/Applications/Slack.app");
+        assert_eq!(registry.find(moved, Some(&AppKey::of(&apps[0]))), Some(0));
+        assert_eq!(registry.find(moved, None), None);
+        assert_eq!(
+            registry.find(&apps[2].path, Some(&AppKey::of(&apps[1]))),
+            Some(2)
+        );
+        // Which copy of zoom.us moved can't be told
+        assert_eq!(registry.find(moved, Some(&AppKey::of(&apps[1]))), None);
+        assert_eq!(AppKey::of(&apps[3]), AppKey::Path(apps[3].path.clone()));
+    }
+}
@@ -0,0 +1,374 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::{WrapErr, bail};
+use serde::Serialize;
+
+use crate::bytesize::ByteSize;
+use crate::command;
+use crate::scanner::AppInfo;
+use crate::state;
+use crate::trimmer::TrimError;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many batch reports are kept in the state directory, the oldest are removed.
+const KEPT_BATCH_RECORDS: usize = 50;
+
+/// Where to deliver the report of a headless run, from `--report`/`--webhook` or
+/// the config.
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    /// Write the JSON report to this file, replacing it
+    pub path: Option<PathBuf>,
+    /// POST the JSON report to this URL
+    pub webhook: Option<String>,
+}
+
+impl ReportOptions {
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some() || self.webhook.is_some()
+    }
+}
+
+/// How long the parts of a run took, to spot performance regressions across runs
+/// and versions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Timings {
+    /// Finding and analyzing every bundle
+    pub scan_ms: u64,
+    /// The whole trim batch, zero if nothing was trimmed
+    pub trim_ms: u64,
+}
+
+/// Summary of a headless run for fleet tooling.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunReport {
+    pub version: &'static str,
+    pub host: String,
+    /// Seconds since the Unix epoch
+    pub finished_at: u64,
+    /// Bytes freed by the trimmed apps, as estimated while scanning
+    pub saved_bytes: ByteSize,
+    pub timings: Timings,
+    pub trimmed: Vec<TrimmedApp>,
+    pub failed: Vec<FailedApp>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrimmedApp {
+    pub name: String,
+    pub bundle_id: Option<String>,
+    pub path: PathBuf,
+    pub saved_bytes: ByteSize,
+    /// How long the scan took to analyze the app
+    pub analysis_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FailedApp {
+    pub name: String,
+    pub bundle_id: Option<String>,
+    pub path: PathBuf,
+    pub error: String,
+    /// Did it fail with a temporary error, so that trying again later may work?
+    pub transient: bool,
+    pub analysis_ms: u64,
+}
+
+impl RunReport {
+    /// Report of trimming `apps`, where the ones in `failures` didn't succeed.
+    pub fn new(apps: &[&AppInfo], failures: &[(&AppInfo, TrimError)], timings: Timings) -> Self {
+        let failed_paths: Vec<_> = failures.iter().map(|(app, _)| &app.path).collect();
+        let trimmed: Vec<_> = apps
+            .iter()
+            .filter(|app| !failed_paths.contains(&&app.path))
+            .map(|app| TrimmedApp {
+                name: app.name.clone(),
+                bundle_id: app.bundle_id.clone(),
+                path: app.path.clone(),
+                saved_bytes: app.prune_size().unwrap_or_default(),
+                analysis_ms: app.analysis_ms,
+            })
+            .collect();
+        let failed = failures
+            .iter()
+            .map(|(app, err)| FailedApp {
+                name: app.name.clone(),
+                bundle_id: app.bundle_id.clone(),
+                path: app.path.clone(),
+                error: err.to_string(),
+                transient: err.is_transient(),
+                analysis_ms: app.analysis_ms,
+            })
+            .collect();
+
+        RunReport {
+            version: env!("CARGO_PKG_VERSION"),
+            host: hostname().unwrap_or_default(),
+            finished_at: unix_time(),
+            saved_bytes: trimmed.iter().map(|app| app.saved_bytes).sum(),
+            timings,
+            trimmed,
+            failed,
+        }
+    }
+
+    /// Writes and sends the report wherever `options` say. Both are attempted even
+    /// if one fails, the first error is returned.
+    pub fn deliver(&self, options: &ReportOptions) -> color_eyre::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        let written = options
+            .path
+            .as_deref()
+            .map_or(Ok(()), |path| write(path, &json));
+        let posted = options
+            .webhook
+            .as_deref()
+            .map_or(Ok(()), |url| post(url, &json));
+        written.and(posted)
+    }
+}
+
+/// Everything one trim batch did, written as JSON after every batch for later
+/// analysis and support requests. Unlike [`RunReport`] it's written in every mode
+/// and lists each binary.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchRecord {
+    pub version: &'static str,
+    pub host: String,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    pub duration_ms: u64,
+    /// How long the scan that found the apps took
+    pub scan_duration_ms: u64,
+    pub apps: Vec<AppRecord>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AppRecord {
+    pub name: String,
+    pub bundle_id: Option<String>,
+    pub path: PathBuf,
+    /// Bytes the scan expected trimming to free
+    pub estimated_bytes: u64,
+    /// Bytes trimming actually freed
+    pub saved_bytes: u64,
+    /// How long the scan took to analyze the app
+    pub analysis_ms: u64,
+    /// From when the app was started until its last binary was done
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Did it fail with a temporary error, so that trying again later may work?
+    pub transient: bool,
+    pub binaries: Vec<BinaryRecord>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BinaryRecord {
+    pub path: PathBuf,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Could the binary's owner be restored after lipo replaced it?
+    pub owner_restored: bool,
+    /// From the previous step of the batch until the binary was done
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl BatchRecord {
+    /// An empty record of trimming `apps`, filled in as the batch goes.
+    pub fn new(apps: &[AppInfo]) -> Self {
+        BatchRecord {
+            version: env!("CARGO_PKG_VERSION"),
+            host: hostname().unwrap_or_default(),
+            started_at: unix_time(),
+            duration_ms: 0,
+            scan_duration_ms: 0,
+            apps: apps
+                .iter()
+                .map(|app| AppRecord {
+                    name: app.name.clone(),
+                    bundle_id: app.bundle_id.clone(),
+                    path: app.path.clone(),
+                    estimated_bytes: app.prune_size().unwrap_or_default().bytes(),
+                    analysis_ms: app.analysis_ms,
+                    ..AppRecord::default()
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the record to `reports/` in the state directory, unless `elevated`
+    /// since root would own the file, and to `out` if given. Both are attempted
+    /// even if one fails, the first error is returned.
+    pub fn save(&self, out: Option<&Path>, elevated: bool) -> color_eyre::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        let kept = match state::state_dir().filter(|_| !elevated) {
+            Some(dir) => {
+                let dir = dir.join("reports");
+                write(&dir.join(format!("batch-{}.json", self.started_at)), &json)
+                    .map(|()| prune_records(&dir))
+            }
+            None => Ok(()),
+        };
+        let written = out.map_or(Ok(()), |path| write(path, &json));
+        kept.and(written)
+    }
+}
+
+/// Removes all but the newest [`KEPT_BATCH_RECORDS`] batch reports in `dir`.
+fn prune_records(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut records: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("batch-") && name.ends_with(".json"))
+        })
+        .collect();
+    // The names hold the start time, so they sort oldest first
+    records.sort();
+    let excess = records.len().saturating_sub(KEPT_BATCH_RECORDS);
+    for path in &records[..excess] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// `duration` in whole milliseconds, as the reports store durations.
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+fn write(path: &Path, json: &str) -> color_eyre::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(path, json).wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+fn post(url: &str, json: &str) -> color_eyre::Result<()> {
+    let output = command::run(
+        Command::new("curl")
+            .arg("-fsS")
+            .arg("-X")
+            .arg("POST")
+            .arg("-H")
+            .arg("Content-Type: application/json")
+            .arg("--data-binary")
+            .arg("@-")
+            .arg(url),
+        Some(json),
+        WEBHOOK_TIMEOUT,
+    )
+    .wrap_err_with(|| format!("failed to POST to {}", url))?;
+    if !output.status.success() {
+        bail!(
+            "failed to POST to {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    if unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) } != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|&byte| byte == 0)?;
+    Some(String::from_utf8_lossy(&buffer[..length]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", None), ("x86_64", None)]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        slack.prune_bytes = Some(1000);
+        let mut zoom = AppInfo::for_test("zoom.us", &[("arm64", None), ("x86_64", None)]);
+        zoom.prune_bytes = Some(500);
+        let failures = [(&zoom, TrimError::Immutable)];
+
+        slack.analysis_ms = 120;
+        let timings = Timings {
+            scan_ms: 4000,
+            trim_ms: 900,
+        };
+        let report = RunReport::new(&[&slack, &zoom], &failures, timings);
+        assert_eq!(report.saved_bytes, ByteSize(1000));
+        assert_eq!(report.trimmed.len(), 1);
+        assert_eq!(report.failed[0].name, "zoom.us");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(json["trimmed"][0]["bundle-id"], "com.tinyspeck.slackmacgap");
+        assert_eq!(json["trimmed"][0]["saved-bytes"], 1000);
+        assert_eq!(json["trimmed"][0]["analysis-ms"], 120);
+        assert_eq!(json["timings"]["scan-ms"], 4000);
+        assert_eq!(json["timings"]["trim-ms"], 900);
+        assert!(json["failed"][0]["error"].is_string());
+        assert_eq!(json["failed"][0]["transient"], false);
+    }
+
+    #[test]
+    fn test_batch_record() {
+        let mut slack = AppInfo::for_test("Slack", &[("arm64", None), ("x86_64", None)]);
+        slack.prune_bytes = Some(1000);
+        let mut record = BatchRecord::new(&[slack]);
+        record.apps[0].binaries.push(BinaryRecord {
+            path: PathBuf::from("/Applications/Slack.app/Contents/MacOS/Slack"),
+            bytes_before: 3000,
+            bytes_after: 2000,
+            owner_restored: false,
+            duration_ms: 250,
+            error: None,
+        });
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&record).unwrap()).unwrap();
+        assert_eq!(json["apps"][0]["estimated-bytes"], 1000);
+        assert_eq!(json["apps"][0]["binaries"][0]["bytes-after"], 2000);
+        assert_eq!(json["apps"][0]["binaries"][0]["owner-restored"], false);
+        assert_eq!(json["apps"][0]["binaries"][0]["duration-ms"], 250);
+        assert!(json["apps"][0]["error"].is_null());
+
+        let dir = std::env::temp_dir().join(format!("bintrim-records-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for started_at in 0..KEPT_BATCH_RECORDS + 2 {
+            fs::write(dir.join(format!("batch-{:010}.json", started_at)), "{}").unwrap();
+        }
+        prune_records(&dir);
+        let oldest_kept = dir.join("batch-0000000002.json");
+        let count = fs::read_dir(&dir).unwrap().count();
+        let kept = oldest_kept.exists() && !dir.join("batch-0000000001.json").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(count, KEPT_BATCH_RECORDS);
+        assert!(kept);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+use crate::flags::FileFlags;
+use crate::scanner::{self, AppInfo};
+
+/// Share of uncompressed files that transparent APFS compression typically saves,
+/// as `ditto --hfsCompression` would apply it.
+const COMPRESSION_RATIO: f64 = 0.4;
+
+/// What trimming an app could free with every approach bintrim knows about. Only
+/// slices and nested binaries are removed today; languages and compression are
+/// estimates of what is left on the table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PotentialSavings {
+    /// The x86_64 slice of the main binary
+    pub slices: u64,
+    /// x86_64 slices of nested binaries that are trimmed with the app
+    pub nested: u64,
+    /// Localizations other than Base, English and the system language
+    pub languages: u64,
+    /// Estimated gain from compressing the files that aren't compressed yet
+    pub compression: u64,
+}
+
+impl PotentialSavings {
+    pub fn total(&self) -> u64 {
+        self.slices + self.nested + self.languages + self.compression
+    }
+}
+
+/// Estimates the potential savings of `app`, walking its whole bundle, and keeping
+/// the localizations in `keep_languages` (e.g. `de`, which also keeps `de_AT`).
+pub fn estimate(app: &AppInfo, keep_languages: &[String]) -> PotentialSavings {
+    let mut savings = PotentialSavings {
+        slices: scanner::trim_savings(&app.binary_path, &app.architectures, app.strategy)
+            .unwrap_or(0),
+        nested: app
+            .nested_binaries
+            .iter()
+            .filter_map(|binary| scanner::nested_savings(binary))
+            .sum(),
+        ..PotentialSavings::default()
+    };
+
+    let mut uncompressed = 0;
+    let mut pending = vec![(app.path.clone(), false)];
+    while let Some((dir, in_language)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                let removable = in_language || is_removable_language(&path, keep_languages);
+                pending.push((path, removable));
+            } else if file_type.is_file() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if in_language {
+                    savings.languages += metadata.len();
+                } else if !FileFlags::for_path(&path).is_some_and(|flags| flags.compressed()) {
+                    uncompressed += metadata.len();
+                }
+            }
+        }
+    }
+    savings.compression = (uncompressed as f64 * COMPRESSION_RATIO) as u64;
+    savings
+}
+
+/// Is `path` a `.lproj` directory of a language that isn't kept?
+fn is_removable_language(path: &Path, keep_languages: &[String]) -> bool {
+    if path
+        .extension()
+        .is_none_or(|extension| extension != "lproj")
+    {
+        return false;
+    }
+    let Some(name) = path
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+    else {
+        return false;
+    };
+    let language = name.split(['_', '-']).next().unwrap_or_default();
+    !["base", "en", "english"].contains(&language)
+        && !keep_languages
+            .iter()
+            .any(|keep| keep.eq_ignore_ascii_case(language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let root =
+            std::env::temp_dir().join(format!("bintrim-savings-estimate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut app = AppInfo::for_test("Slack", &[("arm64", None)]);
+        app.path = root.join("Slack.app");
+        app.binary_path = app.path.join("Contents/MacOS/Slack");
+        let resources = app.path.join("Contents/Resources");
+        for language in ["Base", "en", "de_AT", "fr", "zh-Hans"] {
+            fs::create_dir_all(resources.join(format!("{}.lproj", language))).unwrap();
+            fs::write(
+                resources.join(format!("{}.lproj/Localizable.strings", language)),
+                [0u8; 100],
+            )
+            .unwrap();
+        }
+        fs::write(resources.join("app.asar"), [0u8; 1000]).unwrap();
+
+        let savings = estimate(&app, &["de".to_string()]);
+        fs::remove_dir_all(&root).unwrap();
+
+        // fr and zh-Hans go, everything else may be compressed
+        assert_eq!(savings.languages, 200);
+        assert_eq!(savings.slices, 0);
+        assert_eq!(savings.compression, (1300.0 * COMPRESSION_RATIO) as u64);
+        assert_eq!(savings.total(), 200 + savings.compression);
+    }
+}
@@ -1,137 +1,1549 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+use tracing::{debug, info, trace, warn};
+
+use crate::alias;
+use crate::appex;
+use crate::arch::Arch;
+use crate::bytesize::ByteSize;
+use crate::cancel::CancelToken;
+use crate::command::{self, CommandError};
+use crate::denylist::Denylist;
+use crate::extensions;
+use crate::flags::{self, FileFlags};
+use crate::frameworks;
+use crate::helpers::{self, HELPERS_GROUP, Helper};
+use crate::history::TrimHistory;
+use crate::ignore::IgnorePattern;
+use crate::inspect::{self, Risk};
+use crate::jvm;
+use crate::location::{self, Location};
+use crate::macho::{self, FAT_MAGIC_64};
+use crate::managed::ManagedInventory;
+use crate::processes::RunningProcesses;
+use crate::report::millis;
+use crate::savings::{self, PotentialSavings};
+use crate::sharing::Sharing;
+use crate::tools;
+use crate::trimmer::{self, Strategy};
+use crate::vendor;
+use crate::volume::VolumeStats;
+use crate::xcode;
+
+/// Folders inside a scan root that third-party stores install their apps into.
+const STORE_FOLDERS: &[&str] = &["Setapp"];
+
+/// Size of `struct fat_header`.
+const FAT_HEADER_SIZE: u64 = 8;
+/// Size of `struct fat_arch` and `struct fat_arch_64`.
+const FAT_ARCH_SIZE: u64 = 20;
+const FAT_ARCH_64_SIZE: u64 = 32;
 
 #[derive(Debug, Clone)]
 pub struct ArchInfo {
-    pub cpu_type: String,
+    pub arch: Arch,
     pub size_bytes: Option<u64>,
+    /// Alignment of the slice in a fat file, in bytes
+    pub align: Option<u64>,
+}
+
+/// What identifies a binary's contents without reading them, to notice when an app
+/// was updated or replaced between scanning and trimming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryIdentity {
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    /// Updaters usually replace the file rather than rewrite it
+    pub inode: u64,
+}
+
+impl BinaryIdentity {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            inode: metadata.ino(),
+        })
+    }
+}
+
+/// Notable properties of a bundle that affect whether it may be trimmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    /// iOS/iPadOS app installed on Apple Silicon, which must never be modified
+    IosApp,
+    /// Installed by a management system (MDM, Munki, Jamf) that may reinstall it
+    Managed,
+    /// Installed from the Mac App Store
+    AppStore,
+    /// Installed through Setapp
+    Setapp,
+    /// Linked into a scan root from a read-only location
+    ReadOnly,
+    /// Hidden from the Finder with `chflags hidden`
+    Hidden,
+    /// The binary is locked with `chflags uchg` or `schg`
+    Immutable,
+    /// Set to `skip` in the config
+    Excluded,
+    /// Deep inspection found something that trimming may break
+    Risky,
+    /// Bundles Sparkle or Squirrel, whose next update restores the universal binary
+    AutoUpdates,
+    /// Xcode, whose toolchain is trimmed too while SDKs and simulators are kept
+    Xcode,
+    /// Runs Windows programs with Wine, which needs its x86 and x86_64 code
+    Wine,
+    /// The binary is hard-linked or an APFS clone, so trimming frees less
+    Shared,
+    /// A translocated or disk image copy rather than the installed app
+    Copy,
+    /// Running while scanned, so it keeps using the universal binary until restarted
+    Running,
+    /// bintrim trimmed it before, but it's universal again
+    TrimmedBefore,
+    /// System or security software on the built-in denylist
+    Protected,
+}
+
+impl Badge {
+    /// Every badge, in the order of the legend.
+    pub const ALL: [Badge; 17] = [
+        Badge::AppStore,
+        Badge::Managed,
+        Badge::Running,
+        Badge::AutoUpdates,
+        Badge::Risky,
+        Badge::Excluded,
+        Badge::TrimmedBefore,
+        Badge::Setapp,
+        Badge::Shared,
+        Badge::Xcode,
+        Badge::Hidden,
+        Badge::IosApp,
+        Badge::Protected,
+        Badge::ReadOnly,
+        Badge::Immutable,
+        Badge::Wine,
+        Badge::Copy,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Badge::IosApp => "iOS",
+            Badge::Managed => "managed",
+            Badge::AppStore => "MAS",
+            Badge::Setapp => "Setapp",
+            Badge::ReadOnly => "RO",
+            Badge::Hidden => "hidden",
+            Badge::Immutable => "locked",
+            Badge::Excluded => "skip",
+            Badge::Risky => "risky",
+            Badge::AutoUpdates => "updater",
+            Badge::Xcode => "Xcode",
+            Badge::Wine => "Wine",
+            Badge::Shared => "shared",
+            Badge::Copy => "copy",
+            Badge::Running => "running",
+            Badge::TrimmedBefore => "trimmed",
+            Badge::Protected => "protected",
+        }
+    }
+
+    /// One character for the badges column, explained in the legend (`?`). ASCII,
+    /// so it looks the same with `--ascii`.
+    pub fn symbol(&self) -> char {
+        match self {
+            Badge::IosApp => 'i',
+            Badge::Managed => 'M',
+            Badge::AppStore => 'A',
+            Badge::Setapp => 'S',
+            Badge::ReadOnly => 'R',
+            Badge::Hidden => 'h',
+            Badge::Immutable => 'L',
+            Badge::Excluded => 'E',
+            Badge::Risky => '!',
+            Badge::AutoUpdates => 'U',
+            Badge::Xcode => 'X',
+            Badge::Wine => 'W',
+            Badge::Shared => '=',
+            Badge::Copy => 'C',
+            Badge::Running => '*',
+            Badge::TrimmedBefore => 'T',
+            Badge::Protected => 'P',
+        }
+    }
+
+    /// Does this badge prevent the app from being trimmed?
+    pub fn blocks_trim(&self) -> bool {
+        match self {
+            Badge::IosApp
+            | Badge::ReadOnly
+            | Badge::Immutable
+            | Badge::Excluded
+            | Badge::Wine
+            | Badge::Copy
+            | Badge::Protected => true,
+            Badge::Managed
+            | Badge::AppStore
+            | Badge::Setapp
+            | Badge::Hidden
+            | Badge::Risky
+            | Badge::AutoUpdates
+            | Badge::Xcode
+            | Badge::Shared
+            | Badge::Running
+            | Badge::TrimmedBefore => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AppInfo {
     pub name: String,
-    #[allow(dead_code)]
     pub path: PathBuf,
     #[allow(dead_code)]
     pub binary_path: PathBuf,
     pub architectures: Vec<ArchInfo>,
     pub selected: bool,
+    /// Label of the scan root this app was found under, if it isn't /Applications
+    pub group: Option<String>,
+    pub badges: Vec<Badge>,
+    /// CFBundleIdentifier from the bundle's Info.plist
+    pub bundle_id: Option<String>,
+    /// Symlink or Finder alias in the scan root that led to the bundle at `path`
+    pub link_path: Option<PathBuf>,
+    /// Bytes freed by trimming, including header and alignment padding
+    pub prune_bytes: Option<u64>,
+    /// How the app will be trimmed
+    pub strategy: Strategy,
+    /// Developer from the code signature or bundle identifier
+    pub vendor: Option<String>,
+    /// LSApplicationCategoryType, such as `public.app-category.developer-tools`
+    pub category: Option<String>,
+    /// The main binary as it was when scanned
+    pub binary_identity: Option<BinaryIdentity>,
+    /// What deep inspection found, empty unless it was enabled
+    pub risks: Vec<Risk>,
+    /// Set for helper daemons and launch agents, whose `path` is the binary itself
+    pub helper: Option<Helper>,
+    /// Savings with every approach, including the ones bintrim can't apply yet
+    pub potential: Option<PotentialSavings>,
+    /// Binaries inside the bundle that are trimmed along with the main one: those
+    /// of bundled Java runtimes, Xcode's toolchain and, with deep inspection,
+    /// native Python and Node extensions
+    pub nested_binaries: Vec<PathBuf>,
+    /// How long analyzing the bundle took, in milliseconds
+    pub analysis_ms: u64,
 }
 
 impl AppInfo {
     pub fn has_x86_64(&self) -> bool {
         self.architectures
             .iter()
-            .any(|arch| arch.cpu_type == "x86_64")
+            .any(|arch| arch.arch == Arch::X86_64)
     }
 
     pub fn has_arm64(&self) -> bool {
-        self.architectures
-            .iter()
-            .any(|arch| arch.cpu_type.starts_with("arm64"))
+        self.architectures.iter().any(|arch| arch.arch.is_arm64())
     }
 
-    pub fn x86_64_size_mb(&self) -> Option<f64> {
-        self.architectures
-            .iter()
-            .find(|arch| arch.cpu_type == "x86_64")
-            .and_then(|arch| arch.size_bytes.map(|size| size as f64 / 1024.0 / 1024.0))
+    /// Can this app be selected for trimming?
+    pub fn is_prunable(&self) -> bool {
+        self.has_x86_64()
+            && !self.badges.iter().any(Badge::blocks_trim)
+            // Nothing left to trim once the nested binaries are thin
+            && (self.strategy != Strategy::NestedOnly || self.prune_bytes.is_some())
+    }
+
+    /// Space freed by trimming, falling back to the x86_64 slice size if the fat
+    /// layout couldn't be read.
+    pub fn prune_size(&self) -> Option<ByteSize> {
+        self.prune_bytes
+            .or_else(|| {
+                self.architectures
+                    .iter()
+                    .find(|arch| arch.arch == Arch::X86_64)
+                    .and_then(|arch| arch.size_bytes)
+            })
+            .map(ByteSize)
     }
 
     pub fn architectures_display(&self) -> String {
         self.architectures
             .iter()
-            .map(|arch| arch.cpu_type.as_str())
+            .map(|arch| arch.arch.as_str())
             .collect::<Vec<_>>()
             .join(", ")
     }
+
+    /// Size of each slice, arm64 first and x86_64 next so the slice that stays can be
+    /// compared with the one that goes, e.g. `arm64 58.20 MB · x86_64 61.00 MB`.
+    pub fn slices_display(&self, not_available: &str) -> String {
+        let rank = |arch: &&ArchInfo| match arch.arch {
+            Arch::Arm64 => 0,
+            Arch::X86_64 => 1,
+            _ => 2,
+        };
+        let mut slices: Vec<_> = self.architectures.iter().collect();
+        slices.sort_by_key(rank);
+        slices
+            .iter()
+            .map(|arch| {
+                let size = arch.size_bytes.map_or_else(
+                    || not_available.to_string(),
+                    |size| ByteSize(size).to_string(),
+                );
+                format!("{} {}", arch.arch, size)
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+#[cfg(test)]
+impl AppInfo {
+    /// Builds an app located in /Applications with the given architecture slices.
+    pub fn for_test(name: &str, architectures: &[(&str, Option<u64>)]) -> Self {
+        let path = PathBuf::from(format!("/Applications/{}.app", name));
+        Self {
+            name: name.to_string(),
+            binary_path: path.join("Contents").join("MacOS").join(name),
+            path,
+            architectures: architectures
+                .iter()
+                .map(|(arch, size_bytes)| ArchInfo {
+                    arch: Arch::from_name(arch),
+                    size_bytes: *size_bytes,
+                    align: None,
+                })
+                .collect(),
+            selected: false,
+            group: None,
+            badges: Vec::new(),
+            bundle_id: None,
+            link_path: None,
+            prune_bytes: None,
+            strategy: Strategy::default(),
+            vendor: None,
+            category: None,
+            binary_identity: None,
+            risks: Vec::new(),
+            helper: None,
+            nested_binaries: Vec::new(),
+            potential: None,
+            analysis_ms: 0,
+        }
+    }
+}
+
+/// A directory that is searched for `.app` bundles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanRoot {
+    pub path: PathBuf,
+    /// Label used to group apps found under this root, `None` for /Applications
+    pub group: Option<String>,
+    /// Volume information, only collected for external volumes
+    pub volume: Option<VolumeStats>,
 }
 
-pub fn scan_applications_with_progress<F>(mut progress_callback: F) -> Vec<AppInfo>
+impl ScanRoot {
+    /// The system-wide /Applications folder.
+    pub fn system() -> Self {
+        Self {
+            path: PathBuf::from("/Applications"),
+            group: None,
+            volume: None,
+        }
+    }
+}
+
+/// Returns a scan root for every local user that has an `~/Applications` folder.
+pub fn user_roots() -> Vec<ScanRoot> {
+    user_roots_in(Path::new("/Users"))
+}
+
+fn user_roots_in(users_dir: &Path) -> Vec<ScanRoot> {
+    let mut roots = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(users_dir) {
+        for entry in entries.flatten() {
+            let user = entry.file_name().to_string_lossy().to_string();
+
+            // Skip the Shared folder and hidden entries such as .localized
+            if user == "Shared" || user.starts_with('.') {
+                continue;
+            }
+
+            let apps_dir = entry.path().join("Applications");
+            if apps_dir.is_dir() {
+                roots.push(ScanRoot {
+                    path: apps_dir,
+                    group: Some(format!("~{}/Applications", user)),
+                    volume: None,
+                });
+            }
+        }
+    }
+
+    roots.sort_by(|a, b| a.group.cmp(&b.group));
+    roots
+}
+
+/// Returns a scan root for every mounted volume that has an `Applications` folder.
+///
+/// The boot volume (which shows up as a symlink to `/`) and read-only volumes such as
+/// mounted disk images are skipped, since their apps can't be trimmed.
+pub fn volume_roots() -> Vec<ScanRoot> {
+    let mut roots = Vec::new();
+
+    if let Ok(entries) = fs::read_dir("/Volumes") {
+        for entry in entries.flatten() {
+            let volume_path = entry.path();
+
+            if fs::canonicalize(&volume_path).is_ok_and(|path| path == Path::new("/")) {
+                continue;
+            }
+
+            let apps_dir = volume_path.join("Applications");
+            if !apps_dir.is_dir() {
+                continue;
+            }
+
+            let Some(volume) = VolumeStats::for_path(&apps_dir) else {
+                continue;
+            };
+            if volume.read_only {
+                continue;
+            }
+
+            roots.push(ScanRoot {
+                path: apps_dir.clone(),
+                group: Some(apps_dir.to_string_lossy().to_string()),
+                volume: Some(volume),
+            });
+        }
+    }
+
+    roots.sort_by(|a, b| a.group.cmp(&b.group));
+    roots
+}
+
+/// Returns a scan root for every build configuration of every project in the
+/// user's Xcode DerivedData, such as `MyApp-abcdef/Build/Products/Release`.
+pub fn derived_data_roots() -> Vec<ScanRoot> {
+    std::env::var_os("HOME")
+        .map(|home| {
+            derived_data_roots_in(&PathBuf::from(home).join("Library/Developer/Xcode/DerivedData"))
+        })
+        .unwrap_or_default()
+}
+
+fn derived_data_roots_in(derived_data: &Path) -> Vec<ScanRoot> {
+    let mut roots = Vec::new();
+
+    for project in fs::read_dir(derived_data).into_iter().flatten().flatten() {
+        let dir_name = project.file_name().to_string_lossy().to_string();
+        // Xcode appends a hash of the project's path to its name
+        let project_name = dir_name
+            .rsplit_once('-')
+            .map_or(dir_name.as_str(), |(name, _)| name);
+        let products = project.path().join("Build/Products");
+        for configuration in fs::read_dir(&products).into_iter().flatten().flatten() {
+            if !configuration.path().is_dir() {
+                continue;
+            }
+            roots.push(ScanRoot {
+                path: configuration.path(),
+                group: Some(format!(
+                    "DerivedData: {} ({})",
+                    project_name,
+                    configuration.file_name().to_string_lossy()
+                )),
+                volume: None,
+            });
+        }
+    }
+
+    roots.sort_by(|a, b| a.group.cmp(&b.group));
+    roots
+}
+
+/// Group of the apps found in game libraries.
+pub const GAMES_GROUP: &str = "Games";
+
+/// Where Steam installs games unless another library folder was added.
+pub fn steam_library() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library/Application Support/Steam/steamapps/common"))
+}
+
+/// Returns a scan root for every game in a library folder such as Steam's
+/// `steamapps/common`, which has a folder per game with the app inside.
+pub fn game_roots(library: &Path) -> Vec<ScanRoot> {
+    let mut entries: Vec<_> = fs::read_dir(library)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    let root = |path: &Path| ScanRoot {
+        path: path.to_path_buf(),
+        group: Some(GAMES_GROUP.to_string()),
+        volume: None,
+    };
+
+    let mut roots: Vec<_> = entries
+        .iter()
+        .filter(|path| path.is_dir() && !is_app_bundle(path))
+        .map(|path| root(path))
+        .collect();
+    // Some games are installed straight into the library folder
+    if entries.iter().any(|path| is_app_bundle(path)) {
+        roots.insert(0, root(library));
+    }
+    roots
+}
+
+/// A scan root for a folder of build products, such as a project's `build` folder.
+pub fn build_root(path: PathBuf) -> ScanRoot {
+    ScanRoot {
+        group: Some(path.to_string_lossy().to_string()),
+        path,
+        volume: None,
+    }
+}
+
+/// Default time a single `lipo` call may take while scanning.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where and how applications are scanned.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Directories searched for applications
+    pub roots: Vec<ScanRoot>,
+    /// Maximum time for each external command
+    pub command_timeout: Duration,
+    /// Trim strategies by bundle identifier, for apps that shouldn't use the default
+    pub strategies: HashMap<String, Strategy>,
+    /// Bundles to leave out of the scan entirely
+    pub ignore: Vec<IgnorePattern>,
+    /// Bundles that are listed but never trimmed
+    pub denylist: Denylist,
+    /// Inspect load commands and code signatures for risks, see [`inspect::inspect`]
+    pub deep: bool,
+    /// Also list helper daemons and launch agents, see [`helpers::find_helpers`]
+    pub helpers: bool,
+    /// Localizations that count as needed when estimating potential savings
+    pub keep_languages: Vec<String>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            roots: vec![ScanRoot::system()],
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            strategies: HashMap::new(),
+            ignore: Vec::new(),
+            denylist: Denylist::default(),
+            deep: false,
+            helpers: false,
+            keep_languages: Vec::new(),
+        }
+    }
+}
+
+/// Why a bundle couldn't be analyzed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    /// The bundle has no `Contents/MacOS` directory
+    NoMacOSDir,
+    /// No executable was found in the bundle
+    NoExecutable,
+    /// The executable isn't a Mach-O binary (e.g. a shell script)
+    NotMachO,
+    /// `lipo` exited with an error
+    LipoFailed { stderr: String },
+    /// Reading the bundle or running `lipo` failed
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+    /// `lipo` didn't finish in time
+    TimedOut(Duration),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::NoMacOSDir => write!(f, "no Contents/MacOS directory"),
+            ScanError::NoExecutable => write!(f, "no executable found"),
+            ScanError::NotMachO => write!(f, "executable is not a Mach-O binary"),
+            ScanError::LipoFailed { stderr } => write!(f, "lipo failed: {}", stderr),
+            ScanError::Io { message, .. } => write!(f, "{}", message),
+            ScanError::TimedOut(timeout) => {
+                write!(f, "lipo timed out after {}s", timeout.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+impl From<CommandError> for ScanError {
+    fn from(err: CommandError) -> Self {
+        match err {
+            CommandError::Io(err) => err.into(),
+            CommandError::TimedOut(timeout) => ScanError::TimedOut(timeout),
+        }
+    }
+}
+
+impl From<io::Error> for ScanError {
+    fn from(err: io::Error) -> Self {
+        ScanError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Why a bundle found during a scan is not part of the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The bundle couldn't be analyzed
+    Error(ScanError),
+    /// The binary has no arm64 slice, so there is nothing left to keep after trimming
+    NoArm64,
+    /// The same bundle was already found at this path, e.g. through a symlinked folder
+    Duplicate(PathBuf),
+    /// Matched an ignore pattern from the config or `--ignore`
+    Ignored,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Error(err) => err.fmt(f),
+            SkipReason::NoArm64 => write!(f, "no arm64 slice"),
+            SkipReason::Duplicate(path) => write!(f, "same bundle as {}", path.display()),
+            SkipReason::Ignored => write!(f, "ignored by pattern"),
+        }
+    }
+}
+
+/// Events emitted while scanning, in order: `Started`, then for every bundle a
+/// `BundleFound` followed by either `BundleAnalyzed` or `BundleSkipped`, then `Finished`.
+///
+/// Bundles are found before any is analyzed, so `total` counts bundles and
+/// `total_bytes` their main binaries. `bytes` is the size of the bundle's main
+/// binary, which lets progress follow the work instead of the bundle count.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    Started {
+        total: usize,
+        total_bytes: u64,
+    },
+    BundleFound {
+        index: usize,
+        name: String,
+        bytes: u64,
+    },
+    BundleAnalyzed {
+        name: String,
+    },
+    BundleSkipped {
+        name: String,
+        reason: SkipReason,
+    },
+    Finished {
+        analyzed: usize,
+        skipped: usize,
+        duration: Duration,
+    },
+}
+
+/// Running totals built from [`ScanEvent`]s, for front-ends that only need counts.
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub current: usize,
+    pub total: usize,
+    pub current_name: String,
+    /// When analyzing the current bundle started
+    pub current_started: Option<Instant>,
+    /// Size of the current bundle's main binary
+    pub current_bytes: u64,
+    /// Binary bytes of all bundles finished so far
+    pub done_bytes: u64,
+    pub total_bytes: u64,
+    pub analyzed: usize,
+    pub skipped: Vec<(String, SkipReason)>,
+    pub finished: bool,
+    /// How long the scan took, once it's finished
+    pub duration: Duration,
+}
+
+impl ScanProgress {
+    pub fn apply(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Started { total, total_bytes } => {
+                *self = Self {
+                    total,
+                    total_bytes,
+                    ..Self::default()
+                };
+            }
+            ScanEvent::BundleFound { index, name, bytes } => {
+                self.current = index;
+                self.current_name = name;
+                self.current_started = Some(Instant::now());
+                self.current_bytes = bytes;
+            }
+            ScanEvent::BundleAnalyzed { .. } => {
+                self.analyzed += 1;
+                self.done_bytes += self.current_bytes;
+            }
+            ScanEvent::BundleSkipped { name, reason } => {
+                self.skipped.push((name, reason));
+                self.done_bytes += self.current_bytes;
+            }
+            ScanEvent::Finished { duration, .. } => {
+                self.duration = duration;
+                self.current = self.total;
+                self.done_bytes = self.total_bytes;
+                self.current_started = None;
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Share of the scan that is done, weighted by binary size so one giant app
+    /// doesn't stall the gauge. Falls back to counting bundles.
+    pub fn ratio(&self) -> f64 {
+        if self.total_bytes > 0 {
+            self.done_bytes as f64 / self.total_bytes as f64
+        } else if self.total > 0 {
+            // `current` is still being analyzed
+            self.current.saturating_sub(1) as f64 / self.total as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Number of skipped bundles that couldn't be analyzed, as opposed to
+    /// being skipped for lacking an arm64 slice.
+    pub fn error_count(&self) -> usize {
+        self.skipped
+            .iter()
+            .filter(|(_, reason)| matches!(reason, SkipReason::Error(_)))
+            .count()
+    }
+}
+
+/// Scans the roots and helpers of `options`, reporting each bundle to `on_event`.
+/// Once `cancel` is cancelled, the scan stops before the next bundle and returns
+/// the apps analyzed so far.
+pub fn scan_applications_with_progress<F>(
+    options: &ScanOptions,
+    cancel: &CancelToken,
+    mut on_event: F,
+) -> Vec<AppInfo>
 where
-    F: FnMut(usize, usize, &str),
+    F: FnMut(ScanEvent),
 {
-    let apps_dir = Path::new("/Applications");
+    let started = Instant::now();
     let mut apps = Vec::new();
+    let mut skipped = 0;
+    let context = ScanContext::load();
+    // Device and inode of every bundle seen so far, to catch bundles reachable twice
+    let mut seen: HashMap<(u64, u64), PathBuf> = HashMap::new();
 
-    if let Ok(entries) = fs::read_dir(apps_dir) {
-        let entries: Vec<_> = entries.flatten().collect();
-        let total = entries.len();
+    let roots = &options.roots;
+    let bundles: Vec<_> = roots
+        .iter()
+        .flat_map(|root| {
+            root_entries(&root.path)
+                .iter()
+                .filter_map(bundle_for_entry)
+                .map(|(path, link_path)| {
+                    let bytes = main_binary_size(&path);
+                    (root, path, link_path, bytes)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let found_helpers = if options.helpers {
+        helpers::find_helpers()
+    } else {
+        Vec::new()
+    };
+    debug!(roots = ?roots.iter().map(|root| &root.path).collect::<Vec<_>>(), helpers = found_helpers.len(), "scanning");
+    on_event(ScanEvent::Started {
+        total: bundles.len() + found_helpers.len(),
+        total_bytes: bundles.iter().map(|(.., bytes)| bytes).sum::<u64>()
+            + found_helpers
+                .iter()
+                .map(|helper| file_size(&helper.binary_path))
+                .sum::<u64>(),
+    });
+    let bundle_count = bundles.len();
 
-        for (index, entry) in entries.iter().enumerate() {
-            if let Ok(file_type) = entry.file_type() {
-                let path = entry.path();
+    for (index, (root, path, link_path, bytes)) in bundles.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            info!(analyzed = apps.len(), "scan cancelled");
+            break;
+        }
+        let app_name = link_path
+            .as_deref()
+            .unwrap_or(&path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
 
-                // Check if it's an .app bundle
-                if file_type.is_dir() && path.extension().and_then(|s| s.to_str()) == Some("app") {
-                    let app_name = path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("Unknown");
+        trace!(path = %path.display(), link = ?link_path, "analyzing bundle");
+        on_event(ScanEvent::BundleFound {
+            index: index + 1,
+            name: app_name.clone(),
+            bytes,
+        });
 
-                    progress_callback(index + 1, total, app_name);
+        let first_path = fs::metadata(&path)
+            .ok()
+            .and_then(|metadata| seen.insert((metadata.dev(), metadata.ino()), path.clone()));
 
-                    if let Some(app_info) = analyze_app(&path) {
-                        // Only include apps that have arm64 architecture
-                        if app_info.has_arm64() {
-                            apps.push(app_info);
-                        }
-                    }
-                }
+        let analysis_started = Instant::now();
+        let result = match first_path {
+            Some(first_path) => Err(SkipReason::Duplicate(first_path)),
+            None => analyze_bundle(&path, root.group.clone(), link_path, options, &context),
+        };
+        let result = with_analysis_time(result, analysis_started);
+        let reason = match result {
+            Ok(app_info) => {
+                debug!(
+                    app = %app_name,
+                    architectures = %app_info.architectures_display(),
+                    "analyzed bundle"
+                );
+                apps.push(app_info);
+                on_event(ScanEvent::BundleAnalyzed { name: app_name });
+                continue;
+            }
+            Err(reason) => reason,
+        };
+
+        skipped += 1;
+        debug!(app = %app_name, %reason, "skipped bundle");
+        on_event(ScanEvent::BundleSkipped {
+            name: app_name,
+            reason,
+        });
+    }
+
+    // Listed helpers include the privileged tools, which are trimmed on their own then
+    if !options.helpers {
+        attach_privileged_tools(&mut apps, helpers::privileged_tools(), options);
+    }
+
+    // Helpers come last, once the apps they may belong to are known
+    let mut helper_apps = Vec::new();
+    for (index, mut helper) in found_helpers.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        on_event(ScanEvent::BundleFound {
+            index: bundle_count + index + 1,
+            name: helper.label.clone(),
+            bytes: file_size(&helper.binary_path),
+        });
+        let analysis_started = Instant::now();
+        let result = match apps
+            .iter()
+            .find(|app: &&AppInfo| app.binary_path == helper.binary_path)
+        {
+            // Launch agents that run an app's main binary are trimmed with the app
+            Some(app) => Err(SkipReason::Duplicate(app.path.clone())),
+            None => {
+                helper.parent = helpers::parent_app(&helper, &apps).map(|app| app.name.clone());
+                analyze_helper(helper.clone(), options)
+            }
+        };
+        match with_analysis_time(result, analysis_started) {
+            Ok(app_info) => {
+                debug!(helper = %helper.label, parent = ?helper.parent, "analyzed helper");
+                helper_apps.push(app_info);
+                on_event(ScanEvent::BundleAnalyzed { name: helper.label });
+            }
+            Err(reason) => {
+                skipped += 1;
+                debug!(helper = %helper.label, %reason, "skipped helper");
+                on_event(ScanEvent::BundleSkipped {
+                    name: helper.label,
+                    reason,
+                });
             }
         }
     }
+    apps.extend(helper_apps);
+
+    let duration = started.elapsed();
+    info!(analyzed = apps.len(), skipped, ?duration, "scan finished");
+    on_event(ScanEvent::Finished {
+        analyzed: apps.len(),
+        skipped,
+        duration,
+    });
 
     // Sort by name
     apps.sort_by(|a, b| a.name.cmp(&b.name));
     apps
 }
 
-fn analyze_app(app_path: &Path) -> Option<AppInfo> {
-    let app_name = app_path.file_stem()?.to_str()?.to_string();
+/// Sets how long analyzing took on an analyzed app.
+fn with_analysis_time(
+    result: Result<AppInfo, SkipReason>,
+    started: Instant,
+) -> Result<AppInfo, SkipReason> {
+    result.map(|mut app| {
+        app.analysis_ms = millis(started.elapsed());
+        app
+    })
+}
 
-    // Find the binary inside Contents/MacOS/
-    let macos_dir = app_path.join("Contents").join("MacOS");
+/// What a scan looks up once and checks every bundle against.
+struct ScanContext {
+    managed: ManagedInventory,
+    running: RunningProcesses,
+    history: TrimHistory,
+}
 
-    if !macos_dir.exists() {
-        return None;
+impl ScanContext {
+    fn load() -> Self {
+        Self {
+            managed: ManagedInventory::load(),
+            running: RunningProcesses::load(),
+            history: TrimHistory::load(),
+        }
     }
+}
 
-    // Try to find the main binary (usually named the same as the app)
-    let mut binary_path = macos_dir.join(&app_name);
+/// Analyzes the bundle at `path`, found in a scan root with `group` (through
+/// `link_path` if it was a link), and applies the badges and settings that depend
+/// on where it is and the config.
+fn analyze_bundle(
+    path: &Path,
+    group: Option<String>,
+    link_path: Option<PathBuf>,
+    options: &ScanOptions,
+    context: &ScanContext,
+) -> Result<AppInfo, SkipReason> {
+    // Path patterns are checked first to save analyzing the bundle
+    if is_ignored(&options.ignore, path, link_path.as_deref(), None) {
+        return Err(SkipReason::Ignored);
+    }
+    let mut app_info = analyze_app(path, options.command_timeout).map_err(SkipReason::Error)?;
+    if is_ignored(
+        &options.ignore,
+        path,
+        link_path.as_deref(),
+        app_info.bundle_id.as_deref(),
+    ) {
+        return Err(SkipReason::Ignored);
+    }
+    // Only include apps that have arm64 architecture
+    if !app_info.has_arm64() {
+        return Err(SkipReason::NoArm64);
+    }
 
-    // If that doesn't exist, try to find any executable in MacOS directory
-    if !binary_path.exists()
-        && let Ok(entries) = fs::read_dir(&macos_dir)
+    app_info.group = group;
+    if link_path.is_some() && VolumeStats::for_path(path).is_some_and(|volume| volume.read_only) {
+        app_info.badges.push(Badge::ReadOnly);
+    }
+    app_info.link_path = link_path;
+    match Location::of(path) {
+        Location::Installed => {}
+        location => {
+            warn!(app = %path.display(), ?location, "not the installed copy of the app");
+            app_info.badges.push(Badge::Copy);
+        }
+    }
+    if FileFlags::for_path(path).is_some_and(|flags| flags.hidden()) {
+        app_info.badges.push(Badge::Hidden);
+    }
+    if flags::is_immutable(&app_info.binary_path) {
+        app_info.badges.push(Badge::Immutable);
+    }
+    if options
+        .denylist
+        .denies(&app_info.path, app_info.bundle_id.as_deref())
     {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                // Check if file is executable
-                if is_executable(&path) {
-                    binary_path = path;
-                    break;
-                }
+        app_info.badges.push(Badge::Protected);
+    }
+    if context.managed.is_managed(&app_info) {
+        app_info.badges.push(Badge::Managed);
+    }
+    if context.running.in_bundle(&app_info.path) {
+        app_info.badges.push(Badge::Running);
+    }
+    // Universal again after bintrim trimmed it, most likely through an update
+    if app_info.has_x86_64() && context.history.contains(&app_info.path) {
+        app_info.badges.push(Badge::TrimmedBefore);
+    }
+    let configured = app_info
+        .bundle_id
+        .as_ref()
+        .and_then(|bundle_id| options.strategies.get(bundle_id));
+    // Trimming breaks emulation unless the user asked for a strategy explicitly
+    if configured.is_none() && is_wine_bundle(&app_info.path, app_info.bundle_id.as_deref()) {
+        app_info.badges.push(Badge::Wine);
+    }
+    if let Some(&strategy) = configured {
+        app_info.strategy = strategy;
+        app_info.prune_bytes =
+            trim_savings(&app_info.binary_path, &app_info.architectures, strategy);
+        if strategy == Strategy::Skip {
+            app_info.badges.push(Badge::Excluded);
+        }
+    }
+    let is_xcode = xcode::is_xcode(app_info.bundle_id.as_deref());
+    if is_xcode {
+        app_info.badges.push(Badge::Xcode);
+    }
+    if app_info.strategy != Strategy::Skip {
+        let mut nested = jvm::fat_binaries(&app_info.path);
+        nested.extend(appex::fat_binaries(&app_info.path));
+        if is_xcode {
+            nested.extend(xcode::plan(&app_info.path).binaries);
+        }
+        if options.deep {
+            nested.extend(extensions::fat_extensions(&app_info.path));
+        }
+        if app_info.strategy == Strategy::NestedOnly {
+            nested.extend(frameworks::fat_binaries(&app_info.path));
+        }
+        if !nested.is_empty() {
+            let savings: u64 = nested.iter().map(|(_, bytes)| bytes).sum();
+            app_info.prune_bytes = Some(app_info.prune_bytes.unwrap_or(0) + savings);
+        }
+        app_info.nested_binaries = nested.into_iter().map(|(path, _)| path).collect();
+    }
+    app_info.potential = Some(savings::estimate(&app_info, &options.keep_languages));
+    if options.deep && app_info.is_prunable() {
+        app_info.risks = inspect::inspect(
+            &app_info.binary_path,
+            &app_info.architectures,
+            options.command_timeout,
+        );
+        if !app_info.risks.is_empty() {
+            app_info.badges.push(Badge::Risky);
+        }
+    }
+    Ok(app_info)
+}
+
+/// Adds each universal privileged helper tool to the trim plan of the app it belongs
+/// to, see [`helpers::parent_app`]. They run as root and need sudo to trim like the
+/// app itself, and some vendors install several hundred MB of them.
+fn attach_privileged_tools(apps: &mut [AppInfo], tools: Vec<Helper>, options: &ScanOptions) {
+    for tool in tools {
+        if is_ignored(&options.ignore, &tool.binary_path, None, Some(&tool.label)) {
+            continue;
+        }
+        let Some(index) = helpers::parent_app(&tool, apps)
+            .map(|parent| parent.path.clone())
+            .and_then(|path| apps.iter().position(|app| app.path == path))
+        else {
+            continue;
+        };
+        let parent = &mut apps[index];
+        if parent.strategy == Strategy::Skip {
+            continue;
+        }
+        if let Some(savings) = nested_savings(&tool.binary_path) {
+            debug!(tool = %tool.label, app = %parent.name, savings, "attached privileged helper tool");
+            parent.prune_bytes = Some(parent.prune_bytes.unwrap_or(0) + savings);
+            parent.nested_binaries.push(tool.binary_path);
+            parent.potential = Some(savings::estimate(parent, &options.keep_languages));
+        }
+    }
+}
+
+/// Analyzes a helper's binary like the main binary of an app, listing it under
+/// [`HELPERS_GROUP`] with its label as the bundle identifier.
+fn analyze_helper(helper: Helper, options: &ScanOptions) -> Result<AppInfo, SkipReason> {
+    let binary_path = helper.binary_path.clone();
+    if is_ignored(&options.ignore, &binary_path, None, Some(&helper.label)) {
+        return Err(SkipReason::Ignored);
+    }
+    let binary_identity = BinaryIdentity::of(&binary_path);
+    let architectures =
+        extract_architectures(&binary_path, options.command_timeout).map_err(SkipReason::Error)?;
+    let mut app_info = AppInfo {
+        name: helper.label.clone(),
+        path: binary_path.clone(),
+        prune_bytes: trim_savings(&binary_path, &architectures, Strategy::default()),
+        binary_path,
+        architectures,
+        selected: false,
+        group: Some(HELPERS_GROUP.to_string()),
+        badges: Vec::new(),
+        bundle_id: Some(helper.label.clone()),
+        link_path: None,
+        strategy: Strategy::default(),
+        vendor: None,
+        category: None,
+        binary_identity,
+        risks: Vec::new(),
+        helper: Some(helper),
+        nested_binaries: Vec::new(),
+        potential: None,
+        analysis_ms: 0,
+    };
+    if !app_info.has_arm64() {
+        return Err(SkipReason::NoArm64);
+    }
+    if flags::is_immutable(&app_info.binary_path) {
+        app_info.badges.push(Badge::Immutable);
+    }
+    if options
+        .denylist
+        .denies(&app_info.path, Some(&app_info.name))
+    {
+        app_info.badges.push(Badge::Protected);
+    }
+    if let Some(&strategy) = options.strategies.get(&app_info.name) {
+        app_info.strategy = strategy;
+        app_info.prune_bytes =
+            trim_savings(&app_info.binary_path, &app_info.architectures, strategy);
+        if strategy == Strategy::Skip {
+            app_info.badges.push(Badge::Excluded);
+        }
+    }
+    Ok(app_info)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+/// Analyzes the bundles of `apps` again, e.g. after trimming them, instead of
+/// scanning everything. Emits the same events as a full scan and returns each
+/// app's path with its new info, or why it is skipped now.
+pub fn reanalyze<F>(
+    apps: &[AppInfo],
+    options: &ScanOptions,
+    mut on_event: F,
+) -> Vec<(PathBuf, Result<AppInfo, SkipReason>)>
+where
+    F: FnMut(ScanEvent),
+{
+    let started = Instant::now();
+    let context = ScanContext::load();
+    let sizes: Vec<_> = apps
+        .iter()
+        .map(|app| match app.helper {
+            Some(_) => file_size(&app.path),
+            None => main_binary_size(&app.path),
+        })
+        .collect();
+    on_event(ScanEvent::Started {
+        total: apps.len(),
+        total_bytes: sizes.iter().sum(),
+    });
+
+    let mut results = Vec::new();
+    let mut skipped = 0;
+    for (index, (app, bytes)) in apps.iter().zip(sizes).enumerate() {
+        on_event(ScanEvent::BundleFound {
+            index: index + 1,
+            name: app.name.clone(),
+            bytes,
+        });
+        let analysis_started = Instant::now();
+        let result = match &app.helper {
+            Some(helper) => analyze_helper(helper.clone(), options),
+            None => analyze_bundle(
+                &app.path,
+                app.group.clone(),
+                app.link_path.clone(),
+                options,
+                &context,
+            )
+            .map(|mut reanalyzed| {
+                // The privileged tools it had, unless trimming left them thin
+                let tools = app
+                    .nested_binaries
+                    .iter()
+                    .filter(|binary| !binary.starts_with(&app.path))
+                    .filter_map(|binary| {
+                        Some(Helper {
+                            label: binary.file_name()?.to_string_lossy().into_owned(),
+                            binary_path: binary.clone(),
+                            parent: None,
+                        })
+                    })
+                    .collect();
+                attach_privileged_tools(std::slice::from_mut(&mut reanalyzed), tools, options);
+                reanalyzed
+            }),
+        };
+        let result = with_analysis_time(result, analysis_started);
+        match &result {
+            Ok(_) => on_event(ScanEvent::BundleAnalyzed {
+                name: app.name.clone(),
+            }),
+            Err(reason) => {
+                skipped += 1;
+                on_event(ScanEvent::BundleSkipped {
+                    name: app.name.clone(),
+                    reason: reason.clone(),
+                });
             }
         }
+        results.push((app.path.clone(), result));
+    }
+
+    on_event(ScanEvent::Finished {
+        analyzed: results.len() - skipped,
+        skipped,
+        duration: started.elapsed(),
+    });
+    results
+}
+
+/// Does any pattern ignore the bundle, by its path, the link it was found through
+/// or its bundle identifier?
+fn is_ignored(
+    patterns: &[IgnorePattern],
+    path: &Path,
+    link_path: Option<&Path>,
+    bundle_id: Option<&str>,
+) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern.matches(path, bundle_id)
+            || link_path.is_some_and(|link_path| pattern.matches(link_path, bundle_id))
+    })
+}
+
+/// Lists the entries of a scan root, including the contents of third-party store
+/// folders such as /Applications/Setapp.
+fn root_entries(root: &Path) -> Vec<fs::DirEntry> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut result = Vec::new();
+    for entry in entries.flatten() {
+        if STORE_FOLDERS.contains(&entry.file_name().to_string_lossy().as_ref())
+            && let Ok(store_entries) = fs::read_dir(entry.path())
+        {
+            result.extend(store_entries.flatten());
+        } else {
+            result.push(entry);
+        }
+    }
+    result
+}
+
+/// Finds the `.app` bundle a scan root entry refers to, following symlinks and
+/// Finder aliases. Returns the bundle's real path and, for links, the link itself.
+fn bundle_for_entry(entry: &fs::DirEntry) -> Option<(PathBuf, Option<PathBuf>)> {
+    let path = entry.path();
+    let file_type = entry.file_type().ok()?;
+    if file_type.is_dir() {
+        return is_app_bundle(&path).then_some((path, None));
     }
 
-    if !binary_path.exists() {
+    let target = if file_type.is_symlink() {
+        location::installed_path(&fs::canonicalize(&path).ok()?)
+    } else if file_type.is_file() {
+        location::installed_path(&alias::resolve(&path)?)
+    } else {
         return None;
+    };
+
+    (target.is_dir() && is_app_bundle(&target)).then_some((target, Some(path)))
+}
+
+fn is_app_bundle(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("app")
+}
+
+/// Detects how an app was distributed from markers inside and around the bundle.
+fn store_badge(app_path: &Path, bundle_id: Option<&str>) -> Option<Badge> {
+    let in_setapp_folder = app_path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .is_some_and(|name| name == "Setapp");
+
+    if in_setapp_folder
+        || bundle_id.is_some_and(|id| id.ends_with("-setapp"))
+        || app_path
+            .join("Contents/Frameworks/Setapp.framework")
+            .exists()
+    {
+        return Some(Badge::Setapp);
+    }
+
+    if app_path.join("Contents/_MASReceipt/receipt").exists() {
+        return Some(Badge::AppStore);
+    }
+
+    None
+}
+
+/// Bundle identifiers of Wine front-ends.
+const WINE_BUNDLE_IDS: [&str; 3] = [
+    "com.codeweavers.CrossOver",
+    "com.isaacmarovitz.Whisky",
+    "org.winehq.wine",
+];
+/// Where CrossOver, Wineskin, Porting Kit and plain Wine builds keep Wine inside
+/// the bundle, including the app wrappers CrossOver creates for bottled programs.
+const WINE_MARKERS: [&str; 5] = [
+    "Contents/SharedSupport/CrossOver",
+    "Contents/SharedSupport/wine",
+    "Contents/Resources/wine",
+    "Contents/Wineskin.app",
+    "Contents/SharedSupport/prefix/system.reg",
+];
+
+fn is_wine_bundle(app_path: &Path, bundle_id: Option<&str>) -> bool {
+    bundle_id.is_some_and(|id| {
+        WINE_BUNDLE_IDS
+            .iter()
+            .any(|wine| id == *wine || id.starts_with(&format!("{}.", wine)))
+    }) || WINE_MARKERS
+        .iter()
+        .any(|marker| app_path.join(marker).exists())
+}
+
+/// Frameworks of self-updating apps. They download the full universal app, so an
+/// update undoes the trim.
+const UPDATE_FRAMEWORKS: [&str; 2] = ["Sparkle.framework", "Squirrel.framework"];
+
+fn update_badge(app_path: &Path) -> Option<Badge> {
+    let frameworks = app_path.join("Contents/Frameworks");
+    UPDATE_FRAMEWORKS
+        .iter()
+        .any(|framework| frameworks.join(framework).exists())
+        .then_some(Badge::AutoUpdates)
+}
+
+fn analyze_app(app_path: &Path, timeout: Duration) -> Result<AppInfo, ScanError> {
+    let app_name = app_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+
+    // iOS apps are installed as a wrapper around the real bundle
+    if let Some(wrapped_app) = wrapped_bundle(app_path) {
+        return analyze_wrapped_app(app_path, &wrapped_app, app_name, timeout);
+    }
+
+    // Find the binary inside Contents/MacOS/
+    let macos_dir = app_path.join("Contents").join("MacOS");
+
+    if !macos_dir.exists() {
+        return Err(ScanError::NoMacOSDir);
+    }
+
+    let Some(binary_path) = find_main_binary(&macos_dir, &app_name) else {
+        return Err(ScanError::NoExecutable);
+    };
+
+    let binary_identity = BinaryIdentity::of(&binary_path);
+    let architectures = extract_architectures(&binary_path, timeout)?;
+    let (bundle_id, category) = read_bundle_info(&app_path.join("Contents").join("Info.plist"));
+    let vendor = vendor::vendor(app_path, bundle_id.as_deref(), timeout);
+    let shared_badge = (Sharing::of(&binary_path) != Sharing::Exclusive).then_some(Badge::Shared);
+
+    Ok(AppInfo {
+        name: app_name,
+        path: app_path.to_path_buf(),
+        prune_bytes: trim_savings(&binary_path, &architectures, Strategy::default()),
+        binary_path,
+        architectures,
+        selected: false,
+        group: None,
+        badges: store_badge(app_path, bundle_id.as_deref())
+            .into_iter()
+            .chain(update_badge(app_path))
+            .chain(shared_badge)
+            .collect(),
+        bundle_id,
+        link_path: None,
+        strategy: Strategy::default(),
+        vendor,
+        category,
+        binary_identity,
+        risks: Vec::new(),
+        helper: None,
+        nested_binaries: Vec::new(),
+        potential: None,
+        analysis_ms: 0,
+    })
+}
+
+/// The main binary in `macos_dir`, usually named the same as the app. Case-sensitive
+/// volumes only find it under the exact name, so the name is matched ignoring case
+/// before falling back to the first executable.
+fn find_main_binary(macos_dir: &Path, app_name: &str) -> Option<PathBuf> {
+    let named = macos_dir.join(app_name);
+    if named.is_file() {
+        return Some(named);
+    }
+
+    let mut files: Vec<_> = fs::read_dir(macos_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && !is_apple_double(path))
+        .collect();
+    files.sort();
+    let name_matches = |path: &&PathBuf| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.eq_ignore_ascii_case(app_name))
+    };
+    files
+        .iter()
+        .find(name_matches)
+        .or_else(|| files.iter().find(|path| is_executable(path)))
+        .cloned()
+}
+
+/// Is this a `._` file in which FAT and exFAT volumes, and network shares, keep
+/// the extended attributes of the file it's named after? They aren't binaries,
+/// but those volumes report every file as executable.
+fn is_apple_double(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.as_encoded_bytes().starts_with(b"._"))
+}
+
+/// Size of the executables in the bundle's `Contents/MacOS`, used to weigh scan
+/// progress before the bundle is analyzed. 0 if there are none, e.g. for iOS apps.
+fn main_binary_size(app_path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(app_path.join("Contents").join("MacOS")) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| !is_apple_double(&entry.path()))
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Returns the inner bundle of an iOS/iPadOS app installed on Apple Silicon.
+///
+/// These are laid out as `Foo.app/Wrapper/Foo.app` with a `WrappedBundle` symlink
+/// pointing at the inner bundle, instead of the usual `Contents/MacOS` structure.
+fn wrapped_bundle(app_path: &Path) -> Option<PathBuf> {
+    let wrapped_link = app_path.join("WrappedBundle");
+    if let Ok(target) = fs::canonicalize(&wrapped_link) {
+        return Some(target);
+    }
+
+    fs::read_dir(app_path.join("Wrapper"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.extension().and_then(|s| s.to_str()) == Some("app"))
+}
+
+fn analyze_wrapped_app(
+    app_path: &Path,
+    wrapped_app: &Path,
+    app_name: String,
+    timeout: Duration,
+) -> Result<AppInfo, ScanError> {
+    // iOS bundles are flat, the executable lives at the root of the inner bundle
+    let mut binary_path = wrapped_app.join(wrapped_app.file_stem().unwrap_or_default());
+
+    if !binary_path.is_file() {
+        binary_path = fs::read_dir(wrapped_app)?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.is_file() && is_executable(path))
+            .ok_or(ScanError::NoExecutable)?;
     }
 
-    let architectures = extract_architectures(&binary_path)?;
+    let binary_identity = BinaryIdentity::of(&binary_path);
+    let architectures = extract_architectures(&binary_path, timeout)?;
+    let (bundle_id, category) = read_bundle_info(&wrapped_app.join("Info.plist"));
 
-    Some(AppInfo {
+    Ok(AppInfo {
         name: app_name,
         path: app_path.to_path_buf(),
         binary_path,
         architectures,
         selected: false,
+        group: None,
+        badges: vec![Badge::IosApp],
+        vendor: vendor::vendor(wrapped_app, bundle_id.as_deref(), timeout),
+        bundle_id,
+        link_path: None,
+        prune_bytes: None,
+        strategy: Strategy::default(),
+        category,
+        binary_identity,
+        risks: Vec::new(),
+        helper: None,
+        nested_binaries: Vec::new(),
+        potential: None,
+        analysis_ms: 0,
     })
 }
 
+/// CFBundleIdentifier and LSApplicationCategoryType from an Info.plist.
+fn read_bundle_info(info_plist: &Path) -> (Option<String>, Option<String>) {
+    let Ok(value) = plist::Value::from_file(info_plist) else {
+        return (None, None);
+    };
+    let Some(dictionary) = value.as_dictionary() else {
+        return (None, None);
+    };
+    let string = |key: &str| {
+        dictionary
+            .get(key)
+            .and_then(plist::Value::as_string)
+            .map(str::to_string)
+    };
+    (
+        string("CFBundleIdentifier"),
+        string("LSApplicationCategoryType"),
+    )
+}
+
+/// `Developer Tools` from `public.app-category.developer-tools`.
+pub fn category_name(category: &str) -> String {
+    let name = category
+        .strip_prefix("public.app-category.")
+        .unwrap_or(category);
+    name.split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 fn is_executable(path: &Path) -> bool {
     #[cfg(unix)]
     {
@@ -145,15 +1557,30 @@ fn is_executable(path: &Path) -> bool {
     false
 }
 
-fn extract_architectures(binary_path: &Path) -> Option<Vec<ArchInfo>> {
-    let output = Command::new("lipo")
-        .arg("-detailed_info")
-        .arg(binary_path)
-        .output()
-        .ok()?;
+fn extract_architectures(
+    binary_path: &Path,
+    timeout: Duration,
+) -> Result<Vec<ArchInfo>, ScanError> {
+    let output = match command::run(
+        Command::new(tools::lipo())
+            .arg("-detailed_info")
+            .arg(binary_path),
+        None,
+        timeout,
+    ) {
+        Err(CommandError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            return native_architectures(binary_path);
+        }
+        result => result?,
+    };
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
+    trace!(binary = %binary_path.display(), %stdout, %stderr, "lipo -detailed_info");
+
+    if trimmer::developer_tools_missing(&stderr) {
+        return native_architectures(binary_path);
+    }
 
     // Check if this is a non-fat file (single architecture)
     // lipo can exit with success (0) for non-fat files, so check the output content
@@ -164,50 +1591,79 @@ fn extract_architectures(binary_path: &Path) -> Option<Vec<ArchInfo>> {
     {
         // Try to extract the architecture from stdout first, then stderr
         let output_to_parse = if !stdout.is_empty() { &stdout } else { &stderr };
-        return extract_single_architecture(binary_path, output_to_parse);
+        return extract_single_architecture(binary_path, output_to_parse, timeout);
     }
 
     // Check if command failed for other reasons
     if !output.status.success() {
-        return None;
+        return Err(lipo_error(&stderr));
     }
 
-    parse_lipo_output(&stdout)
+    parse_lipo_output(&stdout).ok_or(ScanError::NotMachO)
+}
+
+/// Reads the slices from the Mach-O headers when lipo isn't available.
+fn native_architectures(binary_path: &Path) -> Result<Vec<ArchInfo>, ScanError> {
+    debug!(binary = %binary_path.display(), "lipo missing, reading Mach-O headers");
+    macho::architectures(binary_path).ok_or(ScanError::NotMachO)
+}
+
+/// Classifies a lipo failure, telling files that aren't Mach-O binaries apart from
+/// other errors.
+fn lipo_error(stderr: &str) -> ScanError {
+    if stderr.contains("can't figure out the architecture type")
+        || stderr.contains("not an object file")
+    {
+        ScanError::NotMachO
+    } else {
+        ScanError::LipoFailed {
+            stderr: stderr.trim().to_string(),
+        }
+    }
 }
 
-fn extract_single_architecture(binary_path: &Path, stderr: &str) -> Option<Vec<ArchInfo>> {
+fn extract_single_architecture(
+    binary_path: &Path,
+    stderr: &str,
+    timeout: Duration,
+) -> Result<Vec<ArchInfo>, ScanError> {
+    // A thin file is a single slice, so its size is the file size
+    let size_bytes = fs::metadata(binary_path)
+        .ok()
+        .map(|metadata| metadata.len());
+
     // First, try to parse the architecture from stderr
     // Example: "Non-fat file: /path/to/binary is architecture: arm64"
     if let Some(arch) = parse_architecture_from_stderr(stderr) {
-        return Some(vec![ArchInfo {
-            cpu_type: arch,
-            size_bytes: None,
+        return Ok(vec![ArchInfo {
+            arch: Arch::from_name(&arch),
+            size_bytes,
+            align: None,
         }]);
     }
 
     // Fallback: Use lipo -archs to get the architecture of a non-fat file
-    let output = Command::new("lipo")
-        .arg("-archs")
-        .arg(binary_path)
-        .output()
-        .ok()?;
+    let output = command::run(
+        Command::new(tools::lipo()).arg("-archs").arg(binary_path),
+        None,
+        timeout,
+    )?;
 
     if !output.status.success() {
-        return None;
+        return Err(lipo_error(&String::from_utf8_lossy(&output.stderr)));
     }
 
     let arch_str = String::from_utf8_lossy(&output.stdout);
     let arch_name = arch_str.trim();
 
     if arch_name.is_empty() {
-        return None;
+        return Err(ScanError::NotMachO);
     }
 
-    // For non-fat files, we don't have accurate per-architecture size
-    // Set size_bytes to None
-    Some(vec![ArchInfo {
-        cpu_type: arch_name.to_string(),
-        size_bytes: None,
+    Ok(vec![ArchInfo {
+        arch: Arch::from_name(arch_name),
+        size_bytes,
+        align: None,
     }])
 }
 
@@ -215,12 +1671,12 @@ fn parse_architecture_from_stderr(stderr: &str) -> Option<String> {
     // Parse messages like:
     // "Non-fat file: /path/to/binary is architecture: arm64"
     for line in stderr.lines() {
-        if line.contains("is architecture:") {
-            if let Some(arch_part) = line.split("is architecture:").nth(1) {
-                let arch = arch_part.trim();
-                if !arch.is_empty() {
-                    return Some(arch.to_string());
-                }
+        if line.contains("is architecture:")
+            && let Some(arch_part) = line.split("is architecture:").nth(1)
+        {
+            let arch = arch_part.trim();
+            if !arch.is_empty() {
+                return Some(arch.to_string());
             }
         }
     }
@@ -236,31 +1692,37 @@ fn parse_lipo_output(output: &str) -> Option<Vec<ArchInfo>> {
         let line = lines[i].trim();
 
         // Look for architecture line
-        if line.starts_with("architecture ") {
-            if let Some(arch_name) = line.strip_prefix("architecture ") {
-                let arch_name = arch_name.trim().to_string();
-
-                // Find the size line (should be a few lines down)
-                let mut size_bytes = None;
-                for j in (i + 1)..std::cmp::min(i + 10, lines.len()) {
-                    let size_line = lines[j].trim();
-                    if size_line.starts_with("size ") {
-                        // Extract size value
-                        let parts: Vec<&str> = size_line.split_whitespace().collect();
-                        if parts.len() >= 2 {
-                            if let Ok(size) = parts[1].parse::<u64>() {
-                                size_bytes = Some(size);
-                                break;
-                            }
-                        }
-                    }
+        if let Some(arch_name) = line.strip_prefix("architecture ") {
+            let arch = Arch::from_name(arch_name.trim());
+
+            // Find the size and align lines (should be a few lines down)
+            let mut size_bytes = None;
+            let mut align = None;
+            for detail_line in lines.iter().skip(i + 1).take(9) {
+                let detail_line = detail_line.trim();
+                if detail_line.starts_with("architecture ") {
+                    break;
                 }
 
-                architectures.push(ArchInfo {
-                    cpu_type: arch_name,
-                    size_bytes,
-                });
+                let parts: Vec<&str> = detail_line.split_whitespace().collect();
+                match parts.as_slice() {
+                    ["size", value, ..] => size_bytes = value.parse::<u64>().ok(),
+                    // e.g. "align 2^14 (16384)"
+                    ["align", power, ..] => {
+                        align = power
+                            .strip_prefix("2^")
+                            .and_then(|exponent| exponent.parse::<u32>().ok())
+                            .and_then(|exponent| 1u64.checked_shl(exponent))
+                    }
+                    _ => {}
+                }
             }
+
+            architectures.push(ArchInfo {
+                arch,
+                size_bytes,
+                align,
+            });
         }
 
         i += 1;
@@ -273,6 +1735,72 @@ fn parse_lipo_output(output: &str) -> Option<Vec<ArchInfo>> {
     }
 }
 
+/// Bytes freed by removing the x86_64 slice of a universal binary nested in a
+/// bundle, `None` unless it has both an x86_64 and an arm64 slice.
+pub fn nested_savings(path: &Path) -> Option<u64> {
+    let architectures = macho::fat_architectures(path)?;
+    if !architectures.iter().any(|arch| arch.arch == Arch::Arm64) {
+        return None;
+    }
+    trim_savings(path, &architectures, Strategy::LipoRemove)
+}
+
+/// Bytes freed by trimming with `strategy`, from the binary's current size and the
+/// size of the file lipo would write for the remaining slices.
+pub fn trim_savings(
+    binary_path: &Path,
+    architectures: &[ArchInfo],
+    strategy: Strategy,
+) -> Option<u64> {
+    let mut file = fs::File::open(binary_path).ok()?;
+    let mut magic = [0u8; 4];
+    file.read_exact(&mut magic).ok()?;
+    let fat64 = u32::from_be_bytes(magic) == FAT_MAGIC_64;
+    let file_size = file.metadata().ok()?.len();
+
+    let remaining: Vec<_> = architectures
+        .iter()
+        .filter(|arch| match strategy {
+            Strategy::LipoRemove => arch.arch != Arch::X86_64,
+            Strategy::KeepOnlyNative => arch.arch == Arch::Arm64,
+            Strategy::Skip | Strategy::NestedOnly => true,
+        })
+        .collect();
+    if remaining.len() == architectures.len() {
+        return None;
+    }
+
+    let remaining_size = match remaining.as_slice() {
+        // `lipo -thin` writes the slice on its own, without a fat header
+        [native] if strategy == Strategy::KeepOnlyNative => native.size_bytes?,
+        _ => projected_fat_size(&remaining, fat64)?,
+    };
+    let savings = file_size.checked_sub(remaining_size)?;
+    Some(Sharing::of(binary_path).freeable(savings))
+}
+
+/// Size of a fat file holding `architectures`, laid out the way lipo writes them:
+/// slices ordered by alignment with arm64 last, each starting at the next multiple
+/// of its alignment after the header and the previous slice.
+fn projected_fat_size(architectures: &[&ArchInfo], fat64: bool) -> Option<u64> {
+    let arch_size = if fat64 {
+        FAT_ARCH_64_SIZE
+    } else {
+        FAT_ARCH_SIZE
+    };
+    let mut slices = architectures
+        .iter()
+        .map(|arch| Some((arch.arch.is_arm64(), arch.align?, arch.size_bytes?)))
+        .collect::<Option<Vec<_>>>()?;
+    slices.sort_by_key(|&(arm64, align, _)| (arm64, align));
+
+    let mut end = FAT_HEADER_SIZE + arch_size * slices.len() as u64;
+    for (_, align, size) in slices {
+        end = end.next_multiple_of(align.max(1)) + size;
+    }
+    Some(end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,10 +1827,67 @@ architecture arm64
 
         let archs = parse_lipo_output(output).unwrap();
         assert_eq!(archs.len(), 2);
-        assert_eq!(archs[0].cpu_type, "x86_64");
+        assert_eq!(archs[0].arch, Arch::X86_64);
         assert_eq!(archs[0].size_bytes, Some(9228032));
-        assert_eq!(archs[1].cpu_type, "arm64");
+        assert_eq!(archs[1].arch, Arch::Arm64);
         assert_eq!(archs[1].size_bytes, Some(8804432));
+        assert_eq!(archs[1].align, Some(16384));
+    }
+
+    #[test]
+    fn test_trim_savings_strategies() {
+        let binary =
+            std::env::temp_dir().join(format!("bintrim-trim-savings-{}", std::process::id()));
+        let mut contents = vec![0u8; 10_000];
+        contents[..4].copy_from_slice(&0xcafebabe_u32.to_be_bytes());
+        fs::write(&binary, contents).unwrap();
+        let architectures = [
+            ArchInfo {
+                arch: Arch::X86_64,
+                size_bytes: Some(4000),
+                align: Some(4096),
+            },
+            ArchInfo {
+                arch: Arch::Arm64,
+                size_bytes: Some(3000),
+                align: Some(16384),
+            },
+        ];
+
+        // The thin binary is just the arm64 slice
+        assert_eq!(
+            trim_savings(&binary, &architectures, Strategy::KeepOnlyNative),
+            Some(7000)
+        );
+        assert_eq!(trim_savings(&binary, &architectures, Strategy::Skip), None);
+        fs::remove_file(&binary).unwrap();
+    }
+
+    #[test]
+    fn test_projected_fat_size() {
+        let arch = |name: &str, size, align| ArchInfo {
+            arch: Arch::from_name(name),
+            size_bytes: Some(size),
+            align: Some(align),
+        };
+        let arm64 = arch("arm64", 8804432, 16384);
+        let arm64e = arch("arm64e", 100, 16384);
+        let i386 = arch("i386", 4000, 4096);
+
+        // The header is padded to the slice alignment
+        assert_eq!(projected_fat_size(&[&arm64], false), Some(16384 + 8804432));
+        // Less aligned slices come first, arm64 last
+        assert_eq!(
+            projected_fat_size(&[&arm64e, &i386], false),
+            Some(16384 + 100)
+        );
+        assert_eq!(projected_fat_size(&[&arm64], true), Some(16384 + 8804432));
+
+        let unknown = ArchInfo {
+            align: None,
+            ..arm64.clone()
+        };
+        assert_eq!(projected_fat_size(&[&unknown], false), None);
     }
 
     #[test]
@@ -331,6 +1916,20 @@ architecture arm64
         assert_eq!(arch, "x86_64");
     }
 
+    #[test]
+    fn test_thin_file_size() {
+        let binary = std::env::temp_dir().join(format!("bintrim-thin-{}", std::process::id()));
+        fs::write(&binary, vec![0u8; 4096]).unwrap();
+
+        let stderr = format!("Non-fat file: {} is architecture: arm64", binary.display());
+        let archs = extract_single_architecture(&binary, &stderr, DEFAULT_COMMAND_TIMEOUT);
+        fs::remove_file(&binary).unwrap();
+
+        let archs = archs.unwrap();
+        assert_eq!(archs[0].arch, Arch::Arm64);
+        assert_eq!(archs[0].size_bytes, Some(4096));
+    }
+
     #[test]
     fn test_parse_lipo_output_fat_binary() {
         let output = r#"Fat header in: /Applications/WezTerm.app/Contents/MacOS/wezterm-gui
@@ -345,7 +1944,447 @@ architecture arm64
 
         let archs = parse_lipo_output(output).unwrap();
         assert_eq!(archs.len(), 2);
-        assert_eq!(archs[0].cpu_type, "x86_64");
-        assert_eq!(archs[1].cpu_type, "arm64");
+        assert_eq!(archs[0].arch, Arch::X86_64);
+        assert_eq!(archs[1].arch, Arch::Arm64);
+    }
+
+    #[test]
+    fn test_wrapped_bundle() {
+        let app = std::env::temp_dir().join(format!("bintrim-wrapped-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&app);
+        fs::create_dir_all(app.join("Wrapper").join("Game.app")).unwrap();
+
+        let wrapped = wrapped_bundle(&app);
+        let regular = wrapped_bundle(&app.join("Wrapper").join("Game.app"));
+        fs::remove_dir_all(&app).unwrap();
+
+        assert_eq!(wrapped, Some(app.join("Wrapper").join("Game.app")));
+        assert!(regular.is_none());
+    }
+
+    #[test]
+    fn test_store_badge() {
+        let root = std::env::temp_dir().join(format!("bintrim-stores-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let setapp = root.join("Setapp").join("CleanShot X.app");
+        let mas = root.join("Xcode.app");
+        let plain = root.join("Firefox.app");
+        fs::create_dir_all(&setapp).unwrap();
+        fs::create_dir_all(mas.join("Contents/_MASReceipt")).unwrap();
+        fs::write(mas.join("Contents/_MASReceipt/receipt"), b"").unwrap();
+        fs::create_dir_all(&plain).unwrap();
+
+        let names: Vec<_> = root_entries(&root)
+            .iter()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        let badges = (
+            store_badge(&setapp, None),
+            store_badge(&mas, None),
+            store_badge(&plain, None),
+            store_badge(&plain, Some("com.example.app-setapp")),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(names.contains(&"CleanShot X.app".to_string()));
+        assert!(!names.contains(&"Setapp".to_string()));
+        assert_eq!(
+            badges,
+            (
+                Some(Badge::Setapp),
+                Some(Badge::AppStore),
+                None,
+                Some(Badge::Setapp)
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_badge() {
+        let root = std::env::temp_dir().join(format!("bintrim-updaters-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let sparkle = root.join("Transmission.app");
+        let squirrel = root.join("Slack.app");
+        let plain = root.join("Firefox.app");
+        fs::create_dir_all(sparkle.join("Contents/Frameworks/Sparkle.framework")).unwrap();
+        fs::create_dir_all(squirrel.join("Contents/Frameworks/Squirrel.framework")).unwrap();
+        fs::create_dir_all(plain.join("Contents/Frameworks")).unwrap();
+
+        let badges = (
+            update_badge(&sparkle),
+            update_badge(&squirrel),
+            update_badge(&plain),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            badges,
+            (Some(Badge::AutoUpdates), Some(Badge::AutoUpdates), None)
+        );
+    }
+
+    #[test]
+    fn test_is_wine_bundle() {
+        let root = std::env::temp_dir().join(format!("bintrim-wine-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let wrapper = root.join("Steam.app");
+        let plain = root.join("Firefox.app");
+        fs::create_dir_all(wrapper.join("Contents/SharedSupport/wine")).unwrap();
+        fs::create_dir_all(&plain).unwrap();
+
+        let detected = (
+            is_wine_bundle(&wrapper, None),
+            is_wine_bundle(&plain, Some("com.codeweavers.CrossOver")),
+            is_wine_bundle(&plain, Some("org.mozilla.firefox")),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(detected, (true, true, false));
+    }
+
+    #[test]
+    fn test_ios_app_is_not_prunable() {
+        let mut app = AppInfo::for_test("Game", &[("x86_64", Some(1024)), ("arm64", Some(1024))]);
+        app.badges.push(Badge::IosApp);
+
+        assert!(app.has_x86_64());
+        assert!(!app.is_prunable());
+    }
+
+    #[test]
+    fn test_lipo_error() {
+        let not_macho = "fatal error: /usr/bin/lipo: can't figure out the architecture type of: /Applications/Foo.app/Contents/MacOS/foo";
+        assert_eq!(lipo_error(not_macho), ScanError::NotMachO);
+
+        let denied = "fatal error: /usr/bin/lipo: can't open input file: /Applications/Foo.app/Contents/MacOS/foo (Permission denied)\n";
+        assert_eq!(
+            lipo_error(denied),
+            ScanError::LipoFailed {
+                stderr: denied.trim().to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_analyze_app_errors() {
+        let root = std::env::temp_dir().join(format!("bintrim-errors-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let no_macos = root.join("Empty.app");
+        let no_executable = root.join("Data.app");
+        fs::create_dir_all(&no_macos).unwrap();
+        fs::create_dir_all(no_executable.join("Contents/MacOS")).unwrap();
+
+        let results = (
+            analyze_app(&no_macos, DEFAULT_COMMAND_TIMEOUT).err(),
+            analyze_app(&no_executable, DEFAULT_COMMAND_TIMEOUT).err(),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            results,
+            (Some(ScanError::NoMacOSDir), Some(ScanError::NoExecutable))
+        );
+    }
+
+    #[test]
+    fn test_scan_progress() {
+        let mut progress = ScanProgress::default();
+        progress.apply(ScanEvent::Started {
+            total: 10,
+            total_bytes: 1000,
+        });
+        progress.apply(ScanEvent::BundleFound {
+            index: 3,
+            name: "Slack".to_string(),
+            bytes: 600,
+        });
+        assert_eq!(progress.ratio(), 0.0);
+        progress.apply(ScanEvent::BundleAnalyzed {
+            name: "Slack".to_string(),
+        });
+        // One big app outweighs the count
+        assert_eq!(progress.ratio(), 0.6);
+        progress.apply(ScanEvent::BundleFound {
+            index: 7,
+            name: "Old".to_string(),
+            bytes: 100,
+        });
+        progress.apply(ScanEvent::BundleSkipped {
+            name: "Old".to_string(),
+            reason: SkipReason::NoArm64,
+        });
+
+        assert_eq!((progress.current, progress.total), (7, 10));
+        assert_eq!(progress.current_name, "Old");
+        assert_eq!(progress.analyzed, 1);
+        assert_eq!(progress.skipped.len(), 1);
+        assert_eq!(progress.error_count(), 0);
+        assert!(!progress.finished);
+
+        progress.apply(ScanEvent::Finished {
+            analyzed: 1,
+            skipped: 1,
+            duration: Duration::from_secs(3),
+        });
+        assert_eq!(progress.current, 10);
+        assert_eq!(progress.ratio(), 1.0);
+        assert!(progress.finished);
+        assert_eq!(progress.duration, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_main_binary_size() {
+        let dir = std::env::temp_dir().join(format!("bintrim-binary-size-{}", std::process::id()));
+        let macos = dir.join("Slack.app/Contents/MacOS");
+        fs::create_dir_all(&macos).unwrap();
+        fs::write(macos.join("Slack"), [0u8; 300]).unwrap();
+        fs::write(macos.join("helper"), [0u8; 200]).unwrap();
+        fs::write(macos.join("._Slack"), [0u8; 4096]).unwrap();
+
+        assert_eq!(main_binary_size(&dir.join("Slack.app")), 500);
+        assert_eq!(main_binary_size(&dir.join("Missing.app")), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_main_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("bintrim-main-binary-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // As on exFAT, where every file looks executable
+        for name in ["._Zoom", "Helper", "zoom"] {
+            fs::write(dir.join(name), []).unwrap();
+            fs::set_permissions(dir.join(name), fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let found = [
+            find_main_binary(&dir, "Zoom"),
+            find_main_binary(&dir, "Helper"),
+            find_main_binary(&dir, "Other"),
+            find_main_binary(&dir.join("missing"), "Zoom"),
+        ];
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            found,
+            [
+                Some(dir.join("zoom")),
+                Some(dir.join("Helper")),
+                Some(dir.join("Helper")),
+                None
+            ]
+        );
+        assert!(is_apple_double(Path::new("/Volumes/USB/._Zoom")));
+        assert!(!is_apple_double(Path::new("/Volumes/USB/Zoom")));
+    }
+
+    #[test]
+    fn test_bundle_for_entry() {
+        let root = std::env::temp_dir().join(format!("bintrim-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let store = root.join("store").join("Firefox.app");
+        fs::create_dir_all(&store).unwrap();
+        fs::create_dir_all(root.join("Applications").join("Slack.app")).unwrap();
+        fs::write(root.join("Applications").join("notes.txt"), "").unwrap();
+        std::os::unix::fs::symlink(&store, root.join("Applications").join("Firefox.app")).unwrap();
+
+        let mut bundles: Vec<_> = fs::read_dir(root.join("Applications"))
+            .unwrap()
+            .flatten()
+            .filter_map(|entry| bundle_for_entry(&entry))
+            .collect();
+        bundles.sort();
+        let store = fs::canonicalize(&store).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            bundles,
+            vec![
+                (root.join("Applications").join("Slack.app"), None),
+                (store, Some(root.join("Applications").join("Firefox.app"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_bundles() {
+        let root = std::env::temp_dir().join(format!("bintrim-duplicates-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("Applications").join("Slack.app")).unwrap();
+        std::os::unix::fs::symlink(root.join("Applications"), root.join("Linked")).unwrap();
+
+        let options = ScanOptions {
+            roots: vec![
+                ScanRoot {
+                    path: root.join("Applications"),
+                    group: None,
+                    volume: None,
+                },
+                ScanRoot {
+                    path: root.join("Linked"),
+                    group: None,
+                    volume: None,
+                },
+            ],
+            ..ScanOptions::default()
+        };
+        let mut reasons = Vec::new();
+        scan_applications_with_progress(&options, &CancelToken::default(), |event| {
+            if let ScanEvent::BundleSkipped { reason, .. } = event {
+                reasons.push(reason);
+            }
+        });
+        // A cancelled scan stops before the first bundle
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let mut events = Vec::new();
+        scan_applications_with_progress(&options, &cancel, |event| events.push(event));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(matches!(
+            events[..],
+            [
+                ScanEvent::Started { total: 2, .. },
+                ScanEvent::Finished {
+                    analyzed: 0,
+                    skipped: 0,
+                    ..
+                }
+            ]
+        ));
+
+        assert_eq!(
+            reasons,
+            vec![
+                SkipReason::Error(ScanError::NoMacOSDir),
+                SkipReason::Duplicate(root.join("Applications").join("Slack.app")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_user_roots_in() {
+        let users_dir = std::env::temp_dir().join(format!("bintrim-users-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&users_dir);
+        fs::create_dir_all(users_dir.join("bob").join("Applications")).unwrap();
+        fs::create_dir_all(users_dir.join("alice").join("Applications")).unwrap();
+        fs::create_dir_all(users_dir.join("carol")).unwrap();
+        fs::create_dir_all(users_dir.join("Shared").join("Applications")).unwrap();
+
+        let roots = user_roots_in(&users_dir);
+        fs::remove_dir_all(&users_dir).unwrap();
+
+        let groups: Vec<_> = roots.iter().map(|r| r.group.as_deref().unwrap()).collect();
+        assert_eq!(groups, vec!["~alice/Applications", "~bob/Applications"]);
+    }
+
+    #[test]
+    fn test_derived_data_roots_in() {
+        let derived_data =
+            std::env::temp_dir().join(format!("bintrim-derived-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&derived_data);
+        let products = derived_data.join("My-App-bqzcfhdyhpxjkd/Build/Products");
+        fs::create_dir_all(products.join("Release")).unwrap();
+        fs::create_dir_all(products.join("Debug")).unwrap();
+        fs::create_dir_all(derived_data.join("ModuleCache.noindex")).unwrap();
+
+        let roots = derived_data_roots_in(&derived_data);
+        fs::remove_dir_all(&derived_data).unwrap();
+
+        let groups: Vec<_> = roots.iter().map(|r| r.group.as_deref().unwrap()).collect();
+        assert_eq!(
+            groups,
+            vec![
+                "DerivedData: My-App (Debug)",
+                "DerivedData: My-App (Release)"
+            ]
+        );
+        assert!(roots[1].path.ends_with("Build/Products/Release"));
+    }
+
+    #[test]
+    fn test_game_roots() {
+        let library = std::env::temp_dir().join(format!("bintrim-games-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&library);
+        fs::create_dir_all(library.join("Hades/Hades.app")).unwrap();
+        fs::create_dir_all(library.join("Celeste")).unwrap();
+
+        let roots = game_roots(&library);
+        fs::remove_dir_all(&library).unwrap();
+
+        let paths: Vec<_> = roots.iter().map(|root| root.path.clone()).collect();
+        assert_eq!(paths, [library.join("Celeste"), library.join("Hades")]);
+        assert!(
+            roots
+                .iter()
+                .all(|root| root.group.as_deref() == Some(GAMES_GROUP))
+        );
+    }
+
+    #[test]
+    fn test_slices_display() {
+        let app = AppInfo::for_test(
+            "Slack",
+            &[
+                ("x86_64", Some(64_000_000)),
+                ("arm64e", None),
+                ("arm64", Some(61_000_000)),
+            ],
+        );
+        assert_eq!(
+            app.slices_display("N/A"),
+            "arm64 58.17 MB · x86_64 61.04 MB · arm64e N/A"
+        );
+    }
+
+    #[test]
+    fn test_category_name() {
+        assert_eq!(
+            category_name("public.app-category.developer-tools"),
+            "Developer Tools"
+        );
+        assert_eq!(category_name("public.app-category.games"), "Games");
+    }
+
+    #[test]
+    fn test_attach_privileged_tools() {
+        let tools_dir = std::env::temp_dir().join(format!("bintrim-tools-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tools_dir);
+        fs::create_dir_all(&tools_dir).unwrap();
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        for (index, cpu_type) in [0x0100_0007u32, 0x0100_000c].into_iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * 3, 0);
+        let tool = |label: &str| {
+            let binary_path = tools_dir.join(label);
+            fs::write(&binary_path, &contents).unwrap();
+            Helper {
+                label: label.to_string(),
+                binary_path,
+                parent: None,
+            }
+        };
+        let tools = vec![tool("com.docker.vmnetd"), tool("com.example.updater")];
+
+        let mut docker = AppInfo::for_test("Docker", &[("x86_64", Some(100)), ("arm64", Some(90))]);
+        docker.bundle_id = Some("com.docker.docker".to_string());
+        docker.prune_bytes = Some(100);
+        let mut apps = [docker];
+        attach_privileged_tools(&mut apps, tools, &ScanOptions::default());
+        fs::remove_dir_all(&tools_dir).unwrap();
+
+        assert_eq!(
+            apps[0].nested_binaries,
+            [tools_dir.join("com.docker.vmnetd")]
+        );
+        // The arm64 slice alone takes a page for the header and 1000 bytes
+        assert_eq!(apps[0].prune_bytes, Some(100 + 16384 * 3 - 16384 - 1000));
     }
 }
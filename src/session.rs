@@ -0,0 +1,94 @@
+use crate::bytesize::ByteSize;
+use crate::i18n::t;
+use crate::trimmer::{Savings, TrimFailure};
+
+/// What was trimmed while the interface ran, for the summary printed on exit
+/// after the alternate screen is gone.
+#[derive(Debug, Default)]
+pub struct SessionLog {
+    /// Name and freed bytes of each trimmed app
+    trimmed: Vec<(String, u64)>,
+    /// Name and error of each app that couldn't be trimmed
+    failed: Vec<(String, String)>,
+}
+
+impl SessionLog {
+    /// Records a finished trim batch: what the trimmed apps actually freed and the
+    /// apps that failed.
+    pub fn record(&mut self, savings: &[Savings], failures: &[TrimFailure]) {
+        self.trimmed.extend(
+            savings
+                .iter()
+                .map(|savings| (savings.app.clone(), savings.actual)),
+        );
+        self.failed.extend(
+            failures
+                .iter()
+                .map(|failure| (failure.app.clone(), failure.error.to_string())),
+        );
+    }
+
+    /// A few plain lines: how many apps were trimmed and how much that freed, then
+    /// each app, then the failures with their errors.
+    pub fn summary(&self) -> String {
+        let strings = t();
+        if self.trimmed.is_empty() && self.failed.is_empty() {
+            return strings.session_nothing_trimmed.to_string();
+        }
+
+        let freed: u64 = self.trimmed.iter().map(|(_, bytes)| bytes).sum();
+        let mut lines = vec![(strings.session_trimmed)(
+            self.trimmed.len(),
+            &ByteSize(freed).to_string(),
+        )];
+        lines.extend(
+            self.trimmed
+                .iter()
+                .map(|(name, bytes)| format!("  {}  {}", name, ByteSize(*bytes))),
+        );
+        if !self.failed.is_empty() {
+            lines.push((strings.trim_failed_details)(self.failed.len()));
+            lines.extend(
+                self.failed
+                    .iter()
+                    .map(|(name, error)| format!("  {}: {}", name, error)),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trimmer::TrimError;
+
+    #[test]
+    fn test_summary() {
+        let mut log = SessionLog::default();
+        assert_eq!(log.summary(), t().session_nothing_trimmed);
+
+        let savings = |app: &str, actual| Savings {
+            app: app.to_string(),
+            estimated: 0,
+            actual,
+        };
+        let failures = [TrimFailure {
+            index: 0,
+            app: "zoom.us".to_string(),
+            error: TrimError::Immutable,
+        }];
+        log.record(&[savings("Slack", 2 * 1024 * 1024)], &failures);
+        log.record(&[savings("Figma", 1024 * 1024)], &[]);
+
+        let summary = log.summary();
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[0],
+            (t().session_trimmed)(2, &ByteSize(3 * 1024 * 1024).to_string())
+        );
+        assert!(lines[1].starts_with("  Slack  "));
+        assert!(lines[4].starts_with("  zoom.us: binary is locked"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Whether a binary shares its storage with other files, in which case replacing it
+/// with a trimmed copy frees less than the slices it loses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sharing {
+    Exclusive,
+    /// Other hard links keep the original file, so nothing is freed
+    HardLinked,
+    /// An APFS clone, of which only the blocks not shared with other clones are
+    /// freed
+    Cloned {
+        private_bytes: u64,
+    },
+}
+
+impl Sharing {
+    pub fn of(path: &Path) -> Self {
+        let Ok(metadata) = path.metadata() else {
+            return Sharing::Exclusive;
+        };
+        if metadata.nlink() > 1 {
+            return Sharing::HardLinked;
+        }
+        match private_size(path) {
+            Some(private_bytes) if private_bytes < metadata.len() => {
+                Sharing::Cloned { private_bytes }
+            }
+            _ => Sharing::Exclusive,
+        }
+    }
+
+    /// Bytes actually freed when trimming shrinks the file by `bytes`.
+    pub fn freeable(self, bytes: u64) -> u64 {
+        match self {
+            Sharing::Exclusive => bytes,
+            Sharing::HardLinked => 0,
+            Sharing::Cloned { private_bytes } => bytes.min(private_bytes),
+        }
+    }
+}
+
+/// Bytes of the file's storage that no clone shares, from APFS.
+#[cfg(target_os = "macos")]
+fn private_size(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    const ATTR_BIT_MAP_COUNT: u16 = 5;
+    const ATTR_CMNEXT_PRIVATESIZE: u32 = 0x0000_0008;
+    const FSOPT_ATTR_CMN_EXTENDED: u32 = 0x0000_0020;
+
+    /// `struct attrlist` from `<sys/attr.h>`
+    #[repr(C)]
+    struct AttrList {
+        bitmapcount: u16,
+        reserved: u16,
+        commonattr: u32,
+        volattr: u32,
+        dirattr: u32,
+        fileattr: u32,
+        // Holds the extended common attributes with FSOPT_ATTR_CMN_EXTENDED
+        forkattr: u32,
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut request = AttrList {
+        bitmapcount: ATTR_BIT_MAP_COUNT,
+        reserved: 0,
+        commonattr: 0,
+        volattr: 0,
+        dirattr: 0,
+        fileattr: 0,
+        forkattr: ATTR_CMNEXT_PRIVATESIZE,
+    };
+    // The length of the returned attributes, then the size as an off_t
+    let mut buffer = [0u8; 16];
+    let result = unsafe {
+        libc::getattrlist(
+            c_path.as_ptr(),
+            (&raw mut request).cast(),
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+            FSOPT_ATTR_CMN_EXTENDED,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    u64::try_from(i64::from_ne_bytes(buffer[4..12].try_into().ok()?)).ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn private_size(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sharing() {
+        let dir = std::env::temp_dir().join(format!("bintrim-sharing-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("binary");
+        std::fs::write(&binary, [0u8; 100]).unwrap();
+        let exclusive = Sharing::of(&binary);
+        std::fs::hard_link(&binary, dir.join("link")).unwrap();
+        let linked = Sharing::of(&binary);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(exclusive, Sharing::Exclusive);
+        assert_eq!(linked, Sharing::HardLinked);
+        assert_eq!(exclusive.freeable(60), 60);
+        assert_eq!(linked.freeable(60), 0);
+        assert_eq!(Sharing::Cloned { private_bytes: 40 }.freeable(60), 40);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::bytesize::ByteSize;
+use crate::i18n::t;
+use crate::scanner::AppInfo;
+use crate::state;
+
+/// Changes kept per app, the oldest are dropped first.
+const MAX_SAMPLES: usize = 20;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Sizes of an app in one scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SizeSample {
+    /// Seconds since the Unix epoch
+    pub scanned_at: u64,
+    /// All slices of the main binary
+    pub binary_bytes: u64,
+    pub prunable_bytes: u64,
+}
+
+impl SizeSample {
+    fn of(app: &AppInfo, scanned_at: u64) -> Self {
+        Self {
+            scanned_at,
+            binary_bytes: app
+                .architectures
+                .iter()
+                .filter_map(|arch| arch.size_bytes)
+                .sum(),
+            prunable_bytes: app.prune_size().map_or(0, |size| size.bytes()),
+        }
+    }
+
+    fn same_sizes(&self, other: &Self) -> bool {
+        self.binary_bytes == other.binary_bytes && self.prunable_bytes == other.prunable_bytes
+    }
+}
+
+/// How each app's size changed across scans, stored in
+/// `~/.local/state/bintrim/sizes.toml`, so apps that grow with every update stand
+/// out. A scan only adds a sample when the sizes differ from the last one.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SizeHistory {
+    /// Samples by bundle path, oldest first
+    apps: BTreeMap<String, Vec<SizeSample>>,
+}
+
+impl SizeHistory {
+    /// Loads the history. A missing or unreadable file means no scans were recorded.
+    pub fn load() -> Self {
+        history_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = history_path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Adds the sizes `apps` have in the scan at `scanned_at`.
+    pub fn record(&mut self, apps: &[AppInfo], scanned_at: u64) {
+        for app in apps {
+            let sample = SizeSample::of(app, scanned_at);
+            let samples = self.apps.entry(key(&app.path)).or_default();
+            if samples.last().is_some_and(|last| last.same_sizes(&sample)) {
+                continue;
+            }
+            samples.push(sample);
+            if samples.len() > MAX_SAMPLES {
+                samples.remove(0);
+            }
+        }
+    }
+
+    /// The first and the latest sample of the bundle at `path`, if its sizes changed
+    /// since it was first recorded.
+    pub fn change(&self, path: &Path) -> Option<(SizeSample, SizeSample)> {
+        let samples = self.apps.get(&key(path))?;
+        let (first, latest) = (samples.first()?, samples.last()?);
+        (!first.same_sizes(latest)).then_some((*first, *latest))
+    }
+
+    /// One line such as `binary 120.00 MB → 160.00 MB (+40.00 MB), prunable ...
+    /// within 42 days`, or `None` if the sizes didn't change.
+    pub fn summary(&self, path: &Path, now: u64) -> Option<String> {
+        let (first, latest) = self.change(path)?;
+        let days = now.saturating_sub(first.scanned_at) / SECONDS_PER_DAY;
+        Some((t().size_history)(
+            &delta(first.binary_bytes, latest.binary_bytes),
+            &delta(first.prunable_bytes, latest.prunable_bytes),
+            days,
+        ))
+    }
+}
+
+/// `before → after (+difference)`.
+fn delta(before: u64, after: u64) -> String {
+    let sign = if after >= before { '+' } else { '-' };
+    format!(
+        "{} → {} ({}{})",
+        ByteSize(before),
+        ByteSize(after),
+        sign,
+        ByteSize(after.abs_diff(before))
+    )
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn history_path() -> Option<PathBuf> {
+    state::state_dir().map(|dir| dir.join("sizes.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut slack = AppInfo::for_test("Slack", &[("x86_64", Some(100)), ("arm64", Some(90))]);
+        let mut history = SizeHistory::default();
+        history.record(std::slice::from_ref(&slack), 0);
+        history.record(std::slice::from_ref(&slack), SECONDS_PER_DAY);
+        assert_eq!(history.apps[&key(&slack.path)].len(), 1);
+        assert_eq!(history.change(&slack.path), None);
+
+        // An update makes the binary bigger
+        slack.architectures[0].size_bytes = Some(150);
+        history.record(std::slice::from_ref(&slack), 3 * SECONDS_PER_DAY);
+        let (first, latest) = history.change(&slack.path).unwrap();
+        assert_eq!(first.binary_bytes, 190);
+        assert_eq!(latest.binary_bytes, 240);
+        assert_eq!(latest.prunable_bytes, 150);
+        assert_eq!(
+            history.summary(&slack.path, 3 * SECONDS_PER_DAY),
+            Some((t().size_history)(&delta(190, 240), &delta(100, 150), 3))
+        );
+        assert_eq!(
+            delta(2 * 1024 * 1024, 1024 * 1024),
+            "2.00 MB → 1.00 MB (-1.00 MB)"
+        );
+
+        for day in 0..MAX_SAMPLES as u64 {
+            slack.architectures[1].size_bytes = Some(day);
+            history.record(std::slice::from_ref(&slack), day);
+        }
+        assert_eq!(history.apps[&key(&slack.path)].len(), MAX_SAMPLES);
+
+        let parsed: SizeHistory = toml::from_str(&toml::to_string(&history).unwrap()).unwrap();
+        assert_eq!(parsed, history);
+    }
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::SortMode;
+use crate::columns::Column;
+use crate::registry::AppKey;
+
+/// UI state restored on the next launch, stored in `~/.local/state/bintrim/state.toml`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct UiState {
+    pub sort_mode: Option<SortMode>,
+    /// Show apps that can't be trimmed
+    pub show_all: bool,
+    pub summary_collapsed: bool,
+    /// Columns turned off in the column chooser, `None` to use the config
+    pub hidden_columns: Option<Vec<Column>>,
+    /// Bundle the cursor was on
+    pub selected_app: Option<PathBuf>,
+    /// Key of that bundle, to find it again if it moved
+    pub selected_app_key: Option<AppKey>,
+}
+
+impl UiState {
+    /// Loads the saved state. A missing or unreadable file just means starting fresh.
+    pub fn load() -> Self {
+        state_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = state_path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Directory holding bintrim's state, honoring `XDG_STATE_HOME`.
+pub fn state_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("bintrim")),
+        _ => Some(state_dir_in(Path::new(&std::env::var_os("HOME")?))),
+    }
+}
+
+/// The state directory under the home directory `home`, without `XDG_STATE_HOME`.
+pub fn state_dir_in(home: &Path) -> PathBuf {
+    home.join(".local/state/bintrim")
+}
+
+fn state_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("state.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let state = UiState {
+            sort_mode: Some(SortMode::Alphabetical),
+            show_all: true,
+            summary_collapsed: false,
+            hidden_columns: Some(vec![Column::Notes]),
+            selected_app: Some(PathBuf::from("/Applications/Slack.app")),
+            selected_app_key: Some(AppKey::BundleId("com.tinyspeck.slackmacgap".to_string())),
+        };
+
+        let serialized = toml::to_string(&state).unwrap();
+        assert!(serialized.contains("sort-mode = \"alphabetical\""));
+        assert!(serialized.contains("hidden-columns = [\"notes\"]"));
+        assert_eq!(toml::from_str::<UiState>(&serialized).unwrap(), state);
+        assert_eq!(toml::from_str::<UiState>("").unwrap(), UiState::default());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::arch::Arch;
+use crate::bytesize::ByteSize;
+use crate::i18n::t;
+use crate::scanner::AppInfo;
+
+/// Apps with the same mix of architectures and the bytes their main binaries take.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ArchGroup {
+    pub apps: usize,
+    pub bytes: u64,
+}
+
+impl ArchGroup {
+    fn add(&mut self, app: &AppInfo) {
+        self.apps += 1;
+        self.bytes += app
+            .architectures
+            .iter()
+            .filter_map(|arch| arch.size_bytes)
+            .sum::<u64>();
+    }
+}
+
+/// How far the scanned apps are in the move to Apple Silicon, shown when a scan
+/// finishes. Helper daemons and launch agents aren't counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ArchStats {
+    pub universal: ArchGroup,
+    pub arm64_only: ArchGroup,
+    pub x86_64_only: ArchGroup,
+    /// Median size of the slices of all scanned apps
+    pub median_slice: Option<u64>,
+}
+
+impl ArchStats {
+    pub fn of(apps: &[AppInfo]) -> Self {
+        let mut stats = ArchStats::default();
+        let mut slices = Vec::new();
+        for app in apps.iter().filter(|app| app.helper.is_none()) {
+            match (app.has_arm64(), app.has_x86_64()) {
+                (true, true) => stats.universal.add(app),
+                (true, false) => stats.arm64_only.add(app),
+                (false, true) => stats.x86_64_only.add(app),
+                // Only i386 or other architectures that aren't part of the move
+                (false, false) => {}
+            }
+            slices.extend(
+                app.architectures
+                    .iter()
+                    .filter(|arch| arch.arch == Arch::X86_64 || arch.arch.is_arm64())
+                    .filter_map(|arch| arch.size_bytes),
+            );
+        }
+        stats.median_slice = median(&mut slices);
+        stats
+    }
+
+    /// One line such as `12 universal (1.20 GB) · 30 arm64 only (3.40 GB) · ...`.
+    pub fn summary(&self) -> String {
+        let group = |label: fn(usize) -> String, group: ArchGroup| match group.apps {
+            0 => label(0),
+            apps => format!("{} ({})", label(apps), ByteSize(group.bytes)),
+        };
+        let strings = t();
+        let mut parts = vec![
+            group(strings.stats_universal, self.universal),
+            group(strings.stats_arm64_only, self.arm64_only),
+            group(strings.stats_x86_64_only, self.x86_64_only),
+        ];
+        if let Some(median) = self.median_slice {
+            parts.push((strings.stats_median_slice)(&ByteSize(median).to_string()));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// The middle of `values`, or the mean of the two middle ones for an even count.
+fn median(values: &mut [u64]) -> Option<u64> {
+    values.sort_unstable();
+    let middle = values.len() / 2;
+    match values.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(values[middle]),
+        _ => Some((values[middle - 1] + values[middle]) / 2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arch_stats() {
+        let apps = [
+            AppInfo::for_test("Slack", &[("x86_64", Some(100)), ("arm64", Some(90))]),
+            AppInfo::for_test("Notes", &[("arm64", Some(40))]),
+            AppInfo::for_test("Zoom", &[("arm64", Some(60))]),
+            AppInfo::for_test("Steam", &[("x86_64", Some(200))]),
+            AppInfo::for_test("Old", &[("i386", Some(10))]),
+        ];
+        let stats = ArchStats::of(&apps);
+        assert_eq!(
+            stats.universal,
+            ArchGroup {
+                apps: 1,
+                bytes: 190
+            }
+        );
+        assert_eq!(
+            stats.arm64_only,
+            ArchGroup {
+                apps: 2,
+                bytes: 100
+            }
+        );
+        assert_eq!(
+            stats.x86_64_only,
+            ArchGroup {
+                apps: 1,
+                bytes: 200
+            }
+        );
+        // 40, 60, 90, 100, 200
+        assert_eq!(stats.median_slice, Some(90));
+
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(2));
+        assert!(
+            ArchStats::default()
+                .summary()
+                .starts_with(&(t().stats_universal)(0))
+        );
+        assert_eq!(ArchStats::of(&[]).median_slice, None);
+    }
+}
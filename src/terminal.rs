@@ -0,0 +1,23 @@
+use std::io;
+
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::DefaultTerminal;
+
+/// Hands the terminal back to the shell while `f` runs, e.g. so `sudo` can show
+/// its own password prompt, then restores the TUI.
+///
+/// Unlike `ratatui::restore`/`ratatui::init` this leaves the panic hooks alone.
+pub fn suspend<T>(terminal: &mut DefaultTerminal, f: impl FnOnce() -> T) -> io::Result<T> {
+    crossterm::execute!(io::stdout(), DisableBracketedPaste, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+
+    let result = f();
+
+    enable_raw_mode()?;
+    crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+    terminal.clear()?;
+    Ok(result)
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// Paths of the external tools bintrim runs, from the `[tools]` table of the
+/// config, such as the lipo of a specific Xcode. Tools without one are looked up
+/// on `PATH`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolPaths {
+    pub lipo: Option<PathBuf>,
+    pub otool: Option<PathBuf>,
+    pub codesign: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ToolError {
+    tool: &'static str,
+    path: PathBuf,
+    reason: &'static str,
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tools.{} = {}: {}",
+            self.tool,
+            self.path.display(),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl ToolPaths {
+    /// Checks that each configured tool is an executable file, so a typo fails at
+    /// startup rather than with every app.
+    pub fn verify(&self) -> Result<(), ToolError> {
+        for (tool, path) in self.configured() {
+            let reason = match path.metadata() {
+                Err(_) => "doesn't exist",
+                Ok(metadata) if !metadata.is_file() => "isn't a file",
+                Ok(metadata) if metadata.permissions().mode() & 0o111 == 0 => "isn't executable",
+                Ok(_) => continue,
+            };
+            return Err(ToolError {
+                tool,
+                path: path.clone(),
+                reason,
+            });
+        }
+        Ok(())
+    }
+
+    fn configured(&self) -> impl Iterator<Item = (&'static str, &PathBuf)> {
+        [
+            ("lipo", &self.lipo),
+            ("otool", &self.otool),
+            ("codesign", &self.codesign),
+        ]
+        .into_iter()
+        .filter_map(|(tool, path)| Some((tool, path.as_ref()?)))
+    }
+}
+
+static TOOLS: OnceLock<ToolPaths> = OnceLock::new();
+
+/// Uses `paths` for the rest of the run.
+pub fn init(paths: ToolPaths) {
+    let _ = TOOLS.set(paths);
+}
+
+fn path(configured: fn(&ToolPaths) -> Option<&PathBuf>, name: &'static str) -> &'static Path {
+    TOOLS
+        .get()
+        .and_then(configured)
+        .map_or(Path::new(name), PathBuf::as_path)
+}
+
+pub fn lipo() -> &'static Path {
+    path(|paths| paths.lipo.as_ref(), "lipo")
+}
+
+pub fn otool() -> &'static Path {
+    path(|paths| paths.otool.as_ref(), "otool")
+}
+
+pub fn codesign() -> &'static Path {
+    path(|paths| paths.codesign.as_ref(), "codesign")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let dir = std::env::temp_dir().join(format!("bintrim-tools-verify-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let lipo = dir.join("lipo");
+        std::fs::write(&lipo, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&lipo, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let otool = dir.join("otool");
+        std::fs::write(&otool, "").unwrap();
+        std::fs::set_permissions(&otool, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let verify = |paths: ToolPaths| paths.verify().map_err(|err| err.reason);
+        let lipo_only = ToolPaths {
+            lipo: Some(lipo.clone()),
+            ..ToolPaths::default()
+        };
+        let with_otool = ToolPaths {
+            otool: Some(otool),
+            ..lipo_only.clone()
+        };
+        let missing = ToolPaths {
+            codesign: Some(dir.join("codesign")),
+            ..ToolPaths::default()
+        };
+        let results = [
+            verify(ToolPaths::default()),
+            verify(lipo_only),
+            verify(with_otool),
+            verify(missing),
+        ];
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Ok(()),
+                Err("isn't executable"),
+                Err("doesn't exist")
+            ]
+        );
+    }
+}
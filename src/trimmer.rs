@@ -0,0 +1,781 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::cancel::CancelToken;
+use crate::command::{self, CommandError};
+use crate::confirm::Confirmation;
+use crate::daemon;
+use crate::elevated::{self, Message, Operation};
+use crate::flags;
+use crate::history::TrimHistory;
+use crate::report::{BatchRecord, BinaryRecord, millis};
+use crate::scanner::{AppInfo, BinaryIdentity};
+use crate::sharing::Sharing;
+use crate::tools;
+use crate::volume::VolumeStats;
+
+/// Default time trimming a single binary may take.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+/// Time the check for lipo may take, which is instant unless the stub asks to install it.
+const LIPO_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Share of the estimate by which the actually freed bytes may differ before the
+/// difference is flagged.
+const DISCREPANCY_RATIO: f64 = 0.1;
+/// Differences below this are never flagged, whatever their share.
+const DISCREPANCY_MIN_BYTES: u64 = 1024 * 1024;
+/// How often binaries that failed with a transient error are tried again.
+const MAX_RETRIES: u32 = 2;
+/// Wait before the first retry, doubled for each one after it.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+/// Parts of lipo's and sudo's errors, in lowercase, that go away on their own.
+const TRANSIENT_MARKERS: [&str; 6] = [
+    "resource busy",
+    "text file busy",
+    "resource temporarily unavailable",
+    "interrupted system call",
+    "operation timed out",
+    "time stamp",
+];
+
+/// How an app is trimmed, chosen per bundle identifier in the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// `lipo -remove x86_64`, keeping any other slices
+    #[default]
+    LipoRemove,
+    /// `lipo -thin arm64`, dropping every slice but the native one
+    KeepOnlyNative,
+    /// `lipo -remove x86_64` on the nested binaries and embedded frameworks only,
+    /// keeping the main executable universal for hosts that load it under Rosetta
+    NestedOnly,
+    /// Never trim the app
+    Skip,
+}
+
+impl Strategy {
+    /// Arguments telling lipo what to do with the binary.
+    pub fn lipo_args(self) -> &'static [&'static str] {
+        match self {
+            Strategy::LipoRemove | Strategy::NestedOnly => &["-remove", "x86_64"],
+            Strategy::KeepOnlyNative => &["-thin", "arm64"],
+            Strategy::Skip => &[],
+        }
+    }
+}
+
+/// Who owns a binary after trimming. `sudo lipo` writes a new file owned by root.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ownership {
+    /// The owner and group it had before, such as root:admin for most of
+    /// /Applications
+    #[default]
+    Keep,
+    /// The user running bintrim
+    CurrentUser,
+    /// Leave it to lipo and skip the chown
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrimOptions {
+    /// Maximum time for each external command
+    pub timeout: Duration,
+    /// Is the process running as root? Keeps the binary's existing owner if so.
+    pub elevated: bool,
+    /// Is lipo installed? Without it apps are scanned but not trimmed.
+    pub lipo_available: bool,
+    /// Trim through the privileged helper instead of sudo
+    pub helper: bool,
+    /// Only report, never trim (`--audit`)
+    pub audit: bool,
+    /// What it takes to start trimming
+    pub confirmation: Confirmation,
+    /// Also write the JSON record of each batch here (`--report-out`)
+    pub report_out: Option<PathBuf>,
+    /// Who owns trimmed binaries
+    pub ownership: Ownership,
+    /// Ownership of the apps that override the default, by bundle identifier
+    pub app_ownership: HashMap<String, Ownership>,
+}
+
+impl TrimOptions {
+    /// How to set the owner of `app`'s binary at `binary_path` after trimming.
+    /// Helpers outside the bundle always keep theirs: launchd runs them as root, so
+    /// they must stay root's.
+    fn ownership(&self, app: &AppInfo, binary_path: &Path) -> Ownership {
+        if app.helper.is_some() || !binary_path.starts_with(&app.path) {
+            return Ownership::Keep;
+        }
+        app.bundle_id
+            .as_ref()
+            .and_then(|bundle_id| self.app_ownership.get(bundle_id))
+            .copied()
+            .unwrap_or(self.ownership)
+    }
+}
+
+impl Default for TrimOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            elevated: false,
+            lipo_available: true,
+            helper: false,
+            audit: false,
+            confirmation: Confirmation::default(),
+            report_out: None,
+            ownership: Ownership::default(),
+            app_ownership: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TrimError {
+    /// The elevated trim script couldn't be run or didn't finish in time
+    Command(CommandError),
+    /// lipo or sudo exited with an error, e.g. because of a wrong password
+    LipoFailed { stderr: String },
+    /// The binary or its directory is locked with `chflags uchg` or `schg`
+    Immutable,
+    /// The binary was replaced or modified since the scan, e.g. by an update
+    Changed,
+    /// lipo isn't installed
+    LipoMissing,
+    /// Trimming was cancelled before the app was done
+    Cancelled,
+}
+
+impl fmt::Display for TrimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrimError::Command(err) => err.fmt(f),
+            TrimError::LipoFailed { stderr } => write!(f, "lipo failed: {}", stderr),
+            TrimError::Immutable => write!(
+                f,
+                "binary is locked, unlock it with `chflags nouchg` (or `noschg`) first"
+            ),
+            TrimError::LipoMissing => write!(
+                f,
+                "lipo isn't installed, install the Xcode Command Line Tools with `xcode-select --install`"
+            ),
+            TrimError::Changed => write!(
+                f,
+                "binary changed since the scan (was the app updated?), rescan and try again"
+            ),
+            TrimError::Cancelled => write!(f, "cancelled before the app was done"),
+        }
+    }
+}
+
+impl TrimError {
+    /// Might the same trim succeed if tried again in a moment, such as when the
+    /// binary was busy or sudo's timestamp file was locked?
+    pub fn is_transient(&self) -> bool {
+        match self {
+            TrimError::Command(CommandError::Io(err)) => matches!(
+                err.kind(),
+                io::ErrorKind::Interrupted
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::ResourceBusy
+            ),
+            TrimError::LipoFailed { stderr } => TRANSIENT_MARKERS
+                .iter()
+                .any(|marker| stderr.to_lowercase().contains(marker)),
+            TrimError::Command(CommandError::TimedOut(_))
+            | TrimError::Immutable
+            | TrimError::Changed
+            | TrimError::LipoMissing
+            | TrimError::Cancelled => false,
+        }
+    }
+}
+
+impl std::error::Error for TrimError {}
+
+impl From<CommandError> for TrimError {
+    fn from(err: CommandError) -> Self {
+        TrimError::Command(err)
+    }
+}
+
+/// Bytes the scan expected trimming an app to free, and how many it did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Savings {
+    pub app: String,
+    pub estimated: u64,
+    pub actual: u64,
+}
+
+impl Savings {
+    /// Does the actual result differ enough from the estimate to point at
+    /// something the scan missed, such as nested binaries it doesn't know about?
+    pub fn is_off(&self) -> bool {
+        let difference = self.actual.abs_diff(self.estimated);
+        difference >= DISCREPANCY_MIN_BYTES
+            && difference as f64 > self.estimated as f64 * DISCREPANCY_RATIO
+    }
+}
+
+/// An app that couldn't be trimmed.
+#[derive(Debug)]
+pub struct TrimFailure {
+    /// Position of the app in the batch
+    pub index: usize,
+    pub app: String,
+    pub error: TrimError,
+}
+
+/// What a finished [`trim_batch`] did: the apps that failed and what the others
+/// actually freed.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub failures: Vec<TrimFailure>,
+    pub savings: Vec<Savings>,
+    /// Every app and binary with sizes, durations and errors
+    pub record: BatchRecord,
+}
+
+/// Progress of [`trim_batch`], sent as it happens so the interface (or any other
+/// consumer) can follow along without sharing state with the worker.
+#[derive(Debug)]
+pub enum TrimEvent {
+    /// Trimming of `total` apps begins
+    BatchStarted { total: usize },
+    /// The app at `index` (counting from zero) is being trimmed
+    AppStarted { index: usize, name: String },
+    /// One of the app's binaries was trimmed and shrank by `bytes`
+    BinaryTrimmed {
+        app: String,
+        path: PathBuf,
+        bytes: u64,
+    },
+    /// The app couldn't be trimmed; the error itself ends up in the report
+    AppFailed { app: String, error: String },
+    /// Every app was tried
+    BatchFinished { report: BatchReport },
+}
+
+/// Does this output of an Xcode tool say that it isn't installed? Without the
+/// Command Line Tools, /usr/bin/lipo is a stub that fails with an xcrun error.
+pub fn developer_tools_missing(stderr: &str) -> bool {
+    stderr.contains("xcrun: error")
+        || stderr.contains("invalid active developer path")
+        || stderr.contains("no developer tools were found")
+}
+
+/// Can lipo be run? Scanning works without it, trimming doesn't.
+pub fn lipo_available() -> bool {
+    let Ok(executable) = std::env::current_exe() else {
+        return true;
+    };
+    match command::run(
+        Command::new(tools::lipo()).arg("-info").arg(executable),
+        None,
+        LIPO_CHECK_TIMEOUT,
+    ) {
+        Ok(output) => !developer_tools_missing(&String::from_utf8_lossy(&output.stderr)),
+        Err(CommandError::Io(err)) => err.kind() != io::ErrorKind::NotFound,
+        Err(CommandError::TimedOut(_)) => true,
+    }
+}
+
+/// Trims `apps` one after the other in a single elevated script, sending a
+/// [`TrimEvent`] for each step as the script reports it and the [`BatchReport`] at
+/// the end. A closed receiver doesn't stop the batch, `cancel` does: the script
+/// stops before the next binary and the apps it didn't finish fail with
+/// [`TrimError::Cancelled`].
+pub fn trim_batch(
+    apps: &[AppInfo],
+    password: &str,
+    options: &TrimOptions,
+    cancel: &CancelToken,
+    events: &Sender<TrimEvent>,
+) {
+    let send = |event| {
+        let _ = events.send(event);
+    };
+    send(TrimEvent::BatchStarted { total: apps.len() });
+
+    let mut batch = Batch::new(apps, send);
+    let mut operations = Vec::new();
+    for (index, app) in apps.iter().enumerate() {
+        match plan_app(index, app, options) {
+            Ok(planned) if planned.is_empty() => batch.results[index] = Some(Ok(0)),
+            Ok(planned) => {
+                batch.remaining[index] = planned.len();
+                operations.extend(planned);
+            }
+            Err(error) => batch.results[index] = Some(Err(error)),
+        }
+    }
+    // Other links and clones keep the original blocks
+    let before: Vec<_> = operations
+        .iter()
+        .map(|operation| (file_size(&operation.binary), Sharing::of(&operation.binary)))
+        .collect();
+
+    let mut trimmed = vec![false; operations.len()];
+    let mut owner_restored = vec![true; operations.len()];
+    // Every operation on the first attempt, then those that failed transiently
+    let mut to_run: Vec<usize> = (0..operations.len()).collect();
+    for attempt in 0..=MAX_RETRIES {
+        if to_run.is_empty() {
+            break;
+        }
+        if attempt > 0 {
+            let backoff = RETRY_BACKOFF * 2u32.pow(attempt - 1);
+            info!(
+                attempt,
+                binaries = to_run.len(),
+                ?backoff,
+                "retrying after a transient error"
+            );
+            std::thread::sleep(backoff);
+        }
+        let last_attempt = attempt == MAX_RETRIES;
+        let subset: Vec<Operation> = to_run.iter().map(|&op| operations[op].clone()).collect();
+        // The script only reports when a step is done, so a binary took from the
+        // previous step until then
+        let mut last_step = Instant::now();
+        let mut step_duration =
+            || millis(std::mem::replace(&mut last_step, Instant::now()).elapsed());
+        // The script counts the operations of this attempt from zero
+        let on_message = |message| match message {
+            Message::AppStarted(app) => {
+                step_duration();
+                batch.start_until(app + 1)
+            }
+            Message::Trimmed(op) => {
+                let op = to_run[op];
+                let operation = &operations[op];
+                let (size_before, sharing) = before[op];
+                let size_after = file_size(&operation.binary);
+                let bytes = sharing.freeable(size_before.saturating_sub(size_after));
+                trimmed[op] = true;
+                batch.report.record.apps[operation.app]
+                    .binaries
+                    .push(BinaryRecord {
+                        path: operation.binary.clone(),
+                        bytes_before: size_before,
+                        bytes_after: size_after,
+                        owner_restored: owner_restored[op],
+                        duration_ms: step_duration(),
+                        error: None,
+                    });
+                batch.binary_trimmed(operation, bytes);
+            }
+            Message::ChownFailed(op, stderr) => {
+                owner_restored[to_run[op]] = false;
+                warn!(binary = %operations[to_run[op]].binary.display(), %stderr, "failed to restore ownership")
+            }
+            Message::Failed(op, stderr) => {
+                let operation = &operations[to_run[op]];
+                batch.report.record.apps[operation.app]
+                    .binaries
+                    .push(BinaryRecord {
+                        path: operation.binary.clone(),
+                        bytes_before: before[to_run[op]].0,
+                        bytes_after: file_size(&operation.binary),
+                        owner_restored: true,
+                        duration_ms: step_duration(),
+                        error: Some(stderr.clone()),
+                    });
+                let error = if developer_tools_missing(&stderr) {
+                    TrimError::LipoMissing
+                } else {
+                    TrimError::LipoFailed { stderr }
+                };
+                let app = operation.app;
+                if error.is_transient() && !last_attempt {
+                    batch.retry_errors[app] = Some(error);
+                } else {
+                    batch.finish(app, Err(error));
+                }
+            }
+        };
+        let result = run_operations(&subset, password, options, cancel, on_message);
+
+        // Apps that failed transiently or that the script didn't get to
+        let cancelled = cancel.is_cancelled();
+        let mut retry = Vec::new();
+        for &op in &to_run {
+            let app = operations[op].app;
+            if trimmed[op] || batch.done[app] {
+                continue;
+            }
+            let error = batch.retry_errors[app].take().unwrap_or_else(|| {
+                if cancelled {
+                    TrimError::Cancelled
+                } else {
+                    interrupted(&result)
+                }
+            });
+            if error.is_transient() && !last_attempt && !cancelled {
+                batch.retry_errors[app] = Some(error);
+                retry.push(op);
+            } else {
+                batch.start_until(app + 1);
+                batch.finish(app, Err(error));
+            }
+        }
+        to_run = retry;
+    }
+    batch.start_until(apps.len());
+    batch.report.record.duration_ms = millis(batch.batch_started.elapsed());
+    // Root would leave the history file owned by root
+    if !options.elevated {
+        let trimmed = apps.iter().enumerate().filter(|(index, app)| {
+            app.strategy != Strategy::Skip
+                && !batch
+                    .report
+                    .failures
+                    .iter()
+                    .any(|failure| failure.index == *index)
+        });
+        if let Err(err) = TrimHistory::record(trimmed.map(|(_, app)| app.path.as_path())) {
+            warn!("failed to record the trimmed apps: {:#}", err);
+        }
+    }
+    let send = batch.send;
+    send(TrimEvent::BatchFinished {
+        report: batch.report,
+    });
+}
+
+/// Runs `operations` through the helper if it's installed or else the elevated
+/// script. Returns what the script printed to stderr, such as sudo's complaints.
+fn run_operations(
+    operations: &[Operation],
+    password: &str,
+    options: &TrimOptions,
+    cancel: &CancelToken,
+    on_message: impl FnMut(Message),
+) -> Result<String, CommandError> {
+    if options.helper {
+        daemon::run(operations, options.timeout, cancel, on_message).map(|()| String::new())
+    } else {
+        elevated::run(operations, password, options.timeout, cancel, on_message)
+            .map(|output| String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Why the script didn't finish an app, such as every app after a wrong password.
+fn interrupted(result: &Result<String, CommandError>) -> TrimError {
+    match result {
+        Ok(stderr) => TrimError::LipoFailed {
+            stderr: stderr.clone(),
+        },
+        Err(CommandError::TimedOut(timeout)) => {
+            TrimError::Command(CommandError::TimedOut(*timeout))
+        }
+        Err(CommandError::Io(err)) => TrimError::Command(CommandError::Io(io::Error::new(
+            err.kind(),
+            err.to_string(),
+        ))),
+    }
+}
+
+/// The part of [`trim_batch`] that follows the script's progress.
+struct Batch<'a, S: Fn(TrimEvent)> {
+    apps: &'a [AppInfo],
+    send: S,
+    /// Results known before the script runs, of skipped apps and the ones that
+    /// can't be trimmed
+    results: Vec<Option<Result<u64, TrimError>>>,
+    /// Binaries of each app the script hasn't trimmed yet
+    remaining: Vec<usize>,
+    freed: Vec<u64>,
+    done: Vec<bool>,
+    /// Transient errors of apps waiting to be retried
+    retry_errors: Vec<Option<TrimError>>,
+    /// Apps reported as started
+    started: usize,
+    /// When each started app was reported as started
+    started_at: Vec<Option<Instant>>,
+    batch_started: Instant,
+    report: BatchReport,
+}
+
+impl<'a, S: Fn(TrimEvent)> Batch<'a, S> {
+    fn new(apps: &'a [AppInfo], send: S) -> Self {
+        Self {
+            apps,
+            send,
+            results: apps.iter().map(|_| None).collect(),
+            remaining: vec![0; apps.len()],
+            freed: vec![0; apps.len()],
+            done: vec![false; apps.len()],
+            retry_errors: apps.iter().map(|_| None).collect(),
+            started: 0,
+            started_at: vec![None; apps.len()],
+            batch_started: Instant::now(),
+            report: BatchReport {
+                record: BatchRecord::new(apps),
+                ..BatchReport::default()
+            },
+        }
+    }
+
+    /// Reports the apps before `end` as started, finishing those whose result was
+    /// known beforehand.
+    fn start_until(&mut self, end: usize) {
+        while self.started < end {
+            let index = self.started;
+            self.started += 1;
+            self.started_at[index] = Some(Instant::now());
+            (self.send)(TrimEvent::AppStarted {
+                index,
+                name: self.apps[index].name.clone(),
+            });
+            if let Some(result) = self.results[index].take() {
+                self.finish(index, result);
+            }
+        }
+    }
+
+    fn binary_trimmed(&mut self, operation: &Operation, bytes: u64) {
+        let app = &self.apps[operation.app];
+        info!(app = %app.name, binary = %operation.binary.display(), bytes, "trimmed");
+        (self.send)(TrimEvent::BinaryTrimmed {
+            app: app.name.clone(),
+            path: operation.binary.clone(),
+            bytes,
+        });
+        self.freed[operation.app] += bytes;
+        self.remaining[operation.app] -= 1;
+        if self.remaining[operation.app] == 0 {
+            self.finish(operation.app, Ok(self.freed[operation.app]));
+        }
+    }
+
+    fn finish(&mut self, index: usize, result: Result<u64, TrimError>) {
+        if std::mem::replace(&mut self.done[index], true) {
+            return;
+        }
+        let app = &self.apps[index];
+        let record = &mut self.report.record.apps[index];
+        record.duration_ms = self.started_at[index].map_or(0, |started| millis(started.elapsed()));
+        match &result {
+            Ok(actual) => record.saved_bytes = *actual,
+            Err(error) => {
+                record.error = Some(error.to_string());
+                record.transient = error.is_transient();
+            }
+        }
+        match result {
+            Ok(actual) => self.report.savings.push(Savings {
+                app: app.name.clone(),
+                estimated: app.prune_size().unwrap_or_default().bytes(),
+                actual,
+            }),
+            Err(error) => {
+                warn!(app = %app.name, %error, "failed to trim");
+                (self.send)(TrimEvent::AppFailed {
+                    app: app.name.clone(),
+                    error: error.to_string(),
+                });
+                self.report.failures.push(TrimFailure {
+                    index,
+                    app: app.name.clone(),
+                    error,
+                });
+            }
+        }
+    }
+}
+
+/// The operations that remove the x86_64 slice from the app's main binary and its
+/// nested binaries, none if its strategy skips it. Fails if the app can't be
+/// trimmed as it is.
+fn plan_app(
+    index: usize,
+    app: &AppInfo,
+    options: &TrimOptions,
+) -> Result<Vec<Operation>, TrimError> {
+    if app.strategy == Strategy::Skip {
+        debug!(app = %app.name, "skipped by strategy");
+        return Ok(Vec::new());
+    }
+
+    if !options.lipo_available {
+        return Err(TrimError::LipoMissing);
+    }
+
+    // The slices from the scan may not describe the binary anymore
+    if app.binary_identity.is_some() && BinaryIdentity::of(&app.binary_path) != app.binary_identity
+    {
+        warn!(app = %app.name, "binary changed since the scan, skipping");
+        return Err(TrimError::Changed);
+    }
+
+    // The main executable stays universal with NestedOnly
+    let main_binary = (app.strategy != Strategy::NestedOnly).then_some(&app.binary_path);
+    main_binary
+        .into_iter()
+        .chain(&app.nested_binaries)
+        .map(|binary| {
+            // lipo would fail with a generic error, even as root
+            if flags::is_immutable(binary) {
+                return Err(TrimError::Immutable);
+            }
+            info!(app = %app.name, binary = %binary.display(), strategy = ?app.strategy, "trimming");
+            Ok(Operation {
+                app: index,
+                binary: binary.clone(),
+                lipo_args: app.strategy.lipo_args(),
+                owner: owner(app, binary, options),
+            })
+        })
+        .collect()
+}
+
+/// The owner to give `app`'s binary at `binary_path` after trimming, captured
+/// before lipo replaces the file. As root (e.g. with --all-users) the current user
+/// is root, so the owner is kept either way, and the helper only ever restores
+/// it. Volumes without owners, such as exFAT drives, would only fail the chown.
+fn owner(app: &AppInfo, binary_path: &Path, options: &TrimOptions) -> Option<(u32, u32)> {
+    if VolumeStats::for_path(binary_path).is_some_and(|volume| !volume.keeps_owners) {
+        return None;
+    }
+    match options.ownership(app, binary_path) {
+        Ownership::Skip => None,
+        Ownership::CurrentUser if !options.elevated && !options.helper => {
+            Some(unsafe { (libc::getuid(), libc::getgid()) })
+        }
+        _ => std::fs::metadata(binary_path)
+            .ok()
+            .map(|metadata| (metadata.uid(), metadata.gid())),
+    }
+}
+
+/// Size of a binary, with a missing file counting as empty.
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_binary() {
+        let binary = std::env::temp_dir().join(format!("bintrim-changed-{}", std::process::id()));
+        std::fs::write(&binary, [0u8; 100]).unwrap();
+        let mut app = AppInfo::for_test("Slack", &[("x86_64", None), ("arm64", None)]);
+        app.binary_path = binary.clone();
+        app.binary_identity = BinaryIdentity::of(&binary);
+
+        // An update replaces the binary with a different one
+        std::fs::remove_file(&binary).unwrap();
+        std::fs::write(&binary, [0u8; 200]).unwrap();
+        let result = plan_app(0, &app, &TrimOptions::default());
+        std::fs::remove_file(&binary).unwrap();
+
+        assert!(matches!(result, Err(TrimError::Changed)));
+    }
+
+    #[test]
+    fn test_trim_batch_events() {
+        let mut skipped = AppInfo::for_test("Slack", &[("x86_64", None), ("arm64", None)]);
+        skipped.strategy = Strategy::Skip;
+        let missing = AppInfo::for_test("zoom.us", &[("x86_64", None), ("arm64", None)]);
+        let options = TrimOptions {
+            lipo_available: false,
+            ..TrimOptions::default()
+        };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        trim_batch(
+            &[skipped, missing],
+            "",
+            &options,
+            &CancelToken::default(),
+            &sender,
+        );
+        let events: Vec<_> = receiver.try_iter().collect();
+
+        assert!(matches!(events[0], TrimEvent::BatchStarted { total: 2 }));
+        assert!(matches!(events[1], TrimEvent::AppStarted { index: 0, .. }));
+        assert!(matches!(events[2], TrimEvent::AppStarted { index: 1, .. }));
+        assert!(matches!(&events[3], TrimEvent::AppFailed { app, .. } if app == "zoom.us"));
+        let TrimEvent::BatchFinished { report } = &events[4] else {
+            panic!("expected the report last, got {:?}", events[4]);
+        };
+        assert_eq!(report.savings.len(), 1);
+        assert_eq!(report.savings[0].actual, 0);
+        assert!(matches!(report.failures[0].error, TrimError::LipoMissing));
+        assert_eq!(events.len(), 5);
+        assert_eq!(report.record.apps.len(), 2);
+        assert!(report.record.apps[0].error.is_none());
+        assert!(report.record.apps[1].error.is_some());
+    }
+
+    #[test]
+    fn test_ownership() {
+        let mut slack = AppInfo::for_test("Slack", &[("x86_64", None), ("arm64", None)]);
+        slack.bundle_id = Some("com.tinyspeck.slackmacgap".to_string());
+        let mut options = TrimOptions {
+            ownership: Ownership::CurrentUser,
+            ..TrimOptions::default()
+        };
+        options
+            .app_ownership
+            .insert("com.tinyspeck.slackmacgap".to_string(), Ownership::Skip);
+        let zoom = AppInfo::for_test("zoom.us", &[("x86_64", None), ("arm64", None)]);
+
+        assert_eq!(
+            options.ownership(&slack, &slack.binary_path),
+            Ownership::Skip
+        );
+        assert_eq!(
+            options.ownership(&zoom, &zoom.binary_path),
+            Ownership::CurrentUser
+        );
+        // A privileged helper tool trimmed with its app
+        let tool = Path::new("/Library/PrivilegedHelperTools/us.zoom.ZoomDaemon");
+        assert_eq!(options.ownership(&zoom, tool), Ownership::Keep);
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let lipo_failed = |stderr: &str| TrimError::LipoFailed {
+            stderr: stderr.to_string(),
+        };
+        assert!(lipo_failed("can't move temporary file: Resource busy").is_transient());
+        assert!(lipo_failed("sudo: unable to lock time stamp file").is_transient());
+        assert!(!lipo_failed("Sorry, try again.").is_transient());
+        assert!(
+            TrimError::Command(CommandError::Io(io::ErrorKind::Interrupted.into())).is_transient()
+        );
+        assert!(!TrimError::Command(CommandError::TimedOut(DEFAULT_TIMEOUT)).is_transient());
+        assert!(!TrimError::Immutable.is_transient());
+    }
+
+    #[test]
+    fn test_savings_is_off() {
+        let savings = |estimated, actual| Savings {
+            app: "Slack".to_string(),
+            estimated,
+            actual,
+        };
+        const MB: u64 = 1024 * 1024;
+        assert!(!savings(100 * MB, 95 * MB).is_off());
+        assert!(savings(100 * MB, 80 * MB).is_off());
+        // Nested binaries freed more than expected
+        assert!(savings(100 * MB, 130 * MB).is_off());
+        // Tiny apps aren't flagged for a few hundred KB
+        assert!(!savings(MB / 2, MB / 4).is_off());
+    }
+}
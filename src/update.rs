@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::command;
+use crate::state;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/ecklf/bintrim/releases/latest";
+/// How long a check result is reused before asking GitHub again.
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of the last update check, stored next to the UI state.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CheckCache {
+    /// Seconds since the Unix epoch
+    checked_at: u64,
+    latest: String,
+}
+
+/// Latest released version if it's newer than this build, checking at most once
+/// a day. Any failure is only logged, the notice just doesn't show up.
+pub fn available_update() -> Option<String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let cached = cache_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| toml::from_str::<CheckCache>(&contents).ok())
+        .filter(|cache| now.saturating_sub(cache.checked_at) < CHECK_INTERVAL.as_secs());
+
+    let latest = match cached {
+        Some(cache) => cache.latest,
+        None => {
+            let latest = fetch_latest()?;
+            if let Some(path) = cache_path() {
+                let cache = CheckCache {
+                    checked_at: now,
+                    latest: latest.clone(),
+                };
+                if let Some(dir) = path.parent() {
+                    let _ = fs::create_dir_all(dir);
+                }
+                if let Err(err) = fs::write(&path, toml::to_string(&cache).ok()?) {
+                    debug!(%err, "failed to cache update check");
+                }
+            }
+            latest
+        }
+    };
+
+    is_newer(&latest, env!("CARGO_PKG_VERSION")).then_some(latest)
+}
+
+fn fetch_latest() -> Option<String> {
+    let output = command::run(
+        Command::new("curl")
+            .arg("-fsSL")
+            .arg("-H")
+            .arg("Accept: application/vnd.github+json")
+            .arg(LATEST_RELEASE_URL),
+        None,
+        CHECK_TIMEOUT,
+    );
+    match output {
+        Ok(output) if output.status.success() => parse_release(&output.stdout),
+        Ok(output) => {
+            debug!(status = %output.status, "update check failed");
+            None
+        }
+        Err(err) => {
+            debug!(%err, "update check failed");
+            None
+        }
+    }
+}
+
+/// Version from the `tag_name` of a GitHub release, without the `v` prefix.
+fn parse_release(json: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Release {
+        tag_name: String,
+    }
+
+    let release: Release = serde_json::from_slice(json).ok()?;
+    Some(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// Compares dotted version numbers, ignoring anything that isn't a number.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(latest) > parse(current)
+}
+
+fn cache_path() -> Option<PathBuf> {
+    state::state_dir().map(|dir| dir.join("update-check.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_release() {
+        let json = br#"{"tag_name": "v0.3.0", "name": "bintrim 0.3.0", "draft": false}"#;
+        assert_eq!(parse_release(json), Some("0.3.0".to_string()));
+        assert_eq!(parse_release(b"{}"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.3.0", "0.2.9"));
+        assert!(is_newer("0.10.0", "0.9.0"));
+        assert!(is_newer("1.0.0", "0.1.0"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-beta", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use tracing::trace;
+
+use crate::command;
+use crate::scanner::AppInfo;
+use crate::tools;
+
+/// Prunable apps and savings of one vendor, for the grouped vendor view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VendorSummary {
+    /// `None` for apps whose vendor couldn't be determined
+    pub name: Option<String>,
+    pub apps: usize,
+    pub prune_bytes: u64,
+}
+
+/// Who made the app: the developer in its code signature, or the organization in
+/// its bundle identifier for unsigned and ad-hoc signed apps.
+pub fn vendor(app_path: &Path, bundle_id: Option<&str>, timeout: Duration) -> Option<String> {
+    // codesign writes the details to stderr
+    let signer = command::run(
+        Command::new(tools::codesign())
+            .arg("-dv")
+            .arg("--verbose=2")
+            .arg(app_path),
+        None,
+        timeout,
+    )
+    .ok()
+    .and_then(|output| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        trace!(app = %app_path.display(), %stderr, "codesign -dv");
+        parse_codesign(&stderr)
+    });
+
+    signer.or_else(|| bundle_id.and_then(bundle_id_vendor))
+}
+
+/// The developer's name from the leaf certificate, e.g. `Microsoft Corporation` from
+/// `Authority=Developer ID Application: Microsoft Corporation (UBF8T346G9)`, or the
+/// Team ID when the certificate doesn't name it, as for App Store apps.
+fn parse_codesign(output: &str) -> Option<String> {
+    let mut team_id = None;
+    for line in output.lines() {
+        if let Some(authority) = line.strip_prefix("Authority=") {
+            // The leaf certificate comes first, the CA chain follows
+            if let Some((_, developer)) = authority.split_once(": ") {
+                let name = developer
+                    .rsplit_once(" (")
+                    .map_or(developer, |(name, _)| name);
+                return Some(name.to_string());
+            }
+            if authority == "Software Signing" {
+                return Some("Apple".to_string());
+            }
+        } else if let Some(id) = line.strip_prefix("TeamIdentifier=")
+            && id != "not set"
+        {
+            team_id = Some(id.to_string());
+        }
+    }
+    team_id
+}
+
+/// `microsoft` from `com.microsoft.Word`.
+fn bundle_id_vendor(bundle_id: &str) -> Option<String> {
+    let mut parts = bundle_id.split('.');
+    let first = parts.next()?;
+    let second = parts.next().filter(|part| !part.is_empty())?;
+    // Reverse-DNS identifiers put the organization second
+    Some(if first.len() <= 3 { second } else { first }.to_string())
+}
+
+/// Groups the prunable apps by vendor, the largest savings first.
+pub fn summarize(apps: &[AppInfo]) -> Vec<VendorSummary> {
+    let mut vendors: HashMap<Option<&str>, VendorSummary> = HashMap::new();
+    for app in apps.iter().filter(|app| app.is_prunable()) {
+        let summary = vendors
+            .entry(app.vendor.as_deref())
+            .or_insert_with(|| VendorSummary {
+                name: app.vendor.clone(),
+                apps: 0,
+                prune_bytes: 0,
+            });
+        summary.apps += 1;
+        summary.prune_bytes += app.prune_size().unwrap_or_default().bytes();
+    }
+
+    let mut summaries: Vec<_> = vendors.into_values().collect();
+    summaries.sort_by(|a, b| {
+        b.prune_bytes
+            .cmp(&a.prune_bytes)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codesign() {
+        let developer_id =
+            "Executable=/Applications/Microsoft Word.app/Contents/MacOS/Microsoft Word
+Identifier=com.microsoft.Word
+Format=app bundle with Mach-O universal (x86_64 arm64)
+Authority=Developer ID Application: Microsoft Corporation (UBF8T346G9)
+Authority=Developer ID Certification Authority
+Authority=Apple Root CA
+TeamIdentifier=UBF8T346G9
+";
+        assert_eq!(
+            parse_codesign(developer_id).as_deref(),
+            Some("Microsoft Corporation")
+        );
+
+        let app_store = "Authority=Apple Mac OS Application Signing
+Authority=Apple Worldwide Developer Relations Certification Authority
+Authority=Apple Root CA
+TeamIdentifier=57T9237FN3
+";
+        assert_eq!(parse_codesign(app_store).as_deref(), Some("57T9237FN3"));
+
+        let ad_hoc = "Signature=adhoc\nTeamIdentifier=not set\n";
+        assert_eq!(parse_codesign(ad_hoc), None);
+    }
+
+    #[test]
+    fn test_bundle_id_vendor() {
+        assert_eq!(
+            bundle_id_vendor("com.microsoft.Word").as_deref(),
+            Some("microsoft")
+        );
+        assert_eq!(bundle_id_vendor("Slack"), None);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut apps: Vec<_> = [
+            ("Word", Some("Microsoft Corporation"), 300),
+            ("Excel", Some("Microsoft Corporation"), 200),
+            ("Slack", Some("Slack Technologies, Inc."), 400),
+            ("Tool", None, 10),
+        ]
+        .into_iter()
+        .map(|(name, vendor, prune_bytes)| {
+            let mut app = AppInfo::for_test(name, &[("x86_64", None), ("arm64", None)]);
+            app.vendor = vendor.map(str::to_string);
+            app.prune_bytes = Some(prune_bytes);
+            app
+        })
+        .collect();
+        apps.push(AppInfo::for_test("Native", &[("arm64", None)]));
+
+        let summaries = summarize(&apps);
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|summary| (summary.name.as_deref(), summary.apps, summary.prune_bytes))
+                .collect::<Vec<_>>(),
+            [
+                (Some("Microsoft Corporation"), 2, 500),
+                (Some("Slack Technologies, Inc."), 1, 400),
+                (None, 1, 10),
+            ]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::app::{self, ListFilter};
+use crate::bytesize::ByteSize;
+use crate::scanner::AppInfo;
+use crate::stats::ArchStats;
+
+/// What the list and the summary show, worked out once when the apps, the
+/// selection, the sort order or the filters change rather than on every frame,
+/// which keeps the interface responsive with thousands of apps.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct View {
+    /// Indices of the apps the list shows, in order
+    pub visible: Vec<usize>,
+    /// See [`app::hidden_note`]
+    pub hidden_note: Option<String>,
+    pub prunable: usize,
+    pub prunable_size: ByteSize,
+    pub selected: usize,
+    pub selected_size: ByteSize,
+    /// Whether a filter narrows the list down, which the `visible_` totals
+    /// below are for
+    pub filtered: bool,
+    pub visible_prunable: usize,
+    pub visible_prunable_size: ByteSize,
+    pub visible_selected: usize,
+    pub visible_selected_size: ByteSize,
+    /// See [`ArchStats::summary`]
+    pub arch_summary: String,
+}
+
+impl View {
+    pub fn of(apps: &[AppInfo], filter: ListFilter) -> Self {
+        let mut view = View {
+            visible: apps
+                .iter()
+                .enumerate()
+                .filter(|(_, app)| app::is_visible(app, filter))
+                .map(|(index, _)| index)
+                .collect(),
+            hidden_note: app::hidden_note(apps, filter),
+            filtered: filter.filter.is_some_and(|filter| filter.is_active()),
+            arch_summary: ArchStats::of(apps).summary(),
+            ..View::default()
+        };
+        for app in view.visible.iter().map(|&index| &apps[index]) {
+            if !app.is_prunable() {
+                continue;
+            }
+            let size = app.prune_size().unwrap_or_default();
+            view.visible_prunable += 1;
+            view.visible_prunable_size += size;
+            if app.selected {
+                view.visible_selected += 1;
+                view.visible_selected_size += size;
+            }
+        }
+        for app in apps.iter().filter(|app| app.is_prunable()) {
+            let size = app.prune_size().unwrap_or_default();
+            view.prunable += 1;
+            view.prunable_size += size;
+            if app.selected {
+                view.selected += 1;
+                view.selected_size += size;
+            }
+        }
+        view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::AppFilter;
+
+    #[test]
+    fn test_view() {
+        let mut apps = vec![
+            AppInfo::for_test("Notes", &[("arm64", None)]),
+            AppInfo::for_test("Slack", &[("x86_64", Some(100)), ("arm64", None)]),
+            AppInfo::for_test("Zoom", &[("x86_64", Some(50)), ("arm64", None)]),
+        ];
+        apps[2].selected = true;
+        let view = View::of(&apps, ListFilter::default());
+        assert_eq!(view.visible, [1, 2]);
+        assert!(view.hidden_note.is_some());
+        assert_eq!((view.prunable, view.prunable_size), (2, ByteSize(150)));
+        assert_eq!((view.selected, view.selected_size), (1, ByteSize(50)));
+        assert_eq!(view.arch_summary, ArchStats::of(&apps).summary());
+
+        let all = ListFilter {
+            show_all: true,
+            ..ListFilter::default()
+        };
+        let view = View::of(&apps, all);
+        assert_eq!(view.visible, [0, 1, 2]);
+        assert_eq!(view.hidden_note, None);
+        assert!(!view.filtered);
+        assert_eq!(
+            (view.visible_prunable, view.visible_prunable_size),
+            (2, ByteSize(150))
+        );
+
+        // A filter narrows the visible totals but not the overall ones
+        let filter = AppFilter::parse("zoom").unwrap();
+        let view = View::of(
+            &apps,
+            ListFilter {
+                filter: Some(&filter),
+                ..ListFilter::default()
+            },
+        );
+        assert!(view.filtered);
+        assert_eq!((view.prunable, view.prunable_size), (2, ByteSize(150)));
+        assert_eq!(
+            (view.visible_prunable, view.visible_prunable_size),
+            (1, ByteSize(50))
+        );
+        assert_eq!(
+            (view.visible_selected, view.visible_selected_size),
+            (1, ByteSize(50))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Filesystem-level information about the volume a path lives on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeStats {
+    /// Bytes available to unprivileged users
+    pub free_bytes: u64,
+    /// Is the volume mounted read-only (e.g. a disk image)?
+    pub read_only: bool,
+    /// Is the volume a network share (SMB, AFP, NFS, ...)?
+    pub network: bool,
+    /// Does the volume keep the owners of files? FAT and exFAT have none, and
+    /// external drives are often mounted with owners ignored.
+    pub keeps_owners: bool,
+}
+
+/// `statfs` flags from `<sys/mount.h>`, which libc only has in part.
+const MNT_LOCAL: u32 = 0x0000_1000;
+const MNT_IGNORE_OWNERSHIP: u32 = 0x0020_0000;
+
+impl VolumeStats {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+
+        let mount = Mount::of(&c_path);
+        Some(Self {
+            free_bytes: stat.f_bavail as u64 * stat.f_frsize as u64,
+            read_only: stat.f_flag & libc::ST_RDONLY != 0,
+            network: mount.as_ref().is_some_and(Mount::is_network),
+            keeps_owners: mount.as_ref().is_none_or(Mount::keeps_owners),
+        })
+    }
+}
+
+/// How a volume is mounted, from `statfs`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct Mount {
+    /// Such as `apfs`, `hfs`, `exfat` or `msdos`
+    fs_type: String,
+    flags: u32,
+}
+
+impl Mount {
+    #[cfg(target_os = "macos")]
+    fn of(c_path: &CString) -> Option<Self> {
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        let fs_type = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+        Some(Self {
+            fs_type: fs_type.to_string_lossy().into_owned(),
+            flags: stat.f_flags,
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn of(_c_path: &CString) -> Option<Self> {
+        None
+    }
+
+    fn is_network(&self) -> bool {
+        self.flags & MNT_LOCAL == 0
+    }
+
+    /// HFS+ and case-sensitive APFS keep owners like APFS does, unless mounted
+    /// with owners ignored.
+    fn keeps_owners(&self) -> bool {
+        self.flags & MNT_IGNORE_OWNERSHIP == 0
+            && !matches!(self.fs_type.as_str(), "msdos" | "exfat")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_path() {
+        let stats = VolumeStats::for_path(&std::env::temp_dir()).unwrap();
+        assert!(!stats.read_only);
+        assert!(VolumeStats::for_path(Path::new("/nonexistent/bintrim")).is_none());
+    }
+
+    #[test]
+    fn test_keeps_owners() {
+        let mount = |fs_type: &str, flags| Mount {
+            fs_type: fs_type.to_string(),
+            flags,
+        };
+        assert!(mount("apfs", 0).keeps_owners());
+        assert!(mount("hfs", 0).keeps_owners());
+        assert!(!mount("hfs", MNT_IGNORE_OWNERSHIP).keeps_owners());
+        assert!(!mount("exfat", 0).keeps_owners());
+        assert!(!mount("msdos", 0).keeps_owners());
+    }
+}
@@ -0,0 +1,128 @@
+use std::any::Any;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::thread::{self, JoinHandle};
+
+use tracing::error;
+
+/// Names of the background threads spawned through [`Supervisor::spawn`].
+const WORKER_NAMES: &[&str] = &["scanner", "trimmer", "breakdown"];
+
+/// A worker that panicked: its name and the panic message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crash {
+    pub worker: &'static str,
+    pub message: String,
+}
+
+/// Owns the handles of the background threads so none of them is left detached,
+/// and reports the ones that died. The UI polls [`Supervisor::reap`] on every tick.
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    workers: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Supervisor {
+    /// Spawns a named background thread whose panics are reported by
+    /// [`Supervisor::reap`] instead of tearing down the terminal.
+    pub fn spawn<F>(&mut self, name: &'static str, f: F) -> io::Result<()>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        debug_assert!(WORKER_NAMES.contains(&name));
+        let handle = thread::Builder::new().name(name.to_string()).spawn(f)?;
+        self.workers.push((name, handle));
+        Ok(())
+    }
+
+    /// Is a worker with this name still running?
+    pub fn is_running(&self, name: &str) -> bool {
+        self.workers
+            .iter()
+            .any(|(worker, handle)| *worker == name && !handle.is_finished())
+    }
+
+    /// Joins the workers that have finished and returns those that panicked.
+    pub fn reap(&mut self) -> Vec<Crash> {
+        let (finished, running) = std::mem::take(&mut self.workers)
+            .into_iter()
+            .partition(|(_, handle)| handle.is_finished());
+        self.workers = running;
+        finished
+            .into_iter()
+            .filter_map(|(worker, handle): (&'static str, JoinHandle<()>)| {
+                let payload = handle.join().err()?;
+                Some(Crash {
+                    worker,
+                    message: panic_message(&*payload),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Wraps the current panic hook so that panics in worker threads are only logged.
+///
+/// The hook installed by `ratatui::init` restores the terminal, which is right for
+/// the UI thread but leaves a running TUI unusable when a worker panics. Worker
+/// panics are surfaced by the UI instead.
+pub fn install_panic_hook() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(
+        move |info: &PanicHookInfo| match thread::current().name() {
+            Some(name) if WORKER_NAMES.contains(&name) => {
+                error!(thread = name, "worker panicked: {}", info);
+            }
+            _ => previous_hook(info),
+        },
+    ));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait(supervisor: &Supervisor) {
+        while WORKER_NAMES.iter().any(|name| supervisor.is_running(name)) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_supervisor() {
+        let mut supervisor = Supervisor::default();
+        supervisor.spawn("scanner", || {}).unwrap();
+        supervisor
+            .spawn("trimmer", || panic!("lipo exploded"))
+            .unwrap();
+        wait(&supervisor);
+
+        assert_eq!(
+            supervisor.reap(),
+            [Crash {
+                worker: "trimmer",
+                message: "lipo exploded".to_string()
+            }]
+        );
+        assert!(supervisor.workers.is_empty());
+        assert!(supervisor.reap().is_empty());
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&"owned".to_string()), "owned");
+        assert_eq!(panic_message(&42), "unknown panic");
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scanner;
+
+pub const XCODE_BUNDLE_ID: &str = "com.apple.dt.Xcode";
+
+/// Directories in `Contents` with command line tools that only ever run on this
+/// Mac, so their x86_64 slices are dead weight. Only their direct children are
+/// trimmed.
+const TOOL_DIRS: [&str; 2] = [
+    "Developer/Toolchains/XcodeDefault.xctoolchain/usr/bin",
+    "Developer/usr/bin",
+];
+
+/// Why part of Xcode must keep every slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    /// SDKs, simulator runtimes and device support, used to build and run Intel code
+    TargetCode,
+    /// Swift runtimes and compiler libraries linked into x86_64 builds
+    TargetLibraries,
+    /// Loaded by Xcode itself and covered by its integrity checks
+    Integrity,
+}
+
+/// Parts of `Contents` that are never trimmed.
+pub const PROTECTED: [(&str, Protection); 4] = [
+    ("Developer/Platforms", Protection::TargetCode),
+    (
+        "Developer/Toolchains/XcodeDefault.xctoolchain/usr/lib",
+        Protection::TargetLibraries,
+    ),
+    ("Frameworks", Protection::Integrity),
+    ("SharedFrameworks", Protection::Integrity),
+];
+
+/// Toolchain binaries that can be trimmed along with Xcode's main binary.
+#[derive(Debug, Default, PartialEq)]
+pub struct XcodePlan {
+    /// Absolute paths with the bytes trimming each one frees
+    pub binaries: Vec<(PathBuf, u64)>,
+}
+
+impl XcodePlan {
+    pub fn savings(&self) -> u64 {
+        self.binaries.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+pub fn is_xcode(bundle_id: Option<&str>) -> bool {
+    bundle_id == Some(XCODE_BUNDLE_ID)
+}
+
+/// Finds the universal tools in [`TOOL_DIRS`] of the Xcode at `app_path`.
+pub fn plan(app_path: &Path) -> XcodePlan {
+    let mut binaries = Vec::new();
+    for dir in TOOL_DIRS {
+        let Ok(entries) = fs::read_dir(app_path.join("Contents").join(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Many tools are symlinks to clang, swift-frontend and friends
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            if let Some(savings) = scanner::nested_savings(&path) {
+                binaries.push((path, savings));
+            }
+        }
+    }
+    binaries.sort();
+    XcodePlan { binaries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fat(path: &Path, cpu_types: &[u32]) {
+        let mut contents = Vec::new();
+        contents.extend(0xcafe_babeu32.to_be_bytes());
+        contents.extend((cpu_types.len() as u32).to_be_bytes());
+        for (index, cpu_type) in cpu_types.iter().enumerate() {
+            contents.extend(cpu_type.to_be_bytes());
+            contents.extend(0u32.to_be_bytes());
+            contents.extend((16384 * (index as u32 + 1)).to_be_bytes());
+            contents.extend(1000u32.to_be_bytes());
+            contents.extend(14u32.to_be_bytes());
+        }
+        contents.resize(16384 * (cpu_types.len() + 1), 0);
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_plan() {
+        let xcode = std::env::temp_dir().join(format!("bintrim-xcode-{}.app", std::process::id()));
+        let _ = fs::remove_dir_all(&xcode);
+        let bin = xcode.join("Contents").join(TOOL_DIRS[0]);
+        let platforms = xcode.join("Contents/Developer/Platforms");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(&platforms).unwrap();
+        write_fat(&bin.join("clang"), &[0x0100_0007, 0x0100_000c]);
+        write_fat(&bin.join("arm64-only"), &[0x0100_000c]);
+        std::os::unix::fs::symlink(bin.join("clang"), bin.join("clang++")).unwrap();
+        // Universal, but outside the tool directories
+        write_fat(&platforms.join("libSDK.dylib"), &[0x0100_0007, 0x0100_000c]);
+
+        let plan = plan(&xcode);
+        fs::remove_dir_all(&xcode).unwrap();
+
+        assert_eq!(
+            plan.binaries
+                .iter()
+                .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["clang"]
+        );
+        assert!(plan.savings() > 0);
+    }
+}